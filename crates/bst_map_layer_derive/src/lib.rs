@@ -1,10 +1,96 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Ident, Type, Variant};
+
+/// The maximum number of layers that fit into a `LayerMask`
+const MAX_LAYERS: usize = 64;
+
+/// The settings declared on a variant with `#[layer(...)]`
+#[derive(Default)]
+struct LayerAttributes {
+    storage: Option<Ident>,
+    default: Option<Expr>,
+}
+
+impl LayerAttributes {
+    /// Parses every `#[layer(...)]` attribute on the given variant
+    fn parse(variant: &Variant) -> syn::Result<LayerAttributes> {
+        let mut attributes = LayerAttributes::default();
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("layer"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    attributes.default = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                let storage = if meta.path.is_ident("dense") {
+                    "Dense"
+                } else if meta.path.is_ident("sparse") {
+                    "Sparse"
+                } else if meta.path.is_ident("palette") {
+                    "Palette"
+                } else {
+                    return Err(meta
+                        .error("expected one of `dense`, `sparse`, `palette` or `default = ...`"));
+                };
+                if attributes.storage.is_some() {
+                    return Err(meta.error("a layer can only declare one storage"));
+                }
+                attributes.storage = Some(Ident::new(storage, meta.path.span()));
+                Ok(())
+            })?;
+        }
+        if attributes.default.is_some() && attributes.storage.is_none() {
+            attributes.storage = Some(Ident::new("Dense", variant.ident.span()));
+        }
+        if let (Some(storage), Some(_)) = (&attributes.storage, &attributes.default) {
+            if storage == "Sparse" {
+                return Err(syn::Error::new(
+                    storage.span(),
+                    "sparse layers can not declare a default",
+                ));
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// Parses the `TileData` declared on the enum with `#[layer(tile_data = ...)]`
+fn parse_tile_data(attrs: &[Attribute]) -> syn::Result<Option<Type>> {
+    let mut tile_data = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("layer")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("tile_data") {
+                return Err(meta.error("expected `tile_data = ...`"));
+            }
+            tile_data = Some(meta.value()?.parse()?);
+            Ok(())
+        })?;
+    }
+    Ok(tile_data)
+}
+
+/// Builds a match over the given arms of the layers that declared a setting, returning `None` for the rest
+fn match_declared_layers(
+    arms: &[proc_macro2::TokenStream],
+    variant_count: usize,
+) -> proc_macro2::TokenStream {
+    if arms.is_empty() {
+        quote! { None }
+    } else if arms.len() < variant_count {
+        quote! { match self { #(#arms)* _ => None } }
+    } else {
+        quote! { match self { #(#arms)* } }
+    }
+}
 
 // Macro taken from Bevy_xpbd
 // https://github.com/Jondolf/bevy_xpbd/blob/main/crates/bevy_xpbd_derive/src/lib.rs
-#[proc_macro_derive(MapLayer)]
+#[proc_macro_derive(MapLayer, attributes(layer))]
 pub fn derive_map_layer(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let enum_ident = input.ident;
@@ -14,37 +100,123 @@ pub fn derive_map_layer(input: TokenStream) -> TokenStream {
         _ => panic!("Only enums can automatically derive MapLayer"),
     };
 
-    assert!(variants.len() <= 32, "Reached the maximum of 32 layers");
+    assert!(
+        variants.len() <= MAX_LAYERS,
+        "Reached the maximum of {MAX_LAYERS} layers"
+    );
 
-    let to_bits = variants.iter().enumerate().map(|(index, variant)| {
-        let bits: u32 = 1 << index;
+    let tile_data = match parse_tile_data(&input.attrs) {
+        Ok(tile_data) => tile_data,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    let mut to_index = vec![];
+    let mut from_index = vec![];
+    let mut storage = vec![];
+    let mut default_tile_data = vec![];
+    let mut tile_data_eq = vec![];
+    for (index, variant) in variants.iter().enumerate() {
         assert!(
             variant.fields.is_empty(),
             "Can only derive MapLayer for enums without fields"
         );
+        let attributes = match LayerAttributes::parse(variant) {
+            Ok(attributes) => attributes,
+            Err(error) => return error.to_compile_error().into(),
+        };
         let ident = &variant.ident;
-        quote! { #enum_ident::#ident => #bits, }
-    });
+        to_index.push(quote! { #enum_ident::#ident => #index, });
+        from_index.push(quote! { #index => Some(#enum_ident::#ident), });
+        if let Some(layer_storage) = &attributes.storage {
+            storage.push(quote! {
+                #enum_ident::#ident => Some(bevy_sparse_tilemap::map::chunk::ChunkLayerStorage::#layer_storage),
+            });
+        }
+        if let Some(default) = &attributes.default {
+            if tile_data.is_none() {
+                return syn::Error::new(
+                    default.span(),
+                    "layers with a default need the tile data of the map declared with `#[layer(tile_data = ...)]` on the enum",
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
+        // Dense and palette layers are filled when they are created so they need tile data to fill them with
+        let Some(layer_storage) = attributes.storage.filter(|storage| storage != "Sparse") else {
+            continue;
+        };
+        let Some(tile_data) = &tile_data else {
+            return syn::Error::new(
+                layer_storage.span(),
+                "dense and palette layers need the tile data of the map declared with `#[layer(tile_data = ...)]` on the enum",
+            )
+            .to_compile_error()
+            .into();
+        };
+        let default = match attributes.default {
+            Some(default) => quote! { #default },
+            None => quote! { <#tile_data as ::core::default::Default>::default() },
+        };
+        default_tile_data.push(quote! {
+            #enum_ident::#ident => Some(#default),
+        });
+        if layer_storage == "Palette" {
+            tile_data_eq.push(quote! {
+                #enum_ident::#ident => Some(<#tile_data as ::core::cmp::PartialEq>::eq),
+            });
+        }
+    }
 
-    let all_bits: u32 = if variants.len() == 32 {
-        0xffffffff
-    } else {
-        (1 << variants.len()) - 1
+    let layer_count = variants.len();
+
+    let storage = match_declared_layers(&storage, variants.len());
+    let default_tile_data = match_declared_layers(&default_tile_data, variants.len());
+    let tile_data_eq = match_declared_layers(&tile_data_eq, variants.len());
+    // Defaults are checked against the declared tile data, enums without one have no defaults for any tile data
+    let map_layer_default = match tile_data {
+        Some(tile_data) => quote! {
+            impl bevy_sparse_tilemap::map::MapLayerDefault<#tile_data> for #enum_ident {
+                fn default_tile_data(&self) -> Option<#tile_data> {
+                    #default_tile_data
+                }
+
+                fn tile_data_eq(&self) -> Option<bevy_sparse_tilemap::map::chunk::TileDataEq<#tile_data>> {
+                    #tile_data_eq
+                }
+            }
+        },
+        None => quote! {
+            impl<TileData> bevy_sparse_tilemap::map::MapLayerDefault<TileData> for #enum_ident {}
+        },
     };
 
     let expanded = quote! {
         use bevy_sparse_tilemap::map::MapLayer;
         impl MapLayer for #enum_ident {
-            fn all_bits() -> u32 {
-                #all_bits
+            fn layer_count() -> usize {
+                #layer_count
             }
 
-            fn to_bits(&self) -> u32 {
+            fn to_index(&self) -> usize {
                 match self {
-                    #(#to_bits)*
+                    #(#to_index)*
+                }
+            }
+
+            fn from_index(index: usize) -> Option<Self> {
+                match index {
+                    #(#from_index)*
+                    _ => None,
                 }
             }
+
+            fn storage(&self) -> Option<bevy_sparse_tilemap::map::chunk::ChunkLayerStorage> {
+                #storage
+            }
         }
+
+        #map_layer_default
     };
 
     TokenStream::from(expanded)
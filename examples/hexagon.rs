@@ -16,7 +16,8 @@ use bevy::DefaultPlugins;
 use bevy_sparse_tilemap::hex::map_chunk_layer::HexagonChunkSettings;
 use bevy_sparse_tilemap::hex::map_data::HexMapData;
 use bevy_sparse_tilemap::hex::{
-    hex_offset_from_orientation, hex_rotation, HexTilemapBuilder, HexTilemapManager,
+    hex_offset_from_orientation, hex_rotation, HexMapShape, HexOffset, HexTilemapBuilder,
+    HexTilemapManager,
 };
 
 use bevy_sparse_tilemap::tilemap_builder::tilemap_layer_builder::TilemapLayer;
@@ -59,7 +60,7 @@ pub enum MapLayers {
     Sparse,
 }
 
-#[derive(Hash, Default, Copy, Clone, Reflect)]
+#[derive(Hash, Default, Copy, Clone, PartialEq, Reflect)]
 struct TileData(u8, u8);
 
 // Resource to hold our map entity so we can use it in systems
@@ -80,16 +81,23 @@ fn spawn_map(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
         TilemapLayer::new_dense_from_vecs(generate_random_tile_data(map_size.clone())),
         HexMapData {
             max_chunk_size: max_chunk_size,
+            orientation: HEXAGON_ORIENTATION,
+            offset: HexOffset::Odd,
+            shape: HexMapShape::Rectangle,
         },
         HexagonChunkSettings {
             orientation: HEXAGON_ORIENTATION,
+            offset: HexOffset::Odd,
             max_chunk_size,
         },
-    );
-    tilemap_builder.add_layer(
-        TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
-        MapLayers::Sparse,
-    );
+    )
+    .unwrap();
+    tilemap_builder
+        .add_layer(
+            TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
+            MapLayers::Sparse,
+        )
+        .unwrap();
 
     let Some(tilemap) = tilemap_builder.spawn_tilemap(&mut commands) else {
         return;
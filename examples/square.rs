@@ -41,7 +41,7 @@ pub enum MapLayers {
     DenseExtra,
 }
 
-#[derive(Hash, Default, Copy, Clone, Reflect)]
+#[derive(Hash, Default, Copy, Clone, PartialEq, Reflect)]
 struct TileData(u8, u8);
 
 // Resource to hold our map entity so we can use it in systems
@@ -57,23 +57,32 @@ fn spawn_map(mut commands: Commands) {
             TilemapLayer::new_dense_from_vecs(generate_random_tile_data(map_size.clone())),
             SquareMapData { max_chunk_size },
             SquareChunkSettings { max_chunk_size },
-        );
-    tilemap_builder.add_layer(
-        TilemapLayer::new_dense_from_vecs(generate_random_tile_data(map_size.clone())),
-        MapLayers::DenseExtra,
-    );
-    tilemap_builder.add_layer(
-        TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
-        MapLayers::Sparse,
-    );
-    tilemap_builder.add_layer(
-        TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
-        MapLayers::SparseTwo,
-    );
-    tilemap_builder.add_layer(
-        TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
-        MapLayers::SparseThree,
-    );
+        )
+        .unwrap();
+    tilemap_builder
+        .add_layer(
+            TilemapLayer::new_dense_from_vecs(generate_random_tile_data(map_size.clone())),
+            MapLayers::DenseExtra,
+        )
+        .unwrap();
+    tilemap_builder
+        .add_layer(
+            TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
+            MapLayers::Sparse,
+        )
+        .unwrap();
+    tilemap_builder
+        .add_layer(
+            TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
+            MapLayers::SparseTwo,
+        )
+        .unwrap();
+    tilemap_builder
+        .add_layer(
+            TilemapLayer::new_sparse_empty(map_size.x as usize, map_size.y as usize),
+            MapLayers::SparseThree,
+        )
+        .unwrap();
 
     let Some(tilemap) = tilemap_builder.spawn_tilemap(&mut commands) else {
         return;
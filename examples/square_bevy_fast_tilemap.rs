@@ -50,7 +50,7 @@ pub enum MapLayers {
 #[derive(Resource)]
 pub struct MapEntity(Entity);
 
-#[derive(Hash, Default, Copy, Clone, Reflect)]
+#[derive(Hash, Default, Copy, Clone, PartialEq, Reflect)]
 struct TileData(u8, u8);
 
 #[derive(Component, Default, Copy, Clone, Reflect)]
@@ -69,7 +69,8 @@ fn startup(mut commands: Commands) {
             TilemapLayer::new_dense_from_vecs(generate_random_tile_data(map_size.clone())),
             SquareMapData { max_chunk_size },
             SquareChunkSettings { max_chunk_size },
-        );
+        )
+        .unwrap();
 
     let Some(tilemap) = tilemap_builder.spawn_tilemap(&mut commands) else {
         return;
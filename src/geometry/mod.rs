@@ -0,0 +1,244 @@
+//! Iterators over the [`Cell`]s of lines and shapes.
+//!
+//! The iterators produce cells anywhere, including outside of a map. Clip them to a map with
+//! [`TilemapManager::clip_cells`] or pass them straight to the bulk functions of the [`TilemapManager`], such as
+//! [`TilemapManager::get_tiles`] and [`TilemapManager::set_tiles`], which skip cells outside of the map and only look up
+//! each chunk once.
+//!
+//! [`TilemapManager`]: crate::tilemap_manager::TilemapManager
+//! [`TilemapManager::clip_cells`]: crate::tilemap_manager::TilemapManager::clip_cells
+//! [`TilemapManager::get_tiles`]: crate::tilemap_manager::TilemapManager::get_tiles
+//! [`TilemapManager::set_tiles`]: crate::tilemap_manager::TilemapManager::set_tiles
+
+use lettuces::cell::Cell;
+
+#[cfg(feature = "hex")]
+use crate::hex::cell_to_hex;
+
+/// Returns the cells of the bresenham line from one [`Cell`] to another, including both of them
+pub fn line(from: Cell, to: Cell) -> impl Iterator<Item = Cell> {
+    Bresenham::new(from, to, Some(to))
+}
+
+/// Returns the cells of the bresenham line that starts at a [`Cell`] and goes on through another cell forever.
+///
+/// # Note
+/// - The ray never ends, limit it with [`Iterator::take`] or [`Iterator::take_while`] before collecting it or passing
+///   it to a bulk function
+/// - A ray through its own start only contains the start
+pub fn ray(from: Cell, through: Cell) -> impl Iterator<Item = Cell> {
+    let end = (from == through).then_some(from);
+    Bresenham::new(from, through, end)
+}
+
+/// Returns every [`Cell`] of the rectangle between two corners, including the corners, row by row
+pub fn rectangle(corner: Cell, opposite_corner: Cell) -> impl Iterator<Item = Cell> {
+    let (min, max) = corners(corner, opposite_corner);
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Cell::new(x, y)))
+}
+
+/// Returns the [`Cell`]s on the edges of the rectangle between two corners, including the corners
+pub fn rectangle_outline(corner: Cell, opposite_corner: Cell) -> impl Iterator<Item = Cell> {
+    let (min, max) = corners(corner, opposite_corner);
+    rectangle(min, max).filter(move |cell| {
+        cell.x == min.x || cell.x == max.x || cell.y == min.y || cell.y == max.y
+    })
+}
+
+/// Returns every [`Cell`] whose euclidean distance to the center is at most the radius, row by row. This is the same
+/// circle as the radius of [`SquareTilemapManager::field_of_view`](crate::square::SquareTilemapManager)
+pub fn circle(center: Cell, radius: u32) -> impl Iterator<Item = Cell> {
+    let radius = radius as i32;
+    rectangle(
+        Cell::new(center.x - radius, center.y - radius),
+        Cell::new(center.x + radius, center.y + radius),
+    )
+    .filter(move |cell| in_circle(center, radius, *cell))
+}
+
+/// Returns the [`Cell`]s of the [`circle`] that share an edge with a cell outside of it
+pub fn circle_outline(center: Cell, radius: u32) -> impl Iterator<Item = Cell> {
+    circle(center, radius).filter(move |cell| {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .any(|(x, y)| !in_circle(center, radius as i32, Cell::new(cell.x + x, cell.y + y)))
+    })
+}
+
+/// Returns the cells of the hex line from one [`Cell`] to another, including both of them. The cells are in the
+/// axial coordinates of a hexagon map
+#[cfg(feature = "hex")]
+pub fn hex_line(from: Cell, to: Cell) -> impl Iterator<Item = Cell> {
+    cell_to_hex(from).line_to(cell_to_hex(to)).map(Cell::from)
+}
+
+/// Returns the cells that are exactly `radius` hexes away from the center. The cells are in the axial coordinates
+/// of a hexagon map
+#[cfg(feature = "hex")]
+pub fn hex_ring(center: Cell, radius: u32) -> impl Iterator<Item = Cell> {
+    cell_to_hex(center).ring(radius).map(Cell::from)
+}
+
+/// Returns the cells that are at most `radius` hexes away from the center, starting at the center and going outwards
+/// ring by ring. The cells are in the axial coordinates of a hexagon map
+#[cfg(feature = "hex")]
+pub fn hex_spiral(center: Cell, radius: u32) -> impl Iterator<Item = Cell> {
+    cell_to_hex(center).spiral_range(0..=radius).map(Cell::from)
+}
+
+/// Returns the cells that are at most `radius` hexes away from the center. The cells are in the axial coordinates
+/// of a hexagon map
+#[cfg(feature = "hex")]
+pub fn hex_range(center: Cell, radius: u32) -> impl Iterator<Item = Cell> {
+    cell_to_hex(center).range(radius).map(Cell::from)
+}
+
+/// Returns the lowest and highest corner of the rectangle between two corners
+fn corners(corner: Cell, opposite_corner: Cell) -> (Cell, Cell) {
+    (
+        Cell::new(
+            corner.x.min(opposite_corner.x),
+            corner.y.min(opposite_corner.y),
+        ),
+        Cell::new(
+            corner.x.max(opposite_corner.x),
+            corner.y.max(opposite_corner.y),
+        ),
+    )
+}
+
+fn in_circle(center: Cell, radius: i32, cell: Cell) -> bool {
+    (cell.x - center.x).pow(2) + (cell.y - center.y).pow(2) <= radius * radius
+}
+
+/// Steps along a bresenham line towards `target`, stopping at `end` if there is one
+struct Bresenham {
+    cell: Cell,
+    end: Option<Cell>,
+    delta: (i32, i32),
+    step: (i32, i32),
+    error: i32,
+    finished: bool,
+}
+
+impl Bresenham {
+    fn new(from: Cell, target: Cell, end: Option<Cell>) -> Self {
+        let delta = ((target.x - from.x).abs(), -(target.y - from.y).abs());
+        Self {
+            cell: from,
+            end,
+            delta,
+            step: ((target.x - from.x).signum(), (target.y - from.y).signum()),
+            error: delta.0 + delta.1,
+            finished: false,
+        }
+    }
+}
+
+impl Iterator for Bresenham {
+    type Item = Cell;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let cell = self.cell;
+        if self.end == Some(cell) {
+            self.finished = true;
+            return Some(cell);
+        }
+        let doubled_error = 2 * self.error;
+        if doubled_error >= self.delta.1 {
+            self.error += self.delta.1;
+            self.cell.x += self.step.0;
+        }
+        if doubled_error <= self.delta.0 {
+            self.error += self.delta.0;
+            self.cell.y += self.step.1;
+        }
+        Some(cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_and_rays() {
+        let cells: Vec<Cell> = line(Cell::new(0, 0), Cell::new(4, 2)).collect();
+        assert_eq!(
+            cells,
+            vec![
+                Cell::new(0, 0),
+                Cell::new(1, 1),
+                Cell::new(2, 1),
+                Cell::new(3, 2),
+                Cell::new(4, 2)
+            ]
+        );
+        assert_eq!(line(Cell::new(3, 3), Cell::new(3, 3)).count(), 1);
+        assert_eq!(line(Cell::new(2, -5), Cell::new(-3, 5)).count(), 11);
+
+        // A ray keeps going along its line
+        let cells: Vec<Cell> = ray(Cell::new(0, 0), Cell::new(2, 1)).take(5).collect();
+        assert_eq!(
+            cells[..3],
+            line(Cell::new(0, 0), Cell::new(2, 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(cells[4], Cell::new(4, 2));
+        assert_eq!(ray(Cell::new(1, 1), Cell::new(1, 1)).count(), 1);
+    }
+
+    #[test]
+    fn rectangles_and_circles() {
+        assert_eq!(rectangle(Cell::new(2, 3), Cell::new(-1, 0)).count(), 16);
+        assert_eq!(
+            rectangle_outline(Cell::new(2, 3), Cell::new(-1, 0)).count(),
+            12
+        );
+        assert_eq!(
+            rectangle_outline(Cell::new(0, 0), Cell::new(4, 0)).count(),
+            5
+        );
+
+        assert_eq!(
+            circle(Cell::new(0, 0), 0).collect::<Vec<_>>(),
+            vec![Cell::new(0, 0)]
+        );
+        assert_eq!(circle(Cell::new(0, 0), 1).count(), 5);
+        assert_eq!(circle(Cell::new(5, -5), 2).count(), 13);
+        let outline: Vec<Cell> = circle_outline(Cell::new(0, 0), 2).collect();
+        assert_eq!(outline.len(), 8);
+        assert!(!outline.contains(&Cell::new(0, 0)));
+        assert!(!outline.contains(&Cell::new(1, 0)));
+        assert!(outline.contains(&Cell::new(1, 1)));
+        assert!(outline.contains(&Cell::new(2, 0)));
+    }
+
+    #[test]
+    fn hex_shapes() {
+        let center = Cell::new(3, -2);
+        assert_eq!(hex_ring(center, 0).count(), 1);
+        assert_eq!(hex_ring(center, 3).count(), 18);
+        assert_eq!(hex_range(center, 3).count(), 37);
+        let spiral: Vec<Cell> = hex_spiral(center, 3).collect();
+        assert_eq!(spiral.len(), 37);
+        assert_eq!(spiral[0], center);
+        assert_eq!(spiral[1..7].len(), hex_ring(center, 1).count());
+        assert!(spiral[1..7]
+            .iter()
+            .all(|cell| hex_ring(center, 1).any(|ring_cell| ring_cell == *cell)));
+
+        let cells: Vec<Cell> = hex_line(center, Cell::new(6, -2)).collect();
+        assert_eq!(
+            cells,
+            vec![
+                Cell::new(3, -2),
+                Cell::new(4, -2),
+                Cell::new(5, -2),
+                Cell::new(6, -2)
+            ]
+        );
+    }
+}
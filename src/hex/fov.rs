@@ -0,0 +1,194 @@
+use crate::hex::{cell_to_hex, HexTilemapManager};
+use crate::map::MapLayer;
+use crate::tilemap_manager::TilemapManagerError;
+use bevy::utils::{HashMap, HashSet};
+use lettuces::cell::Cell;
+use lettuces::Hex;
+
+/// Field of view and line of sight for hexagon maps. Sight is blocked by the tiles in the current layer that the
+/// `blocks_sight` predicate returns true for and by the edges of the map
+impl<'w, 's, TileData, MapLayers> HexTilemapManager<'w, 's, TileData, MapLayers>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + Default + Clone + Copy + Send + Sync + 'static,
+{
+    /// Returns every [`Cell`] that can be seen from the origin within the given radius, including the origin and the
+    /// tiles that block sight.
+    ///
+    /// The hexes around the origin are visited ring by ring and a ray is cast to each of them, so a hex is visible
+    /// when [`has_line_of_sight`](Self::has_line_of_sight) is true for it. Tiles are read straight from the chunks
+    /// and each tile is only read once.
+    ///
+    /// # Note
+    /// - Cells without tile data don't block sight
+    pub fn field_of_view(
+        &self,
+        origin: Cell,
+        radius: u32,
+        blocks_sight: impl Fn(&TileData) -> bool,
+    ) -> Result<HashSet<Cell>, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer().to_index();
+        let mut sight_cache: HashMap<Hex, Option<bool>> = HashMap::default();
+        let mut sight = |hex: Hex| {
+            *sight_cache.entry(hex).or_insert_with(|| {
+                let cell = Cell::from(hex);
+                tile_reader.contains(cell).then(|| {
+                    tile_reader
+                        .tile_data(map_layer, cell)
+                        .is_some_and(&blocks_sight)
+                })
+            })
+        };
+        let mut visible = HashSet::new();
+        let origin = cell_to_hex(origin);
+        if sight(origin).is_none() {
+            return Ok(visible);
+        }
+        visible.insert(Cell::from(origin));
+        for range in 1..=radius {
+            for hex in origin.ring(range) {
+                if sight(hex).is_some() && ray_reaches(origin, hex, &mut sight) {
+                    visible.insert(Cell::from(hex));
+                }
+            }
+        }
+        Ok(visible)
+    }
+
+    /// Returns true if no tile that blocks sight is on the hex line between the two [`Cell`]s. The cells themselves
+    /// can block sight, only the cells between them are checked.
+    ///
+    /// # Note
+    /// - Returns false if either cell is outside of the map
+    pub fn has_line_of_sight(
+        &self,
+        from: Cell,
+        to: Cell,
+        blocks_sight: impl Fn(&TileData) -> bool,
+    ) -> Result<bool, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer().to_index();
+        let mut sight = |hex: Hex| {
+            let cell = Cell::from(hex);
+            tile_reader.contains(cell).then(|| {
+                tile_reader
+                    .tile_data(map_layer, cell)
+                    .is_some_and(&blocks_sight)
+            })
+        };
+        let (from, to) = (cell_to_hex(from), cell_to_hex(to));
+        Ok(sight(from).is_some() && sight(to).is_some() && ray_reaches(from, to, &mut sight))
+    }
+}
+
+/// Returns true if every hex between the two hexes is on the map and doesn't block sight. `sight` returns whether a
+/// hex blocks sight or `None` if it is outside of the map
+fn ray_reaches(from: Hex, to: Hex, sight: &mut impl FnMut(Hex) -> Option<bool>) -> bool {
+    let line: Vec<Hex> = from.line_to(to).collect();
+    line.len() < 3
+        || line[1..line.len() - 1]
+            .iter()
+            .all(|hex| sight(*hex) == Some(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::hex::map_chunk_layer::HexagonChunkSettings;
+    use crate::hex::map_data::HexMapData;
+    use crate::hex::{HexMapShape, HexOffset, HexTilemapBuilder, HexTilemapManager};
+    use crate::map::MapData;
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::World;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+    use lettuces::{Hex, HexOrientation};
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    #[test]
+    fn hex_field_of_view() {
+        let map_data = HexMapData {
+            max_chunk_size: UVec2::new(4, 4),
+            orientation: HexOrientation::Pointy,
+            offset: HexOffset::Odd,
+            shape: HexMapShape::Rectangle,
+        };
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, HexTilemapManager<bool, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let map_entity = HexTilemapBuilder::<bool, MapLayers>::new(
+            TilemapLayer::new_dense_default(11, 11),
+            HexMapData {
+                max_chunk_size: UVec2::new(4, 4),
+                orientation: HexOrientation::Pointy,
+                offset: HexOffset::Odd,
+                shape: HexMapShape::Rectangle,
+            },
+            HexagonChunkSettings {
+                orientation: HexOrientation::Pointy,
+                offset: HexOffset::Odd,
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        )
+        .unwrap()
+        .spawn_tilemap(&mut commands)
+        .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let origin = map_data.offset_coordinates_to_hex([5, 5]);
+        let east = |distance: i32| Cell::from(origin + Hex::new(distance, 0));
+        // A wall two hexes east of the origin
+        tilemap_manager.sets_tile_data(true, east(2)).unwrap();
+        let blocks_sight = |wall: &bool| *wall;
+
+        let visible = tilemap_manager
+            .field_of_view(Cell::from(origin), 3, blocks_sight)
+            .unwrap();
+        // The 37 hexes within 3 of the origin except the one straight behind the wall
+        assert_eq!(visible.len(), 36);
+        assert!(visible.contains(&Cell::from(origin)));
+        assert!(visible.contains(&east(1)));
+        assert!(visible.contains(&east(2)));
+        assert!(!visible.contains(&east(3)));
+        assert!(visible.contains(&Cell::from(origin + Hex::new(-3, 0))));
+
+        // The edges of the map
+        let corner = map_data.offset_coordinates_to_cell([0, 0]);
+        let visible = tilemap_manager
+            .field_of_view(corner, 2, blocks_sight)
+            .unwrap();
+        assert!(visible.iter().all(|cell| {
+            let [column, row] = map_data.cell_to_offset_coordinates(*cell);
+            column >= 0 && row >= 0
+        }));
+        assert!(visible.contains(&map_data.offset_coordinates_to_cell([2, 0])));
+
+        assert!(tilemap_manager
+            .has_line_of_sight(Cell::from(origin), east(2), blocks_sight)
+            .unwrap());
+        assert!(!tilemap_manager
+            .has_line_of_sight(Cell::from(origin), east(4), blocks_sight)
+            .unwrap());
+        assert!(tilemap_manager
+            .has_line_of_sight(
+                Cell::from(origin),
+                Cell::from(origin + Hex::new(0, 4)),
+                blocks_sight
+            )
+            .unwrap());
+        assert!(!tilemap_manager
+            .has_line_of_sight(Cell::from(origin), Cell::new(-20, 0), blocks_sight)
+            .unwrap());
+    }
+}
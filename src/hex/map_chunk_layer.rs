@@ -1,11 +1,16 @@
-use crate::map::chunk::{ChunkCell, ChunkLayer, ChunkLayerType};
+use crate::hex::{cell_to_offset_coordinates, hex_offset_mode, HexOffset};
+use crate::map::chunk::{
+    ChunkCell, ChunkLayer, ChunkLayerStorage, ChunkLayerType, ConversionPolicy, PaletteStorage,
+    TileDataEq,
+};
 use bevy::ecs::entity::{EntityMapper, MapEntities};
 use bevy::math::UVec2;
 use bevy::prelude::{Component, Entity};
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use lettuces::cell::Cell;
+use lettuces::storage::grid::Grid;
 use lettuces::storage::hex::HexRectangleStorage;
-use lettuces::HexOrientation;
+use lettuces::{HexOrientation, OffsetHexMode};
 use std::hash::{Hash, Hasher};
 
 #[cfg(feature = "serde")]
@@ -24,6 +29,8 @@ use bevy::prelude::{Reflect, ReflectComponent};
 pub struct HexagonChunkSettings {
     /// The hex orientation of the map
     pub orientation: HexOrientation,
+    /// Which rows or columns of the map are shifted
+    pub offset: HexOffset,
     /// The maximum size that a chunk can be
     pub max_chunk_size: UVec2,
 }
@@ -33,26 +40,79 @@ impl Default for HexagonChunkSettings {
         Self {
             max_chunk_size: UVec2 { x: 10, y: 10 },
             orientation: HexOrientation::default(),
+            offset: HexOffset::default(),
         }
     }
 }
 
+impl HexagonChunkSettings {
+    /// Returns the [`OffsetHexMode`] that chunks are laid out in
+    pub fn offset_mode(&self) -> OffsetHexMode {
+        hex_offset_mode(self.orientation, self.offset)
+    }
+}
+
 /// A struct that holds the chunk map data for the given layer
-#[derive(Component, Default)]
+#[derive(Component)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T: Deserialize<'de>")))]
 #[cfg_attr(feature = "reflect", derive(Reflect))]
-#[cfg_attr(feature = "reflect", reflect(Hash, Component, MapEntities))]
+#[cfg_attr(feature = "reflect", reflect(Component, MapEntities))]
 pub struct HexChunkLayer<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     layer_type_data: HexChunkLayerData<T>,
     tile_entities: HashMap<u64, Entity>,
+    orientation: HexOrientation,
+    offset: HexOffset,
+    default_tile_data: Option<T>,
+    conversion_policy: Option<ConversionPolicy>,
+    /// Whether the layer records its dirty cells, off unless enabled with `set_track_dirty_cells`
+    #[cfg_attr(feature = "serde", serde(default))]
+    track_dirty_cells: bool,
+    /// Compares tiles to the default tile data. Given along with the conversion policy or the palette
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    tile_data_eq: Option<TileDataEq<T>>,
+    /// The amount of non default tiles in a dense layer. `None` when it has to be recounted
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    filled_tiles: Option<usize>,
+    /// A tile handed out by `get_tile_data_mut` and whether it held data before, counted on the next change
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    unsettled_tile: Option<(ChunkCell, bool)>,
+    /// The cells whose data changed since the dirty cells were last cleared
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    dirty_cells: HashSet<u64>,
+}
+
+impl<T> Default for HexChunkLayer<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    fn default() -> Self {
+        Self {
+            layer_type_data: Default::default(),
+            tile_entities: Default::default(),
+            orientation: Default::default(),
+            offset: Default::default(),
+            default_tile_data: None,
+            conversion_policy: None,
+            track_dirty_cells: false,
+            tile_data_eq: None,
+            filled_tiles: None,
+            unsettled_tile: None,
+            dirty_cells: Default::default(),
+        }
+    }
 }
 
 impl<T> MapEntities for HexChunkLayer<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
         for tile_entity in self.tile_entities.iter_mut() {
@@ -63,7 +123,7 @@ where
 
 impl<T> Hash for HexChunkLayer<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Hash + Clone + Sized + Send + Sync,
 {
     fn hash<H: Hasher>(&self, h: &mut H) {
         let mut pairs: Vec<_> = self.tile_entities.iter().collect();
@@ -72,19 +132,130 @@ where
         Hash::hash(&self.layer_type_data, h);
     }
 }
+impl<T> HexChunkLayer<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    /// Records the cell as dirty if the layer tracks its dirty cells
+    fn mark_dirty(&mut self, chunk_cell: ChunkCell) {
+        if self.track_dirty_cells {
+            self.dirty_cells.insert(chunk_cell.as_u64());
+        }
+    }
+
+    /// Returns whether the tile holds data, which it does unless it is equal to the default tile data of the layer.
+    /// `None` if the layer has no default tile data or no way to compare tiles
+    fn holds_data(&self, tile_data: &T) -> Option<bool> {
+        let (Some(default_tile_data), Some(tile_data_eq)) =
+            (&self.default_tile_data, self.tile_data_eq)
+        else {
+            return None;
+        };
+        Some(!tile_data_eq(tile_data, default_tile_data))
+    }
+
+    /// Counts the change made to the tile last handed out by `get_tile_data_mut`, if the filled tiles are tracked
+    fn settle_filled_tiles(&mut self) {
+        let Some((chunk_tile_pos, was_filled)) = self.unsettled_tile.take() else {
+            return;
+        };
+        let (Some(filled_tiles), Some(holds_data)) = (
+            self.filled_tiles,
+            self.layer_type_data
+                .get_tile_data(chunk_tile_pos)
+                .and_then(|tile_data| self.holds_data(tile_data)),
+        ) else {
+            return;
+        };
+        self.filled_tiles = Some(filled_tiles + usize::from(holds_data) - usize::from(was_filled));
+    }
+
+    /// Keeps the filled tile count of a dense layer up to date with a tile that is about to be set
+    fn track_filled_tiles(&mut self, chunk_tile_pos: ChunkCell, holds_data: bool) {
+        self.settle_filled_tiles();
+        let (Some(filled_tiles), HexChunkLayerData::Dense(..)) =
+            (self.filled_tiles, &self.layer_type_data)
+        else {
+            return;
+        };
+        let Some(held_data) = self
+            .layer_type_data
+            .get_tile_data(chunk_tile_pos)
+            .and_then(|tile_data| self.holds_data(tile_data))
+        else {
+            return;
+        };
+        self.filled_tiles = Some(filled_tiles + usize::from(holds_data) - usize::from(held_data));
+    }
+
+    /// Converts the layer between sparse and dense storage if its [`ConversionPolicy`] says it should be
+    fn apply_conversion_policy(&mut self) {
+        self.settle_filled_tiles();
+        let (Some(conversion_policy), Some(default_tile_data), Some(tile_data_eq)) = (
+            self.conversion_policy,
+            &self.default_tile_data,
+            self.tile_data_eq,
+        ) else {
+            return;
+        };
+        let holds_data = |tile_data: &T| !tile_data_eq(tile_data, default_tile_data);
+        let dimensions = self.layer_type_data.get_dimensions();
+        let total_tiles = (dimensions.x * dimensions.y) as usize;
+        match &self.layer_type_data {
+            HexChunkLayerData::Sparse(layer_data, ..) => {
+                if conversion_policy.should_convert_to_dense(layer_data.len(), total_tiles) {
+                    self.layer_type_data = std::mem::take(&mut self.layer_type_data).into_dense(
+                        self.orientation,
+                        self.offset,
+                        default_tile_data,
+                    );
+                    self.filled_tiles = None;
+                }
+            }
+            HexChunkLayerData::Dense(..) => {
+                let filled_tiles = *self
+                    .filled_tiles
+                    .get_or_insert_with(|| self.layer_type_data.filled_tiles(holds_data));
+                if conversion_policy.should_convert_to_sparse(filled_tiles, total_tiles) {
+                    self.layer_type_data =
+                        std::mem::take(&mut self.layer_type_data).into_sparse(holds_data);
+                    self.filled_tiles = None;
+                }
+            }
+            // Palette layers are already compact so they are left as they are
+            HexChunkLayerData::Palette(..) => {}
+        }
+    }
+}
+
 impl<TileData> ChunkLayer<TileData> for HexChunkLayer<TileData>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    TileData: Clone + Sized + Send + Sync,
 {
     type ChunkSettings = HexagonChunkSettings;
 
     fn into_chunk_cell(cell: Cell, chunk_settings: &Self::ChunkSettings) -> ChunkCell {
-        let chunk_pos_x = cell.x / chunk_settings.max_chunk_size.x as i32;
-        let chunk_pos_y = cell.y / chunk_settings.max_chunk_size.y as i32;
-        ChunkCell::new(
-            cell.x - (chunk_pos_x * chunk_settings.max_chunk_size.x as i32),
-            cell.y - (chunk_pos_y * chunk_settings.max_chunk_size.y as i32),
-        )
+        // Chunks are laid out in offset coordinates so find the offset position inside the chunk
+        // and convert that back into the axial coordinates used by the chunks storage
+        let [column, row] = cell_to_offset_coordinates(cell, chunk_settings.offset_mode());
+        let chunk_cell = Cell::from_offset_coordinates(
+            [
+                column.rem_euclid(chunk_settings.max_chunk_size.x as i32),
+                row.rem_euclid(chunk_settings.max_chunk_size.y as i32),
+            ],
+            chunk_settings.offset_mode(),
+        );
+        ChunkCell::new(chunk_cell.x, chunk_cell.y)
+    }
+
+    fn with_max_chunk_size(
+        chunk_settings: Self::ChunkSettings,
+        max_chunk_size: UVec2,
+    ) -> Self::ChunkSettings {
+        Self::ChunkSettings {
+            max_chunk_size,
+            ..chunk_settings
+        }
     }
 
     fn new(
@@ -96,9 +267,12 @@ where
             ChunkLayerType::Dense(dense_data) => Self {
                 layer_type_data: HexChunkLayerData::new_dense_from_vecs(
                     &dense_data,
-                    settings.orientation.clone(),
+                    settings.orientation,
+                    settings.offset,
                 ),
-                tile_entities: Default::default(),
+                orientation: settings.orientation,
+                offset: settings.offset,
+                ..Default::default()
             },
             ChunkLayerType::Sparse(hashmap) => {
                 let sparse_data = hashmap
@@ -109,9 +283,22 @@ where
                     .collect();
                 HexChunkLayer {
                     layer_type_data: HexChunkLayerData::Sparse(sparse_data, chunk_dimensions),
-                    tile_entities: Default::default(),
+                    orientation: settings.orientation,
+                    offset: settings.offset,
+                    ..Default::default()
                 }
             }
+            ChunkLayerType::Palette(palette_data, tile_data_eq) => Self {
+                layer_type_data: HexChunkLayerData::Palette(
+                    PaletteStorage::new_from_vecs_with(&palette_data, tile_data_eq),
+                    settings.orientation,
+                    settings.offset,
+                ),
+                orientation: settings.orientation,
+                offset: settings.offset,
+                tile_data_eq: Some(tile_data_eq),
+                ..Default::default()
+            },
         }
     }
 
@@ -119,7 +306,27 @@ where
         self.layer_type_data.get_dimensions()
     }
 
+    fn storage(&self) -> ChunkLayerStorage {
+        match self.layer_type_data {
+            HexChunkLayerData::Sparse(..) => ChunkLayerStorage::Sparse,
+            HexChunkLayerData::Dense(..) => ChunkLayerStorage::Dense,
+            HexChunkLayerData::Palette(..) => ChunkLayerStorage::Palette,
+        }
+    }
+
     fn get_tile_data_mut(&mut self, chunk_tile_pos: ChunkCell) -> Option<&mut TileData> {
+        // Changes made through the reference are counted on the next change to the layer
+        self.settle_filled_tiles();
+        let was_filled = self
+            .layer_type_data
+            .get_tile_data(chunk_tile_pos)
+            .map(|tile_data| self.holds_data(tile_data).unwrap_or(true))?;
+        if self.filled_tiles.is_some()
+            && matches!(self.layer_type_data, HexChunkLayerData::Dense(..))
+        {
+            self.unsettled_tile = Some((chunk_tile_pos, was_filled));
+        }
+        self.mark_dirty(chunk_tile_pos);
         self.layer_type_data.get_tile_data_mut(chunk_tile_pos)
     }
 
@@ -128,42 +335,162 @@ where
     }
 
     fn set_tile_data(&mut self, chunk_tile_pos: ChunkCell, tile_data: TileData) {
+        let holds_data = self.holds_data(&tile_data).unwrap_or(true);
+        self.track_filled_tiles(chunk_tile_pos, holds_data);
+        if self
+            .layer_type_data
+            .set_tile_data(chunk_tile_pos, tile_data)
+        {
+            self.mark_dirty(chunk_tile_pos);
+        }
+        self.apply_conversion_policy();
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (ChunkCell, &TileData)> + '_> {
+        self.layer_type_data.iter()
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(ChunkCell, &mut TileData)) {
+        // Changes made through the references can't be tracked so recount on the next set
+        self.filled_tiles = None;
+        let mut dirty_cells = self.track_dirty_cells.then_some(&mut self.dirty_cells);
         self.layer_type_data
-            .set_tile_data(chunk_tile_pos, tile_data);
+            .for_each_mut(&mut |chunk_cell, tile_data| {
+                if let Some(dirty_cells) = dirty_cells.as_mut() {
+                    dirty_cells.insert(chunk_cell.as_u64());
+                }
+                f(chunk_cell, tile_data);
+            });
+    }
+
+    fn take_tile_data(&mut self, chunk_tile_pos: ChunkCell) -> Option<TileData> {
+        self.track_filled_tiles(chunk_tile_pos, false);
+        let tile_data = self
+            .layer_type_data
+            .take_tile_data(chunk_tile_pos, self.default_tile_data.as_ref());
+        if tile_data.is_some() {
+            self.mark_dirty(chunk_tile_pos);
+        }
+        self.apply_conversion_policy();
+        tile_data
+    }
+
+    fn clear_tile_data(&mut self) {
+        let default_tile_data = self.default_tile_data.as_ref();
+        if self.track_dirty_cells
+            && (default_tile_data.is_some()
+                || matches!(self.layer_type_data, HexChunkLayerData::Sparse(..)))
+        {
+            for (chunk_cell, tile_data) in self.layer_type_data.iter() {
+                if self.holds_data(tile_data).unwrap_or(true) {
+                    self.dirty_cells.insert(chunk_cell.as_u64());
+                }
+            }
+        }
+        self.layer_type_data.clear(default_tile_data);
+        self.filled_tiles = None;
+        self.apply_conversion_policy();
+    }
+
+    fn default_tile_data(&self) -> Option<&TileData> {
+        self.default_tile_data.as_ref()
+    }
+
+    fn set_default_tile_data(&mut self, tile_data: Option<TileData>) {
+        self.default_tile_data = tile_data;
+        self.filled_tiles = None;
+    }
+
+    fn dirty_cells(&self) -> Box<dyn Iterator<Item = ChunkCell> + '_> {
+        Box::new(
+            self.dirty_cells
+                .iter()
+                .map(|number| ChunkCell::from_u64(*number)),
+        )
+    }
+
+    fn clear_dirty_cells(&mut self) {
+        self.dirty_cells.clear();
+    }
+
+    fn set_track_dirty_cells(&mut self, track_dirty_cells: bool) {
+        self.track_dirty_cells = track_dirty_cells;
+        if !track_dirty_cells {
+            self.dirty_cells = Default::default();
+        }
+    }
+
+    fn tracks_dirty_cells(&self) -> bool {
+        self.track_dirty_cells
     }
 
     fn get_tile_entity(&self, chunk_tile_pos: ChunkCell) -> Option<Entity> {
-        let number = ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+        let number = chunk_tile_pos.as_u64();
         self.tile_entities.get(&number).cloned()
     }
 
     fn set_tile_entity(&mut self, chunk_tile_pos: ChunkCell, entity: Entity) {
-        let number = ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+        let number = chunk_tile_pos.as_u64();
         self.tile_entities.insert(number, entity);
     }
+
+    fn remove_tile_entity(&mut self, chunk_tile_pos: ChunkCell) -> Option<Entity> {
+        let number = chunk_tile_pos.as_u64();
+        self.tile_entities.remove(&number)
+    }
+
+    fn iter_tile_entities(&self) -> Box<dyn Iterator<Item = (ChunkCell, Entity)> + '_> {
+        Box::new(
+            self.tile_entities
+                .iter()
+                .map(|(number, entity)| (ChunkCell::from_u64(*number), *entity)),
+        )
+    }
+
+    fn set_conversion_policy_with(
+        &mut self,
+        conversion_policy: Option<ConversionPolicy>,
+        tile_data_eq: TileDataEq<TileData>,
+    ) {
+        self.conversion_policy = conversion_policy;
+        self.tile_data_eq = Some(tile_data_eq);
+        self.filled_tiles = None;
+    }
+
+    fn conversion_policy(&self) -> Option<ConversionPolicy> {
+        self.conversion_policy
+    }
+
+    fn tile_data_eq(&self) -> Option<TileDataEq<TileData>> {
+        self.tile_data_eq
+    }
 }
 
 /// The data of a hex chunk layer
 #[derive(Clone)]
 #[cfg_attr(feature = "reflect", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "reflect", reflect(Hash))]
 pub enum HexChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// A layer where ***NOT*** every position on the chunk has data
     ///
     /// 0. A hashmap of TilePos -> TileData
     /// 1. A UVec2 representing the actual size of the chunk
     Sparse(HashMap<(i32, i32), T>, UVec2),
-    /// A layer where ***EVERY***  position on the chunk must have data
-    Dense(HexRectangleStorage<T>),
+    /// A layer where ***EVERY***  position on the chunk must have data. Rows and columns are laid out in offset
+    /// coordinates for the orientation of the storage and the given offset
+    Dense(HexRectangleStorage<T>, HexOffset),
+    /// A layer where every position on the chunk has data, stored as a palette of unique values and bit
+    /// packed indices into it. Rows and columns are laid out in offset coordinates for the given orientation and
+    /// offset
+    Palette(PaletteStorage<T>, HexOrientation, HexOffset),
 }
 
 impl<T> Hash for HexChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Hash + Clone + Sized + Send + Sync,
 {
     fn hash<H: Hasher>(&self, h: &mut H) {
         match self {
@@ -173,8 +500,14 @@ where
                 Hash::hash(&pairs, h);
                 Hash::hash(&chunk_size, h);
             }
-            HexChunkLayerData::Dense(grid) => {
+            HexChunkLayerData::Dense(grid, offset) => {
                 Hash::hash(grid, h);
+                Hash::hash(offset, h);
+            }
+            HexChunkLayerData::Palette(palette, orientation, offset) => {
+                Hash::hash(palette, h);
+                Hash::hash(orientation, h);
+                Hash::hash(offset, h);
             }
         }
     }
@@ -182,16 +515,22 @@ where
 
 impl<T> Default for HexChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     fn default() -> Self {
-        Self::Dense(HexRectangleStorage::<T>::new(0, 0, HexOrientation::Pointy))
+        Self::Dense(
+            HexRectangleStorage {
+                grid: Grid::from_vec(vec![], 0),
+                orientation: HexOrientation::Pointy,
+            },
+            HexOffset::default(),
+        )
     }
 }
 
 impl<T> HexChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// Creates a new [`HexChunkLayerData::Dense`] with all the tiles having the same data as the default
     /// for T
@@ -199,9 +538,13 @@ where
         chunk_size_x: usize,
         chunk_size_y: usize,
         orientation: HexOrientation,
-    ) -> Self {
+        offset: HexOffset,
+    ) -> Self
+    where
+        T: Default,
+    {
         let grid = HexRectangleStorage::new(chunk_size_x, chunk_size_y, orientation);
-        Self::Dense(grid)
+        Self::Dense(grid, offset)
     }
 
     /// Creates a new [`HexChunkLayerData::Dense`] with all the tiles having the same data as the given
@@ -211,14 +554,19 @@ where
         chunk_size_y: usize,
         tile_data: T,
         orientation: HexOrientation,
+        offset: HexOffset,
     ) -> Self {
         let grid =
             HexRectangleStorage::new_uniform(chunk_size_x, chunk_size_y, tile_data, orientation);
-        Self::Dense(grid)
+        Self::Dense(grid, offset)
     }
 
     /// Creates a new [`HexChunkLayerData::Dense`]from the given vectors of vectors of T
-    pub fn new_dense_from_vecs(tile_data: &Vec<Vec<T>>, orientation: HexOrientation) -> Self {
+    pub fn new_dense_from_vecs(
+        tile_data: &[Vec<T>],
+        orientation: HexOrientation,
+        offset: HexOffset,
+    ) -> Self {
         let mut given_tile_count = 0u64;
 
         for tile_data in tile_data.iter() {
@@ -230,80 +578,476 @@ where
             given_tile_count
         );
 
-        let mut grid = HexRectangleStorage::new_uniform(
-            tile_data.len(),
-            tile_data[0].len(),
-            T::default(),
+        let grid = HexRectangleStorage {
+            grid: Grid::from_vec(tile_data.concat(), tile_data[0].len()),
             orientation,
-        );
-        let mut current_x = 0usize;
-        let mut current_y = 0usize;
-        let row_length = tile_data[0].len();
-        grid.grid.fill_with(|| {
-            let tile = tile_data[current_y][current_x];
-            current_x += 1;
-            if current_x == row_length {
-                current_x = 0;
-                current_y += 1;
-            }
-            tile
-        });
+        };
 
-        Self::Dense(grid)
+        Self::Dense(grid, offset)
     }
 }
 
 impl<T> HexChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// Returns the actual dimensions of the chunk
     pub fn get_dimensions(&self) -> UVec2 {
         match self {
             HexChunkLayerData::Sparse(_, dimensions) => *dimensions,
-            HexChunkLayerData::Dense(grid) => {
-                UVec2::new(grid.dimensions().y.into(), grid.dimensions().x.into())
+            HexChunkLayerData::Dense(grid, _) => {
+                UVec2::new(grid.dimensions().x, grid.dimensions().y)
+            }
+            HexChunkLayerData::Palette(palette, ..) => {
+                UVec2::new(palette.cols() as u32, palette.rows() as u32)
             }
         }
     }
 
-    /// Sets the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk
-    pub fn set_tile_data(&mut self, chunk_tile_pos: ChunkCell, tile_data: T) {
+    /// Sets the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk,
+    /// returns whether the tile was set
+    pub fn set_tile_data(&mut self, chunk_tile_pos: ChunkCell, tile_data: T) -> bool {
         match self {
             HexChunkLayerData::Sparse(layer_data, ..) => {
                 layer_data.insert((chunk_tile_pos.x(), chunk_tile_pos.y()), tile_data);
+                true
             }
-            HexChunkLayerData::Dense(layer_data) => {
-                if let Some(tile) =
-                    layer_data.get_mut(Cell::new(chunk_tile_pos.x(), chunk_tile_pos.y()))
-                {
-                    *tile = tile_data
+            HexChunkLayerData::Dense(layer_data, offset) => {
+                let Some(tile) = dense_grid_index(
+                    hex_offset_mode(layer_data.orientation, *offset),
+                    chunk_tile_pos,
+                )
+                .and_then(|(row, column)| layer_data.grid.get_mut(row, column)) else {
+                    return false;
                 };
+                *tile = tile_data;
+                true
             }
-        };
+            HexChunkLayerData::Palette(layer_data, orientation, offset) => {
+                let Some((row, column)) =
+                    dense_grid_index(hex_offset_mode(*orientation, *offset), chunk_tile_pos)
+                        .filter(|(row, column)| layer_data.get(*row, *column).is_some())
+                else {
+                    return false;
+                };
+                layer_data.set(row, column, tile_data);
+                true
+            }
+        }
+    }
+
+    /// Removes the tile data at the given [`ChunkCell`] and returns it. Tiles in dense and palette layers are
+    /// reset to the given default tile data instead, and are not removed without one
+    pub fn take_tile_data(
+        &mut self,
+        chunk_tile_pos: ChunkCell,
+        default_tile_data: Option<&T>,
+    ) -> Option<T> {
+        match self {
+            HexChunkLayerData::Sparse(layer_data, ..) => {
+                layer_data.remove(&(chunk_tile_pos.x(), chunk_tile_pos.y()))
+            }
+            HexChunkLayerData::Dense(..) | HexChunkLayerData::Palette(..) => {
+                let default_tile_data = default_tile_data?.clone();
+                let tile_data = self.get_tile_data(chunk_tile_pos).cloned()?;
+                self.set_tile_data(chunk_tile_pos, default_tile_data);
+                Some(tile_data)
+            }
+        }
+    }
+
+    /// Removes all the tile data in the layer. Tiles in dense and palette layers are reset to the given default
+    /// tile data instead, and are left untouched without one
+    pub fn clear(&mut self, default_tile_data: Option<&T>) {
+        match (self, default_tile_data) {
+            (HexChunkLayerData::Sparse(layer_data, ..), _) => layer_data.clear(),
+            (HexChunkLayerData::Dense(layer_data, _), Some(default_tile_data)) => {
+                layer_data.grid.fill(default_tile_data.clone())
+            }
+            (HexChunkLayerData::Palette(layer_data, ..), Some(default_tile_data)) => {
+                layer_data.fill(default_tile_data.clone())
+            }
+            (_, None) => {}
+        }
     }
 
     /// Gets mutable access to the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk
     pub fn get_tile_data_mut(&mut self, chunk_tile_pos: ChunkCell) -> Option<&mut T> {
-        return match self {
+        match self {
             HexChunkLayerData::Sparse(layer_data, ..) => {
                 layer_data.get_mut(&(chunk_tile_pos.x(), chunk_tile_pos.y()))
             }
-            HexChunkLayerData::Dense(layer_data) => {
-                layer_data.get_mut(Cell::new(chunk_tile_pos.x(), chunk_tile_pos.y()))
+            HexChunkLayerData::Dense(layer_data, offset) => dense_grid_index(
+                hex_offset_mode(layer_data.orientation, *offset),
+                chunk_tile_pos,
+            )
+            .and_then(|(row, column)| layer_data.grid.get_mut(row, column)),
+            HexChunkLayerData::Palette(layer_data, orientation, offset) => {
+                dense_grid_index(hex_offset_mode(*orientation, *offset), chunk_tile_pos)
+                    .and_then(|(row, column)| layer_data.get_mut(row, column))
             }
-        };
+        }
     }
 
     /// Gets immutable access to the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk
     pub fn get_tile_data(&self, chunk_tile_pos: ChunkCell) -> Option<&T> {
-        return match self {
+        match self {
             HexChunkLayerData::Sparse(layer_data, ..) => {
                 layer_data.get(&(chunk_tile_pos.x(), chunk_tile_pos.y()))
             }
-            HexChunkLayerData::Dense(layer_data) => {
-                layer_data.get(Cell::new(chunk_tile_pos.x(), chunk_tile_pos.y()))
+            HexChunkLayerData::Dense(layer_data, offset) => dense_grid_index(
+                hex_offset_mode(layer_data.orientation, *offset),
+                chunk_tile_pos,
+            )
+            .and_then(|(row, column)| layer_data.grid.get(row, column)),
+            HexChunkLayerData::Palette(layer_data, orientation, offset) => {
+                dense_grid_index(hex_offset_mode(*orientation, *offset), chunk_tile_pos)
+                    .and_then(|(row, column)| layer_data.get(row, column))
+            }
+        }
+    }
+}
+
+impl<T> HexChunkLayerData<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    /// Returns the amount of tiles that hold data. Tiles in dense and palette layers only hold data if
+    /// `holds_data` returns true for them
+    pub fn filled_tiles(&self, holds_data: impl Fn(&T) -> bool) -> usize {
+        match self {
+            HexChunkLayerData::Sparse(layer_data, ..) => layer_data.len(),
+            HexChunkLayerData::Dense(layer_data, _) => layer_data
+                .grid
+                .iter()
+                .filter(|tile| holds_data(tile))
+                .count(),
+            HexChunkLayerData::Palette(layer_data, ..) => {
+                layer_data.iter().filter(|tile| holds_data(tile)).count()
+            }
+        }
+    }
+
+    /// Returns an iterator over every tile that holds data along with its [`ChunkCell`]. Sparse layers only
+    /// visit occupied tiles
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (ChunkCell, &T)> + '_> {
+        let cols = self.get_dimensions().x as usize;
+        match self {
+            HexChunkLayerData::Sparse(layer_data, ..) => Box::new(
+                layer_data
+                    .iter()
+                    .map(|((x, y), tile_data)| (ChunkCell::new(*x, *y), tile_data)),
+            ),
+            HexChunkLayerData::Dense(layer_data, offset) => {
+                let offset_mode = hex_offset_mode(layer_data.orientation, *offset);
+                Box::new(
+                    layer_data
+                        .grid
+                        .iter()
+                        .enumerate()
+                        .map(move |(index, tile_data)| {
+                            (dense_grid_cell(offset_mode, index, cols), tile_data)
+                        }),
+                )
+            }
+            HexChunkLayerData::Palette(layer_data, orientation, offset) => {
+                let offset_mode = hex_offset_mode(*orientation, *offset);
+                Box::new(
+                    layer_data
+                        .iter()
+                        .enumerate()
+                        .map(move |(index, tile_data)| {
+                            (dense_grid_cell(offset_mode, index, cols), tile_data)
+                        }),
+                )
+            }
+        }
+    }
+
+    /// Calls `f` with a mutable reference to every tile that holds data along with its [`ChunkCell`]. Palette
+    /// layers hand out a copy of each tile and only write the tiles that changed back into the palette
+    pub fn for_each_mut(&mut self, f: &mut dyn FnMut(ChunkCell, &mut T)) {
+        let cols = self.get_dimensions().x as usize;
+        match self {
+            HexChunkLayerData::Sparse(layer_data, ..) => {
+                for ((x, y), tile_data) in layer_data.iter_mut() {
+                    f(ChunkCell::new(*x, *y), tile_data);
+                }
+            }
+            HexChunkLayerData::Dense(layer_data, offset) => {
+                let offset_mode = hex_offset_mode(layer_data.orientation, *offset);
+                for (index, tile_data) in layer_data.grid.iter_mut().enumerate() {
+                    f(dense_grid_cell(offset_mode, index, cols), tile_data);
+                }
             }
+            HexChunkLayerData::Palette(layer_data, orientation, offset) => {
+                let offset_mode = hex_offset_mode(*orientation, *offset);
+                let cols = layer_data.cols();
+                for index in 0..layer_data.rows() * cols {
+                    let (row, col) = (index / cols, index % cols);
+                    let Some(mut tile_data) = layer_data.get(row, col).cloned() else {
+                        continue;
+                    };
+                    f(dense_grid_cell(offset_mode, index, cols), &mut tile_data);
+                    // Unchanged tiles are left in their palette entry
+                    layer_data.set(row, col, tile_data);
+                }
+            }
+        }
+    }
+
+    /// Converts the layer into [`HexChunkLayerData::Dense`] using the given orientation and offset. Tiles without
+    /// data are set to the given default tile data
+    pub fn into_dense(
+        self,
+        orientation: HexOrientation,
+        offset: HexOffset,
+        default_tile_data: &T,
+    ) -> Self {
+        match self {
+            HexChunkLayerData::Sparse(layer_data, dimensions) => {
+                let mut grid = HexRectangleStorage::new_uniform(
+                    dimensions.x as usize,
+                    dimensions.y as usize,
+                    default_tile_data.clone(),
+                    orientation,
+                );
+                for ((x, y), tile_data) in layer_data {
+                    if let Some(tile) =
+                        dense_grid_index(hex_offset_mode(orientation, offset), ChunkCell::new(x, y))
+                            .and_then(|(row, column)| grid.grid.get_mut(row, column))
+                    {
+                        *tile = tile_data;
+                    }
+                }
+                HexChunkLayerData::Dense(grid, offset)
+            }
+            HexChunkLayerData::Palette(layer_data, orientation, offset) => {
+                HexChunkLayerData::Dense(
+                    HexRectangleStorage {
+                        grid: Grid::from_vec(
+                            layer_data.iter().cloned().collect(),
+                            layer_data.cols(),
+                        ),
+                        orientation,
+                    },
+                    offset,
+                )
+            }
+            dense => dense,
+        }
+    }
+
+    /// Converts the layer into [`HexChunkLayerData::Sparse`]. Tiles that `holds_data` returns false for are
+    /// dropped
+    pub fn into_sparse(self, holds_data: impl Fn(&T) -> bool) -> Self {
+        let dimensions = self.get_dimensions();
+        let (tiles, offset_mode): (Vec<T>, OffsetHexMode) = match self {
+            HexChunkLayerData::Dense(layer_data, offset) => {
+                let offset_mode = hex_offset_mode(layer_data.orientation, offset);
+                (layer_data.grid.into_vec(), offset_mode)
+            }
+            HexChunkLayerData::Palette(layer_data, orientation, offset) => (
+                layer_data.iter().cloned().collect(),
+                hex_offset_mode(orientation, offset),
+            ),
+            sparse => return sparse,
         };
+        let sparse_data = tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile_data)| holds_data(tile_data))
+            .map(|(index, tile_data)| {
+                let cell = dense_grid_cell(offset_mode, index, dimensions.x as usize);
+                ((cell.x(), cell.y()), tile_data.clone())
+            })
+            .collect();
+        HexChunkLayerData::Sparse(sparse_data, dimensions)
+    }
+}
+
+/// Returns the `(row, column)` index into the grid of a dense layer with the given offset mode for the given [`ChunkCell`].
+///
+/// The grid is laid out in offset coordinates with one row per row of the chunk, independent of orientation.
+fn dense_grid_index(offset_mode: OffsetHexMode, chunk_cell: ChunkCell) -> Option<(usize, usize)> {
+    let [column, row] =
+        cell_to_offset_coordinates(Cell::new(chunk_cell.x(), chunk_cell.y()), offset_mode);
+    if column.is_negative() || row.is_negative() {
+        return None;
+    }
+    Some((row as usize, column as usize))
+}
+
+/// Returns the [`ChunkCell`] for the given row major index into the grid of a dense layer with the given offset mode.
+///
+/// The inverse of [`dense_grid_index`].
+fn dense_grid_cell(offset_mode: OffsetHexMode, index: usize, cols: usize) -> ChunkCell {
+    let cell =
+        Cell::from_offset_coordinates([(index % cols) as i32, (index / cols) as i32], offset_mode);
+    ChunkCell::new(cell.x, cell.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hex::map_chunk_layer::{HexChunkLayer, HexChunkLayerData, HexagonChunkSettings};
+    use crate::hex::{hex_offset_mode, HexOffset};
+    use crate::map::chunk::{ChunkCell, ChunkLayer, ChunkLayerType, ConversionPolicy};
+    use bevy::math::UVec2;
+    use bevy::utils::HashMap;
+    use lettuces::cell::Cell;
+    use lettuces::HexOrientation;
+
+    /// Every orientation and offset combination, covering all four offset modes
+    const LAYOUTS: [(HexOrientation, HexOffset); 4] = [
+        (HexOrientation::Pointy, HexOffset::Odd),
+        (HexOrientation::Pointy, HexOffset::Even),
+        (HexOrientation::Flat, HexOffset::Odd),
+        (HexOrientation::Flat, HexOffset::Even),
+    ];
+
+    #[test]
+    fn layer_conversion_preserves_data() {
+        for (orientation, offset) in LAYOUTS {
+            let mode = hex_offset_mode(orientation, offset);
+            let chunk_cell = |index: i32| {
+                let cell = Cell::from_offset_coordinates([index % 3, index / 3], mode);
+                ChunkCell::new(cell.x, cell.y)
+            };
+
+            let mut layer: HexChunkLayer<u32> = HexChunkLayer::new(
+                ChunkLayerType::Sparse(HashMap::new()),
+                UVec2::new(3, 4),
+                &HexagonChunkSettings {
+                    orientation,
+                    offset,
+                    max_chunk_size: UVec2::new(3, 4),
+                },
+            );
+            layer.set_default_tile_data(Some(0));
+            layer.set_conversion_policy(Some(ConversionPolicy::new(0.5, 0.25)));
+
+            for index in 0..7 {
+                layer.set_tile_data(chunk_cell(index), index as u32 + 1);
+            }
+            assert!(matches!(
+                layer.layer_type_data,
+                HexChunkLayerData::Dense(..)
+            ));
+            assert_eq!(layer.get_chunk_dimensions(), UVec2::new(3, 4));
+            for index in 0..7 {
+                assert_eq!(
+                    layer.get_tile_data(chunk_cell(index)),
+                    Some(&(index as u32 + 1))
+                );
+            }
+            assert_eq!(layer.get_tile_data(chunk_cell(11)), Some(&0));
+
+            for index in 0..5 {
+                layer.set_tile_data(chunk_cell(index), 0);
+            }
+            assert!(matches!(
+                layer.layer_type_data,
+                HexChunkLayerData::Sparse(..)
+            ));
+            assert_eq!(layer.get_chunk_dimensions(), UVec2::new(3, 4));
+            assert_eq!(layer.get_tile_data(chunk_cell(0)), None);
+            assert_eq!(layer.get_tile_data(chunk_cell(5)), Some(&6));
+            assert_eq!(layer.get_tile_data(chunk_cell(6)), Some(&7));
+        }
+    }
+
+    #[test]
+    fn palette_layer_matches_dense_layer() {
+        for (orientation, offset) in LAYOUTS {
+            let mode = hex_offset_mode(orientation, offset);
+            let chunk_cell = |index: i32| {
+                let cell = Cell::from_offset_coordinates([index % 3, index / 3], mode);
+                ChunkCell::new(cell.x, cell.y)
+            };
+            let data: Vec<Vec<u32>> = (0..4)
+                .map(|y| (0..3).map(|x| (y * 3 + x) % 2).collect())
+                .collect();
+            let settings = HexagonChunkSettings {
+                orientation,
+                offset,
+                max_chunk_size: UVec2::new(3, 4),
+            };
+
+            let dense: HexChunkLayer<u32> = HexChunkLayer::new(
+                ChunkLayerType::Dense(data.clone()),
+                UVec2::new(3, 4),
+                &settings,
+            );
+            let mut palette: HexChunkLayer<u32> = HexChunkLayer::new(
+                ChunkLayerType::Palette(data, u32::eq),
+                UVec2::new(3, 4),
+                &settings,
+            );
+            assert_eq!(palette.get_chunk_dimensions(), UVec2::new(3, 4));
+            for index in 0..12 {
+                assert_eq!(
+                    palette.get_tile_data(chunk_cell(index)),
+                    dense.get_tile_data(chunk_cell(index))
+                );
+            }
+
+            palette.set_tile_data(chunk_cell(4), 7);
+            assert_eq!(palette.get_tile_data(chunk_cell(4)), Some(&7));
+            assert_eq!(palette.get_tile_data(chunk_cell(5)), Some(&1));
+
+            // Cells outside of the chunk are never set so they can't be dirty
+            palette.set_track_dirty_cells(true);
+            palette.set_tile_data(chunk_cell(12), 7);
+            assert_eq!(palette.get_tile_data(chunk_cell(12)), None);
+            assert_eq!(palette.dirty_cells().count(), 0);
+        }
+    }
+
+    #[test]
+    fn iter_yields_matching_chunk_cells() {
+        for (orientation, offset) in LAYOUTS {
+            let data: Vec<Vec<u32>> = (0..4)
+                .map(|y| (0..3).map(|x| y * 3 + x).collect())
+                .collect();
+            let settings = HexagonChunkSettings {
+                orientation,
+                offset,
+                max_chunk_size: UVec2::new(3, 4),
+            };
+            for layer_type in [
+                ChunkLayerType::Dense(data.clone()),
+                ChunkLayerType::Palette(data.clone(), u32::eq),
+            ] {
+                let mut layer: HexChunkLayer<u32> =
+                    HexChunkLayer::new(layer_type, UVec2::new(3, 4), &settings);
+                assert_eq!(layer.iter().count(), 12);
+                for (chunk_cell, tile_data) in layer.iter() {
+                    assert_eq!(layer.get_tile_data(chunk_cell), Some(tile_data));
+                }
+
+                let storage = layer.storage();
+                layer.for_each_mut(&mut |_, tile_data| *tile_data += 1);
+                let mut tiles: Vec<u32> = layer.iter().map(|(_, tile_data)| *tile_data).collect();
+                tiles.sort();
+                assert_eq!(tiles, (1..13).collect::<Vec<u32>>());
+
+                // Every kind of layer visits all of its tiles and palette layers stay palette compressed
+                let mut visited = 0;
+                layer.for_each_mut(&mut |_, _| visited += 1);
+                assert_eq!(visited, 12);
+                assert_eq!(layer.storage(), storage);
+            }
+
+            let mut layer: HexChunkLayer<u32> = HexChunkLayer::new(
+                ChunkLayerType::Sparse(HashMap::new()),
+                UVec2::new(3, 4),
+                &settings,
+            );
+            layer.set_tile_data(ChunkCell::new(1, 1), 4);
+            assert_eq!(
+                layer.iter().collect::<Vec<_>>(),
+                vec![(ChunkCell::new(1, 1), &4)]
+            );
+        }
     }
 }
@@ -12,9 +12,14 @@ use bevy::prelude::{Reflect, ReflectComponent};
 use serde::{Deserialize, Serialize};
 
 use crate::map::{
-    chunk::{Chunk, ChunkLayerType, ChunkPos},
-    MapData, MapLayer,
+    chunk::{Chunk, ChunkCell, ChunkLayerType, ChunkPos},
+    Connectivity, MapData, MapLayer,
 };
+use crate::tilemap_manager::TilemapManagerError;
+use lettuces::{cell::Cell, Hex, HexOrientation, OffsetHexMode};
+
+use super::map_chunk_layer::HexagonChunkSettings;
+use super::{cell_to_hex, cell_to_offset_coordinates, hex_offset_mode, HexMapShape, HexOffset};
 
 /// [`MapData`] implementation for a hexagonal map. Uses essentially the same logic as for a square map. Prior to map construction the map is in offset coordinates
 #[derive(Default, Hash, Component)]
@@ -24,43 +29,202 @@ use crate::map::{
 pub struct HexMapData {
     /// The maximum size that chunk can be
     pub max_chunk_size: UVec2,
+    /// The hex orientation of the map. Must match the orientation used in the maps [`HexagonChunkSettings`](super::map_chunk_layer::HexagonChunkSettings)
+    pub orientation: HexOrientation,
+    /// Which rows or columns of the map are shifted. Must match the offset used in the maps [`HexagonChunkSettings`](super::map_chunk_layer::HexagonChunkSettings)
+    pub offset: HexOffset,
+    /// The shape of the map. Maps that aren't rectangles must start at the [`ChunkPos`] (0, 0) and be the size of
+    /// their shape, see [`HexMapShape::dimensions`]
+    pub shape: HexMapShape,
+}
+
+impl HexMapData {
+    /// Returns the [`OffsetHexMode`] that the map is laid out in
+    pub fn offset_mode(&self) -> OffsetHexMode {
+        hex_offset_mode(self.orientation, self.offset)
+    }
+
+    /// Returns the center of the map if it is a [`HexMapShape::Hexagon`]
+    pub fn hexagon_center(&self) -> Option<Hex> {
+        match self.shape {
+            HexMapShape::Rectangle => None,
+            HexMapShape::Hexagon { radius } => {
+                Some(self.offset_coordinates_to_hex([radius as i32, radius as i32]))
+            }
+        }
+    }
+
+    /// Returns whether the [`Hex`] is part of the shape of the map. Every [`Hex`] is part of a
+    /// [`HexMapShape::Rectangle`], its bounds are the bounds of its chunks
+    pub fn contains_hex(&self, hex: Hex) -> bool {
+        match self.shape {
+            HexMapShape::Rectangle => true,
+            HexMapShape::Hexagon { radius } => self
+                .hexagon_center()
+                .is_some_and(|center| center.unsigned_distance_to(hex) <= radius),
+        }
+    }
+
+    /// Converts a [`Hex`] into the `[column, row]` offset coordinates of the map using the maps orientation and offset
+    pub fn hex_to_offset_coordinates(&self, hex: Hex) -> [i32; 2] {
+        hex.to_offset_coordinates(self.offset_mode())
+    }
+
+    /// Converts `[column, row]` offset coordinates of the map into a [`Hex`] using the maps orientation and offset
+    pub fn offset_coordinates_to_hex(&self, offset_coordinates: [i32; 2]) -> Hex {
+        Hex::from_offset_coordinates(offset_coordinates, self.offset_mode())
+    }
+
+    /// Converts `[x, y, z]` cube coordinates into the `[column, row]` offset coordinates of the map
+    ///
+    /// # Panics
+    /// - If the cube coordinates don't add up to zero
+    pub fn cube_to_offset_coordinates(&self, cube_coordinates: [i32; 3]) -> [i32; 2] {
+        let [x, y, z] = cube_coordinates;
+        self.hex_to_offset_coordinates(Hex::new_cubic(x, y, z))
+    }
+
+    /// Converts `[column, row]` offset coordinates of the map into `[x, y, z]` cube coordinates
+    pub fn offset_coordinates_to_cube(&self, offset_coordinates: [i32; 2]) -> [i32; 3] {
+        self.offset_coordinates_to_hex(offset_coordinates)
+            .to_cubic_array()
+    }
+
+    /// Returns the [`ChunkPos`] of the chunk the [`Hex`] is in along with its [`ChunkCell`] inside of that chunk
+    pub fn hex_to_chunk_cell(&self, hex: Hex) -> (ChunkPos, ChunkCell) {
+        let [column, row] = self.hex_to_offset_coordinates(hex);
+        let chunk_hex = self.offset_coordinates_to_hex([
+            column.rem_euclid(self.max_chunk_size.x as i32),
+            row.rem_euclid(self.max_chunk_size.y as i32),
+        ]);
+        (
+            self.into_chunk_pos(Cell::from(hex)),
+            ChunkCell::new(chunk_hex.x, chunk_hex.y),
+        )
+    }
+
+    /// Converts a [`ChunkCell`] inside of the chunk at the given [`ChunkPos`] into a [`Hex`] of the map. Hex
+    /// neighbours and distances only work across chunk boundaries on these map wide coordinates
+    pub fn chunk_cell_to_hex(&self, chunk_pos: ChunkPos, chunk_cell: ChunkCell) -> Hex {
+        cell_to_hex(self.into_cell(chunk_pos, chunk_cell))
+    }
+}
+
+impl From<&HexMapData> for HexagonChunkSettings {
+    fn from(map: &HexMapData) -> Self {
+        Self {
+            orientation: map.orientation,
+            offset: map.offset,
+            max_chunk_size: map.max_chunk_size,
+        }
+    }
 }
 
 impl MapData for HexMapData {
-    fn into_chunk_pos(&self, cell: lettuces::cell::Cell) -> ChunkPos {
+    fn into_chunk_pos(&self, cell: Cell) -> ChunkPos {
+        let [column, row] = cell_to_offset_coordinates(cell, self.offset_mode());
         ChunkPos::new(
-            cell.x / self.max_chunk_size.x as i32,
-            cell.y / self.max_chunk_size.y as i32,
+            column.div_euclid(self.max_chunk_size.x as i32),
+            row.div_euclid(self.max_chunk_size.y as i32),
+        )
+    }
+
+    fn into_cell(&self, chunk_pos: ChunkPos, chunk_cell: ChunkCell) -> Cell {
+        let [column, row] = cell_to_offset_coordinates(
+            Cell::new(chunk_cell.x(), chunk_cell.y()),
+            self.offset_mode(),
+        );
+        Cell::from_offset_coordinates(
+            [
+                chunk_pos.x() * self.max_chunk_size.x as i32 + column,
+                chunk_pos.y() * self.max_chunk_size.y as i32 + row,
+            ],
+            self.offset_mode(),
         )
     }
 
+    fn cell_to_offset_coordinates(&self, cell: Cell) -> [i32; 2] {
+        cell_to_offset_coordinates(cell, self.offset_mode())
+    }
+
+    fn offset_coordinates_to_cell(&self, offset_coordinates: [i32; 2]) -> Cell {
+        Cell::from_offset_coordinates(offset_coordinates, self.offset_mode())
+    }
+
+    /// Hexagons share an edge with all 6 of their neighbours so both [`Connectivity`]s return the same cells. The
+    /// cells are axial so the neighbours are the same for every offset mode
+    fn neighbours(&self, cell: Cell, _connectivity: Connectivity) -> Vec<Cell> {
+        cell_to_hex(cell)
+            .all_neighbors()
+            .into_iter()
+            .map(Cell::from)
+            .collect()
+    }
+
+    fn distance(&self, from: Cell, to: Cell, _connectivity: Connectivity) -> u32 {
+        cell_to_hex(from).unsigned_distance_to(cell_to_hex(to))
+    }
+
+    fn contains_cell(&self, cell: Cell) -> bool {
+        self.contains_hex(cell_to_hex(cell))
+    }
+
+    fn cell_count(&self, dimensions: UVec2) -> usize {
+        self.shape.cell_count(dimensions)
+    }
+
+    fn chunk_in_shape(&self, chunk_pos: ChunkPos) -> bool {
+        if self.shape == HexMapShape::Rectangle {
+            return true;
+        }
+        let first_column = chunk_pos.x() * self.max_chunk_size.x as i32;
+        let first_row = chunk_pos.y() * self.max_chunk_size.y as i32;
+        (first_row..first_row + self.max_chunk_size.y as i32).any(|row| {
+            (first_column..first_column + self.max_chunk_size.x as i32)
+                .any(|column| self.contains_hex(self.offset_coordinates_to_hex([column, row])))
+        })
+    }
+
+    fn check_layout(&self, map_size: UVec2, origin: ChunkPos) -> Result<(), TilemapManagerError> {
+        match self.shape.dimensions() {
+            Some(dimensions) if dimensions != map_size || origin != ChunkPos::default() => {
+                Err(TilemapManagerError::MapShapeMismatch(map_size, origin))
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn max_chunk_size(&self) -> UVec2 {
         self.max_chunk_size
     }
 
+    fn set_max_chunk_size(&mut self, max_chunk_size: UVec2) {
+        self.max_chunk_size = max_chunk_size;
+    }
+
     fn break_data_vecs_down_into_chunk_data<TileData>(
         &self,
-        data: &Vec<Vec<TileData>>,
+        data: &[Vec<TileData>],
         chunk_pos: ChunkPos,
         max_chunk_size: UVec2,
     ) -> Vec<Vec<TileData>>
     where
-        TileData: Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
     {
         let amount_of_x_tiles_done = (chunk_pos.x() * max_chunk_size.x as i32) as usize;
         let amount_of_y_tiles_done = (chunk_pos.y() * max_chunk_size.y as i32) as usize;
         let mut vec: Vec<Vec<TileData>> = vec![];
-        for y in amount_of_y_tiles_done..data.len() {
-            if y >= (amount_of_y_tiles_done + max_chunk_size.y as usize) {
-                continue;
-            }
-            let mut row_vec: Vec<TileData> = vec![];
-            for x in amount_of_x_tiles_done..data[0].len() {
-                if x >= (amount_of_x_tiles_done + max_chunk_size.x as usize) {
-                    continue;
-                }
-                row_vec.push(data[y][x]);
-            }
+        for row in data
+            .iter()
+            .skip(amount_of_y_tiles_done)
+            .take(max_chunk_size.y as usize)
+        {
+            let row_vec: Vec<TileData> = row
+                .iter()
+                .skip(amount_of_x_tiles_done)
+                .take(max_chunk_size.x as usize)
+                .cloned()
+                .collect();
             vec.push(row_vec);
         }
         vec
@@ -68,12 +232,13 @@ impl MapData for HexMapData {
 
     fn break_data_vecs_into_chunks<TileData, MapChunk>(
         &self,
-        data: &Vec<Vec<TileData>>,
+        data: &[Vec<TileData>],
+        origin: ChunkPos,
         max_chunk_size: UVec2,
         chunk_settings: MapChunk::ChunkSettings,
     ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
     where
-        TileData: std::hash::Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
         MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
     {
         let mut chunks: Vec<Vec<Chunk<MapChunk, TileData>>> = vec![];
@@ -87,12 +252,12 @@ impl MapData for HexMapData {
             let mut chunks_rows: Vec<Chunk<MapChunk, TileData>> = vec![];
             for x in 0..chunks_on_x {
                 let vec = self.break_data_vecs_down_into_chunk_data(
-                    &data,
+                    data,
                     ChunkPos::new(x, y),
                     max_chunk_size,
                 );
                 let chunk = Chunk::<MapChunk, TileData>::new(
-                    ChunkPos::new(x, y),
+                    ChunkPos::new(origin.x() + x, origin.y() + y),
                     UVec2::new(vec.len() as u32, vec[0].len() as u32),
                     ChunkLayerType::Dense(vec),
                     chunk_settings,
@@ -108,13 +273,14 @@ impl MapData for HexMapData {
     fn break_hashmap_into_chunks<TileData, MapChunk>(
         &self,
         map_layer: impl MapLayer,
-        data: &bevy::utils::HashMap<lettuces::cell::Cell, TileData>,
+        data: &bevy::utils::HashMap<Cell, TileData>,
+        origin: ChunkPos,
         map_size: UVec2,
         max_chunk_size: UVec2,
         chunk_settings: MapChunk::ChunkSettings,
     ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
     where
-        TileData: std::hash::Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
         MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
     {
         let mut chunks: Vec<Vec<Chunk<MapChunk, TileData>>> = vec![];
@@ -144,7 +310,7 @@ impl MapData for HexMapData {
                         ((max_chunks_floats.x - x as f32) * max_chunk_size.x as f32).ceil() as u32
                 };
                 chunks_rows.push(Chunk::new(
-                    ChunkPos::new(x, y),
+                    ChunkPos::new(origin.x() + x, origin.y() + y),
                     chunk_size,
                     ChunkLayerType::Sparse(HashMap::new()),
                     chunk_settings,
@@ -153,16 +319,368 @@ impl MapData for HexMapData {
             chunks.push(chunks_rows);
         }
 
-        for (cell, tile_data) in data.iter() {
+        for (cell, tile_data) in data.iter().filter(|(cell, _)| self.contains_cell(**cell)) {
             let chunk_pos = self.into_chunk_pos(*cell);
-            let chunk = &mut chunks[chunk_pos.y() as usize][chunk_pos.x() as usize];
+            let chunk = &mut chunks[(chunk_pos.y() - origin.y()) as usize]
+                [(chunk_pos.x() - origin.x()) as usize];
             chunk.set_tile_data(
-                map_layer.to_bits(),
+                map_layer.to_index(),
                 MapChunk::into_chunk_cell(*cell, &chunk.chunk_settings),
-                *tile_data,
+                tile_data.clone(),
             );
         }
 
         chunks
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::hex::map_chunk_layer::{HexChunkLayer, HexChunkLayerData, HexagonChunkSettings};
+    use crate::hex::map_data::HexMapData;
+    use crate::hex::{hex_offset_mode, HexMapShape, HexOffset};
+    use crate::map::chunk::{Chunk, ChunkCell, ChunkLayer, ChunkPos};
+    use crate::map::{Connectivity, MapData};
+    use bevy::math::UVec2;
+    use bevy::utils::HashMap;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+    use lettuces::HexOrientation;
+
+    /// Every orientation and offset combination, covering all four offset modes
+    const LAYOUTS: [(HexOrientation, HexOffset); 4] = [
+        (HexOrientation::Pointy, HexOffset::Odd),
+        (HexOrientation::Pointy, HexOffset::Even),
+        (HexOrientation::Flat, HexOffset::Odd),
+        (HexOrientation::Flat, HexOffset::Even),
+    ];
+
+    #[derive(MapLayer, Default)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    #[test]
+    fn test_vec_breakdown_cell_access() {
+        for (orientation, offset) in LAYOUTS {
+            let mode = hex_offset_mode(orientation, offset);
+            let map_data = HexMapData {
+                max_chunk_size: UVec2::new(3, 4),
+                orientation,
+                offset,
+                shape: HexMapShape::Rectangle,
+            };
+            let chunk_settings = HexagonChunkSettings {
+                orientation,
+                offset,
+                max_chunk_size: UVec2::new(3, 4),
+            };
+
+            // Each tile holds its own axial cell, chunks are not square and the edge chunks are partial
+            let mut vecs = vec![];
+            for y in 0..6 {
+                let mut row = vec![];
+                for x in 0..7 {
+                    let cell = Cell::from_offset_coordinates([x, y], mode);
+                    row.push((cell.x, cell.y));
+                }
+                vecs.push(row);
+            }
+
+            let chunks: Vec<Vec<Chunk<HexChunkLayer<(i32, i32)>, (i32, i32)>>> = map_data
+                .break_data_vecs_into_chunks(
+                    &vecs,
+                    ChunkPos::new(0, 0),
+                    map_data.max_chunk_size,
+                    chunk_settings,
+                );
+            assert_eq!(chunks.len(), 2);
+            assert_eq!(chunks[0].len(), 3);
+
+            for y in 0..6 {
+                for x in 0..7 {
+                    let cell = Cell::from_offset_coordinates([x, y], mode);
+                    let chunk_pos = map_data.into_chunk_pos(cell);
+                    assert_eq!(chunk_pos, ChunkPos::new(x / 3, y / 4));
+                    assert_eq!(
+                        chunks[chunk_pos.y() as usize][chunk_pos.x() as usize]
+                            .get_tile_data_from_cell(MapLayers::Main, cell),
+                        Some(&(cell.x, cell.y))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_negative_cell_mapping() {
+        for (orientation, offset) in LAYOUTS {
+            let mode = hex_offset_mode(orientation, offset);
+            let map_data = HexMapData {
+                max_chunk_size: UVec2::new(5, 5),
+                orientation,
+                offset,
+                shape: HexMapShape::Rectangle,
+            };
+
+            assert_eq!(
+                map_data.into_chunk_pos(Cell::from_offset_coordinates([-1, -1], mode)),
+                ChunkPos::new(-1, -1)
+            );
+            assert_eq!(
+                map_data.into_chunk_pos(Cell::from_offset_coordinates([-5, 4], mode)),
+                ChunkPos::new(-1, 0)
+            );
+            assert_eq!(
+                map_data.into_chunk_pos(Cell::from_offset_coordinates([4, -6], mode)),
+                ChunkPos::new(0, -2)
+            );
+
+            let chunk_settings = HexagonChunkSettings {
+                orientation,
+                offset,
+                max_chunk_size: UVec2::new(5, 5),
+            };
+            for offset_coordinates in [[-1, -1], [-5, 4], [4, -6], [7, 12]] {
+                let cell = Cell::from_offset_coordinates(offset_coordinates, mode);
+                assert_eq!(
+                    map_data.cell_to_offset_coordinates(cell),
+                    offset_coordinates
+                );
+                assert_eq!(
+                    map_data.offset_coordinates_to_cell(offset_coordinates),
+                    cell
+                );
+                assert_eq!(
+                    map_data.into_cell(
+                        map_data.into_chunk_pos(cell),
+                        HexChunkLayer::<u8>::into_chunk_cell(cell, &chunk_settings)
+                    ),
+                    cell
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cell_lookups_agree_between_offset_modes() {
+        // Odd chunk sizes so that chunks start on both even and odd rows and columns
+        let max_chunk_size = UVec2::new(3, 3);
+        // Each tile holds its own offset coordinates, which are the same in every offset mode
+        let vecs: Vec<Vec<(i32, i32)>> = (0..5).map(|y| (0..7).map(|x| (x, y)).collect()).collect();
+
+        let mut cells = vec![];
+        for (orientation, offset) in LAYOUTS {
+            let mode = hex_offset_mode(orientation, offset);
+            let map_data = HexMapData {
+                max_chunk_size,
+                orientation,
+                offset,
+                shape: HexMapShape::Rectangle,
+            };
+            let chunk_settings = HexagonChunkSettings {
+                orientation,
+                offset,
+                max_chunk_size,
+            };
+
+            let mut hashmap = HashMap::new();
+            for row in vecs.iter() {
+                for (x, y) in row.iter() {
+                    hashmap.insert(Cell::from_offset_coordinates([*x, *y], mode), (*x, *y));
+                }
+            }
+            let dense: Vec<Vec<Chunk<HexChunkLayer<(i32, i32)>, (i32, i32)>>> = map_data
+                .break_data_vecs_into_chunks(
+                    &vecs,
+                    ChunkPos::new(0, 0),
+                    max_chunk_size,
+                    chunk_settings,
+                );
+            let sparse: Vec<Vec<Chunk<HexChunkLayer<(i32, i32)>, (i32, i32)>>> = map_data
+                .break_hashmap_into_chunks(
+                    MapLayers::Main,
+                    &hashmap,
+                    ChunkPos::new(0, 0),
+                    UVec2::new(7, 5),
+                    max_chunk_size,
+                    chunk_settings,
+                );
+
+            for y in 0..5 {
+                for x in 0..7 {
+                    let cell = Cell::from_offset_coordinates([x, y], mode);
+                    let chunk_pos = map_data.into_chunk_pos(cell);
+                    assert_eq!(chunk_pos, ChunkPos::new(x / 3, y / 3));
+                    for chunks in [&dense, &sparse] {
+                        assert_eq!(
+                            chunks[chunk_pos.y() as usize][chunk_pos.x() as usize]
+                                .get_tile_data_from_cell(MapLayers::Main, cell),
+                            Some(&(x, y))
+                        );
+                    }
+                }
+            }
+            cells.push(Cell::from_offset_coordinates([3, 3], mode));
+        }
+        // The same offset coordinates are a different cell in every mode
+        for (index, cell) in cells.iter().enumerate() {
+            assert!(!cells[index + 1..].contains(cell));
+        }
+    }
+
+    #[test]
+    fn hex_conversions_across_chunks() {
+        for (orientation, offset) in LAYOUTS {
+            let map_data = HexMapData {
+                max_chunk_size: UVec2::new(3, 3),
+                orientation,
+                offset,
+                shape: HexMapShape::Rectangle,
+            };
+            for y in -4..7 {
+                for x in -4..7 {
+                    let hex = map_data.offset_coordinates_to_hex([x, y]);
+                    assert_eq!(map_data.hex_to_offset_coordinates(hex), [x, y]);
+                    let cube = map_data.offset_coordinates_to_cube([x, y]);
+                    assert_eq!(cube, hex.to_cubic_array());
+                    assert_eq!(map_data.cube_to_offset_coordinates(cube), [x, y]);
+
+                    let (chunk_pos, chunk_cell) = map_data.hex_to_chunk_cell(hex);
+                    assert_eq!(chunk_pos, map_data.into_chunk_pos(Cell::from(hex)));
+                    assert_eq!(map_data.chunk_cell_to_hex(chunk_pos, chunk_cell), hex);
+                }
+            }
+
+            // Neighbours and distances between tiles in different chunks are the same as within a chunk
+            let hex = map_data.offset_coordinates_to_hex([2, 2]);
+            let (chunk_pos, chunk_cell) = map_data.hex_to_chunk_cell(hex);
+            for neighbour in hex.all_neighbors() {
+                let (neighbour_chunk_pos, neighbour_chunk_cell) =
+                    map_data.hex_to_chunk_cell(neighbour);
+                let distance = map_data
+                    .chunk_cell_to_hex(chunk_pos, chunk_cell)
+                    .unsigned_distance_to(
+                        map_data.chunk_cell_to_hex(neighbour_chunk_pos, neighbour_chunk_cell),
+                    );
+                assert_eq!(distance, 1);
+            }
+            let far = map_data.offset_coordinates_to_hex([5, 5]);
+            assert_eq!(
+                hex.unsigned_distance_to(far),
+                map_data
+                    .chunk_cell_to_hex(chunk_pos, chunk_cell)
+                    .unsigned_distance_to(map_data.chunk_cell_to_hex(
+                        map_data.hex_to_chunk_cell(far).0,
+                        map_data.hex_to_chunk_cell(far).1
+                    ))
+            );
+        }
+    }
+
+    #[test]
+    fn hexagon_shape_fits_its_dimensions() {
+        for (orientation, offset) in LAYOUTS {
+            for radius in 0..6 {
+                let shape = HexMapShape::Hexagon { radius };
+                let map_data = HexMapData {
+                    max_chunk_size: UVec2::new(3, 3),
+                    orientation,
+                    offset,
+                    shape,
+                };
+                let dimensions = shape.dimensions().unwrap();
+                let center = map_data.hexagon_center().unwrap();
+                let mut cells = 0;
+                for hex in center.range(radius) {
+                    let [x, y] = map_data.hex_to_offset_coordinates(hex);
+                    assert!((0..dimensions.x as i32).contains(&x));
+                    assert!((0..dimensions.y as i32).contains(&y));
+                    assert!(map_data.contains_cell(Cell::from(hex)));
+                    cells += 1;
+                }
+                assert_eq!(map_data.cell_count(dimensions), cells);
+                for neighbour in center.ring(radius + 1) {
+                    assert!(!map_data.contains_cell(Cell::from(neighbour)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_respect_offset_mode() {
+        for (orientation, offset) in LAYOUTS {
+            let map_data = HexMapData {
+                max_chunk_size: UVec2::new(3, 3),
+                orientation,
+                offset,
+                shape: HexMapShape::Rectangle,
+            };
+            let cell = map_data.offset_coordinates_to_cell([1, 1]);
+            let mut neighbours: Vec<[i32; 2]> = map_data
+                .neighbours(cell, Connectivity::Edges)
+                .into_iter()
+                .map(|neighbour| map_data.cell_to_offset_coordinates(neighbour))
+                .collect();
+            neighbours.sort();
+            assert_eq!(
+                map_data.neighbours(cell, Connectivity::Vertices).len(),
+                neighbours.len()
+            );
+            // Row or column 1 is shifted forwards in odd layouts and the others in even layouts
+            let expected = match (orientation, offset) {
+                (HexOrientation::Pointy, HexOffset::Odd) => {
+                    [[0, 1], [1, 0], [1, 2], [2, 0], [2, 1], [2, 2]]
+                }
+                (HexOrientation::Pointy, HexOffset::Even) => {
+                    [[0, 0], [0, 1], [0, 2], [1, 0], [1, 2], [2, 1]]
+                }
+                (HexOrientation::Flat, HexOffset::Odd) => {
+                    [[0, 1], [0, 2], [1, 0], [1, 2], [2, 1], [2, 2]]
+                }
+                (HexOrientation::Flat, HexOffset::Even) => {
+                    [[0, 0], [0, 1], [1, 0], [1, 2], [2, 0], [2, 1]]
+                }
+            };
+            assert_eq!(neighbours, expected);
+        }
+    }
+
+    #[test]
+    fn test_dense_storage_ordering() {
+        for (orientation, offset) in LAYOUTS {
+            let mode = hex_offset_mode(orientation, offset);
+            // Square chunks are stored the same way as before the grid was laid out in offset rows,
+            // non square chunks used to be built with their width and height swapped
+            for (width, height) in [(3usize, 3usize), (3, 2)] {
+                let data: Vec<Vec<(usize, usize)>> = (0..height)
+                    .map(|row| (0..width).map(|column| (row, column)).collect())
+                    .collect();
+                let layer = HexChunkLayerData::new_dense_from_vecs(&data, orientation, offset);
+                assert_eq!(
+                    layer.get_dimensions(),
+                    UVec2::new(width as u32, height as u32)
+                );
+
+                let HexChunkLayerData::Dense(storage, ..) = &layer else {
+                    panic!("Layer should be dense");
+                };
+                assert_eq!(storage.grid.rows(), height);
+                assert_eq!(storage.grid.cols(), width);
+
+                for (row, row_data) in data.iter().enumerate() {
+                    for (column, tile) in row_data.iter().enumerate() {
+                        assert_eq!(storage.grid.get(row, column), Some(tile));
+                        let cell = Cell::from_offset_coordinates([column as i32, row as i32], mode);
+                        assert_eq!(
+                            layer.get_tile_data(ChunkCell::new(cell.x, cell.y)),
+                            Some(tile)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,22 @@
-use lettuces::{HexOrientation, OffsetHexMode, Quat};
+use bevy::math::UVec2;
+use lettuces::cell::Cell;
+use lettuces::{Hex, HexOrientation, OffsetHexMode, Quat};
 use map_chunk_layer::HexChunkLayer;
 use map_data::HexMapData;
 
 use crate::{map::chunk::Chunk, tilemap_builder::TilemapBuilder, tilemap_manager::TilemapManager};
 
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod fov;
 /// Implements [`ChunkLayer`](crate::map::chunk::ChunkLayer) for a hexagonal map
 pub mod map_chunk_layer;
 /// Implements [`MapData`](crate::map::MapData) for a hexagonal map
 pub mod map_data;
+mod tilemap_manager;
 
 /// Type alias for [`TilemapManager`] for the built in hexagon map types.
 pub type HexTilemapManager<'w, 's, TileData, MapLayers> =
@@ -20,18 +29,93 @@ pub type HexChunk<TileData> = Chunk<HexChunkLayer<TileData>, TileData>;
 pub type HexTilemapBuilder<TileData, MapLayers> =
     TilemapBuilder<TileData, MapLayers, HexChunkLayer<TileData>, HexMapData>;
 
-/// Converts a [`HexOrientation`] into a [`OffsetHexMode`]. This sets it to Odd Rows and Odd Columns respectively which are the only two that this crate supports
-pub fn hex_offset_from_orientation(orientation: HexOrientation) -> OffsetHexMode {
-    match orientation {
-        HexOrientation::Pointy => OffsetHexMode::OddRows,
-        HexOrientation::Flat => OffsetHexMode::OddColumns,
+/// Which rows of a pointy map or columns of a flat map are shifted when the map is laid out in offset coordinates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub enum HexOffset {
+    /// Odd rows or columns are shifted, [`OffsetHexMode::OddRows`] and [`OffsetHexMode::OddColumns`]
+    #[default]
+    Odd,
+    /// Even rows or columns are shifted, [`OffsetHexMode::EvenRows`] and [`OffsetHexMode::EvenColumns`]
+    Even,
+}
+
+/// The shape of a hexagonal map
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub enum HexMapShape {
+    /// A rectangle in offset coordinates. Every cell of the map data is part of the map
+    #[default]
+    Rectangle,
+    /// A big hexagon made up of every cell within `radius` of its center. The map is laid out in the
+    /// [`dimensions`](HexMapShape::dimensions) rectangle around the hexagon, with the center at the offset coordinates
+    /// `[radius, radius]`. The cells in the corners of that rectangle are not part of the map, they are left out of
+    /// sparse layers and can't be accessed in dense and palette layers. Chunks that hold no cell of the hexagon get
+    /// empty sparse layers.
+    ///
+    /// The map data must be exactly [`dimensions`](HexMapShape::dimensions) large and start at the
+    /// [`ChunkPos`](crate::map::chunk::ChunkPos) (0, 0), the [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder)
+    /// returns [`TilemapManagerError::MapShapeMismatch`](crate::tilemap_manager::TilemapManagerError::MapShapeMismatch)
+    /// otherwise
+    Hexagon {
+        /// The distance from the center cell to the edge cells of the hexagon
+        radius: u32,
+    },
+}
+
+impl HexMapShape {
+    /// Returns the size of the rectangle around the shape in offset coordinates, which is the size the map data must
+    /// be. Returns `None` for [`HexMapShape::Rectangle`] as it is as large as its map data
+    pub fn dimensions(&self) -> Option<UVec2> {
+        match self {
+            HexMapShape::Rectangle => None,
+            HexMapShape::Hexagon { radius } => Some(UVec2::splat(radius * 2 + 1)),
+        }
+    }
+
+    /// Returns the amount of cells that are part of a map of this shape laid out in the given dimensions
+    pub fn cell_count(&self, dimensions: UVec2) -> usize {
+        match self {
+            HexMapShape::Rectangle => (dimensions.x * dimensions.y) as usize,
+            HexMapShape::Hexagon { radius } => Hex::range_count(*radius) as usize,
+        }
+    }
+}
+
+/// Converts a [`HexOrientation`] and [`HexOffset`] into an [`OffsetHexMode`]. Pointy maps are offset by rows and flat
+/// maps by columns
+pub fn hex_offset_mode(orientation: HexOrientation, offset: HexOffset) -> OffsetHexMode {
+    match (orientation, offset) {
+        (HexOrientation::Pointy, HexOffset::Odd) => OffsetHexMode::OddRows,
+        (HexOrientation::Pointy, HexOffset::Even) => OffsetHexMode::EvenRows,
+        (HexOrientation::Flat, HexOffset::Odd) => OffsetHexMode::OddColumns,
+        (HexOrientation::Flat, HexOffset::Even) => OffsetHexMode::EvenColumns,
     }
 }
 
+/// Converts a [`HexOrientation`] into an [`OffsetHexMode`] using the default [`HexOffset::Odd`]. This sets it to Odd
+/// Rows and Odd Columns respectively. See [`hex_offset_mode`] for even offset layouts
+pub fn hex_offset_from_orientation(orientation: HexOrientation) -> OffsetHexMode {
+    hex_offset_mode(orientation, HexOffset::Odd)
+}
+
+/// Converts an axial [`Cell`] into the `[column, row]` offset coordinates used to lay out a hexagonal map with the given offset mode
+pub fn cell_to_offset_coordinates(cell: Cell, offset_mode: OffsetHexMode) -> [i32; 2] {
+    cell_to_hex(cell).to_offset_coordinates(offset_mode)
+}
+
+/// Converts an axial [`Cell`] into a [`Hex`]. Hexagonal maps store their cells in axial coordinates so this is a
+/// direct conversion, use [`Cell::from`] for the reverse
+pub fn cell_to_hex(cell: Cell) -> Hex {
+    Hex::new(cell.x, cell.y)
+}
+
 /// Returns the correct hexagon rotation for the given orientation
 pub fn hex_rotation(orientation: HexOrientation) -> Quat {
     Quat::from_rotation_z(match orientation {
         HexOrientation::Pointy => 0.0,
-        HexOrientation::Flat => 0.52359878,
+        HexOrientation::Flat => std::f32::consts::FRAC_PI_6,
     })
 }
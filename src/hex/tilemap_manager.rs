@@ -0,0 +1,390 @@
+use crate::hex::{cell_to_hex, HexTilemapManager};
+use crate::map::{Connectivity, MapLayer};
+use crate::tilemap_manager::TilemapManagerError;
+use bevy::prelude::Entity;
+use lettuces::cell::Cell;
+use lettuces::Hex;
+
+/// Hexagon specific functions that take and return [`Hex`] coordinates instead of [`Cell`]s. A [`Hex`] is in axial
+/// coordinates and also exposes its cube coordinates through [`Hex::z`] and [`Hex::to_cubic_array`]. Use
+/// [`HexMapData`](crate::hex::map_data::HexMapData) to convert to and from the offset coordinates of the map
+impl<'w, 's, TileData, MapLayers> HexTilemapManager<'w, 's, TileData, MapLayers>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + Default + Clone + Copy + Send + Sync + 'static,
+{
+    /// Gets a reference to the tile data for the given [`Hex`] if it exists.
+    pub fn get_hex_tile_data(&self, hex: Hex) -> Result<&TileData, TilemapManagerError> {
+        self.get_tile_data(Cell::from(hex))
+    }
+
+    /// Gets a mutable reference to the tile data for the given [`Hex`] if it exists.
+    pub fn get_hex_tile_data_mut(
+        &mut self,
+        hex: Hex,
+    ) -> Result<&mut TileData, TilemapManagerError> {
+        self.get_tile_data_mut(Cell::from(hex))
+    }
+
+    /// Sets the tile data for the given [`Hex`] if it exists.
+    pub fn set_hex_tile_data(
+        &mut self,
+        tile_data: TileData,
+        hex: Hex,
+    ) -> Result<(), TilemapManagerError> {
+        self.sets_tile_data(tile_data, Cell::from(hex))
+    }
+
+    /// Removes the tile data for the given [`Hex`] and returns it.
+    ///
+    /// # Note
+    /// - Tiles in dense layers are reset to `TileData::default()` instead
+    pub fn take_hex_tile_data(&mut self, hex: Hex) -> Result<TileData, TilemapManagerError> {
+        self.take_tile_data(Cell::from(hex))
+    }
+
+    /// Removes the tile data for the given [`Hex`] if it exists.
+    ///
+    /// # Note
+    /// - Tiles in dense layers are reset to `TileData::default()` instead
+    pub fn remove_hex_tile_data(&mut self, hex: Hex) -> Result<(), TilemapManagerError> {
+        self.remove_tile_data(Cell::from(hex))
+    }
+
+    /// Returns an iterator over the tile data of every tile in the current layer of the loaded chunks along
+    /// with its [`Hex`]. Sparse layers only visit occupied tiles
+    pub fn iter_hex_layer(
+        &self,
+    ) -> Result<impl Iterator<Item = (Hex, &TileData)> + '_, TilemapManagerError> {
+        Ok(self
+            .iter_layer()?
+            .map(|(cell, tile_data)| (cell_to_hex(cell), tile_data)))
+    }
+
+    /// Returns the neighbours of the given [`Hex`] that have tile data in the current layer along with their tile
+    /// data. Neighbours in other chunks are included, neighbours outside of the tilemap are skipped
+    pub fn hex_neighbours(&self, hex: Hex) -> impl Iterator<Item = (Hex, &TileData)> + '_ {
+        self.neighbours(Cell::from(hex), Connectivity::Edges)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(cell, tile_data)| (cell_to_hex(cell), tile_data))
+    }
+
+    /// Returns every [`Hex`] within `range` of the given [`Hex`] that has tile data in the current layer along with
+    /// its tile data. Includes the given [`Hex`] itself and spans chunk boundaries
+    pub fn hexes_in_range(
+        &self,
+        hex: Hex,
+        range: u32,
+    ) -> impl Iterator<Item = (Hex, &TileData)> + '_ {
+        hex.range(range)
+            .filter_map(move |hex| Some((hex, self.get_hex_tile_data(hex).ok()?)))
+    }
+
+    /// Gets the [`Entity`] for the given [`Hex`] if it exists.
+    pub fn get_hex_tile_entity(&self, hex: Hex) -> Result<Entity, TilemapManagerError> {
+        self.get_tile_entity(Cell::from(hex))
+    }
+
+    /// Gets the [`Entity`] for the given [`Hex`] if it exists or spawns one and returns that if it
+    /// doesn't.
+    pub fn get_or_spawn_hex_tile_entity(
+        &mut self,
+        hex: Hex,
+    ) -> Result<Entity, TilemapManagerError> {
+        self.get_or_spawn_tile_entity(Cell::from(hex))
+    }
+
+    /// Despawns the [`Entity`] for the given [`Hex`] if it exists and removes it from the chunk.
+    pub fn despawn_hex_tile_entity(&mut self, hex: Hex) -> Result<(), TilemapManagerError> {
+        self.despawn_tile_entity(Cell::from(hex))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::hex::map_chunk_layer::HexagonChunkSettings;
+    use crate::hex::map_data::HexMapData;
+    use crate::hex::{HexMapShape, HexOffset, HexTilemapBuilder, HexTilemapManager};
+    use crate::map::chunk::{ChunkLayer, ChunkLayerStorage, ChunkPos};
+    use crate::map::MapData;
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use crate::tilemap_manager::{MapAnchor, TilemapManagerError};
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::World;
+    use bevy::utils::HashMap;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+    use lettuces::{Hex, HexOrientation};
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+        Secondary,
+    }
+
+    #[test]
+    fn hex_access_across_chunks() {
+        for orientation in [HexOrientation::Pointy, HexOrientation::Flat] {
+            for offset in [HexOffset::Odd, HexOffset::Even] {
+                let map_data = HexMapData {
+                    max_chunk_size: UVec2::new(3, 3),
+                    orientation,
+                    offset,
+                    shape: HexMapShape::Rectangle,
+                };
+                // Each tile holds its own offset coordinates
+                let vecs: Vec<Vec<(i32, i32)>> =
+                    (0..6).map(|y| (0..6).map(|x| (x, y)).collect()).collect();
+
+                let mut world = World::new();
+                let mut system_state: SystemState<(
+                    Commands,
+                    HexTilemapManager<(i32, i32), MapLayers>,
+                )> = SystemState::new(&mut world);
+                let (mut commands, _) = system_state.get_mut(&mut world);
+                let tilemap_builder = HexTilemapBuilder::<(i32, i32), MapLayers>::new(
+                    TilemapLayer::new_dense_from_vecs(vecs),
+                    HexMapData {
+                        max_chunk_size: UVec2::new(3, 3),
+                        orientation,
+                        offset,
+                        shape: HexMapShape::Rectangle,
+                    },
+                    HexagonChunkSettings {
+                        orientation,
+                        offset,
+                        max_chunk_size: UVec2::new(3, 3),
+                    },
+                )
+                .unwrap();
+                let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+                system_state.apply(&mut world);
+
+                let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+                tilemap_manager.set_tilemap_entity(map_entity);
+
+                let hex = map_data.offset_coordinates_to_hex([2, 2]);
+                assert_eq!(tilemap_manager.get_hex_tile_data(hex).unwrap(), &(2, 2));
+
+                // [2, 2] is the corner of a chunk so its neighbours are spread over several chunks
+                let neighbours: Vec<(Hex, &(i32, i32))> =
+                    tilemap_manager.hex_neighbours(hex).collect();
+                assert_eq!(neighbours.len(), 6);
+                for (neighbour, tile_data) in neighbours {
+                    assert_eq!(hex.unsigned_distance_to(neighbour), 1);
+                    assert_eq!(
+                        map_data.hex_to_offset_coordinates(neighbour),
+                        [tile_data.0, tile_data.1]
+                    );
+                }
+                assert!(tilemap_manager
+                    .hex_neighbours(hex)
+                    .any(|(neighbour, _)| map_data.hex_to_chunk_cell(neighbour).0
+                        != map_data.hex_to_chunk_cell(hex).0));
+
+                // Neighbours outside of the map are skipped
+                let corner = map_data.offset_coordinates_to_hex([0, 0]);
+                assert!(tilemap_manager.hex_neighbours(corner).count() < 6);
+                assert_eq!(tilemap_manager.hexes_in_range(hex, 1).count(), 7);
+
+                let cube = map_data.offset_coordinates_to_cube([4, 3]);
+                let [x, y, z] = cube;
+                tilemap_manager
+                    .set_hex_tile_data((-1, -1), Hex::new_cubic(x, y, z))
+                    .unwrap();
+                assert_eq!(
+                    tilemap_manager
+                        .get_hex_tile_data(Hex::new_cubic(x, y, z))
+                        .unwrap(),
+                    &(-1, -1)
+                );
+                assert_eq!(map_data.cube_to_offset_coordinates(cube), [4, 3]);
+                assert!(
+                    tilemap_manager
+                        .iter_hex_layer()
+                        .unwrap()
+                        .any(|(hex, tile_data)| hex.to_cubic_array() == cube
+                            && tile_data == &(-1, -1))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hexagon_shaped_map() {
+        let radius = 4;
+        let shape = HexMapShape::Hexagon { radius };
+        let map_data = HexMapData {
+            max_chunk_size: UVec2::new(3, 3),
+            orientation: HexOrientation::Pointy,
+            offset: HexOffset::Even,
+            shape,
+        };
+        let dimensions = shape.dimensions().unwrap();
+        let center = map_data.hexagon_center().unwrap();
+        let vecs = vec![vec![(1, 1); dimensions.x as usize]; dimensions.y as usize];
+        // Every cell of the rectangle around the map, of which the corners are not part of the map
+        let mut sparse = HashMap::new();
+        for y in 0..dimensions.y as i32 {
+            for x in 0..dimensions.x as i32 {
+                sparse.insert(
+                    Cell::from(map_data.offset_coordinates_to_hex([x, y])),
+                    (x, y),
+                );
+            }
+        }
+
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, HexTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let mut tilemap_builder = HexTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vecs),
+            HexMapData {
+                max_chunk_size: UVec2::new(3, 3),
+                orientation: HexOrientation::Pointy,
+                offset: HexOffset::Even,
+                shape,
+            },
+            HexagonChunkSettings {
+                orientation: HexOrientation::Pointy,
+                offset: HexOffset::Even,
+                max_chunk_size: UVec2::new(3, 3),
+            },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(
+                    dimensions.x as usize,
+                    dimensions.y as usize,
+                    sparse,
+                ),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(9, 9));
+        assert_eq!(tilemap_manager.cell_count().unwrap(), 61);
+        assert_eq!(tilemap_manager.iter_hex_layer().unwrap().count(), 61);
+        assert_eq!(tilemap_manager.hexes_in_range(center, radius).count(), 61);
+
+        let corner = map_data.offset_coordinates_to_hex([0, 0]);
+        assert!(matches!(
+            tilemap_manager.get_hex_tile_data(corner),
+            Err(TilemapManagerError::CellOutsideMapShape(cell)) if cell == Cell::from(corner)
+        ));
+        assert!(matches!(
+            tilemap_manager.set_hex_tile_data((2, 2), corner),
+            Err(TilemapManagerError::CellOutsideMapShape(_))
+        ));
+        let edge = center + Hex::new(radius as i32, 0);
+        assert_eq!(tilemap_manager.get_hex_tile_data(edge).unwrap(), &(1, 1));
+        assert_eq!(tilemap_manager.hex_neighbours(edge).count(), 3);
+
+        // The sparse layer only stored the cells inside of the hexagon
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert_eq!(tilemap_manager.iter_hex_layer().unwrap().count(), 61);
+        assert!(tilemap_manager.get_hex_tile_data(corner).is_err());
+    }
+
+    #[test]
+    fn hexagon_shaped_map_layout() {
+        let radius = 4;
+        let shape = HexMapShape::Hexagon { radius };
+        let map_data = HexMapData {
+            max_chunk_size: UVec2::new(2, 2),
+            orientation: HexOrientation::Flat,
+            offset: HexOffset::Odd,
+            shape,
+        };
+        let chunk_settings = HexagonChunkSettings {
+            orientation: HexOrientation::Flat,
+            offset: HexOffset::Odd,
+            max_chunk_size: UVec2::new(2, 2),
+        };
+        let dimensions = shape.dimensions().unwrap();
+        let vecs = |size: UVec2| vec![vec![(1, 1); size.x as usize]; size.y as usize];
+
+        // The map data must be the size of the hexagon and start at the first chunk
+        assert!(matches!(
+            HexTilemapBuilder::<(i32, i32), MapLayers>::new(
+                TilemapLayer::new_dense_from_vecs(vecs(UVec2::new(8, 9))),
+                HexMapData { ..map_data },
+                chunk_settings,
+            ),
+            Err(TilemapManagerError::MapShapeMismatch(size, _)) if size == UVec2::new(8, 9)
+        ));
+        let mut tilemap_builder = HexTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vecs(dimensions)),
+            HexMapData { ..map_data },
+            chunk_settings,
+        )
+        .unwrap();
+        assert!(matches!(
+            tilemap_builder.set_origin(ChunkPos::new(-1, -1)),
+            Err(TilemapManagerError::MapShapeMismatch(_, origin)) if origin == ChunkPos::new(-1, -1)
+        ));
+        assert!(matches!(
+            tilemap_builder.add_layer(
+                TilemapLayer::new_dense_from_vecs(vecs(UVec2::new(9, 8))),
+                MapLayers::Secondary,
+            ),
+            Err(TilemapManagerError::LayerSizeMismatch(..))
+        ));
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_dense_from_vecs(vecs(dimensions)),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, HexTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        // Chunks without any cell of the hexagon don't store tiles
+        let mut empty_chunks = 0;
+        for y in 0..5 {
+            for x in 0..5 {
+                let chunk_pos = ChunkPos::new(x, y);
+                let chunk = tilemap_manager.get_chunk(chunk_pos).unwrap();
+                for (_, layer) in chunk.layers() {
+                    if map_data.chunk_in_shape(chunk_pos) {
+                        assert_eq!(layer.storage(), ChunkLayerStorage::Dense);
+                    } else {
+                        assert_eq!(layer.storage(), ChunkLayerStorage::Sparse);
+                        assert_eq!(layer.iter().count(), 0);
+                    }
+                }
+                if !map_data.chunk_in_shape(chunk_pos) {
+                    empty_chunks += 1;
+                }
+            }
+        }
+        assert!(empty_chunks > 0);
+        assert_eq!(tilemap_manager.iter_hex_layer().unwrap().count(), 61);
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert_eq!(tilemap_manager.iter_hex_layer().unwrap().count(), 61);
+
+        assert!(matches!(
+            tilemap_manager.resize(UVec2::new(11, 11), MapAnchor::Center, (0, 0)),
+            Err(TilemapManagerError::MapShapeMismatch(..))
+        ));
+    }
+}
@@ -0,0 +1,385 @@
+use bevy::{
+    math::{UVec2, Vec2},
+    prelude::Component,
+};
+
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::map::{
+    chunk::{Chunk, ChunkCell, ChunkPos},
+    Connectivity, MapData, MapLayer,
+};
+use crate::square::map_chunk_layer::SquareChunkSettings;
+use crate::square::map_data::SquareMapData;
+use lettuces::cell::Cell;
+
+use super::IsoLayout;
+
+/// [`MapData`] implementation for an isometric map. The cells of both [`IsoLayout`]s are stored in a grid, so the map
+/// is split into chunks the same way as a square map and only differs in where its cells are placed in the world.
+///
+/// World positions have y pointing up, with the center of cell (0, 0) at the world origin and the map extending
+/// downwards from there
+#[derive(Hash, Component)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Hash))]
+pub struct IsoMapData {
+    /// The maximum size that a chunk can be in the map
+    pub max_chunk_size: UVec2,
+    /// How the cells of the map are placed in the world
+    pub layout: IsoLayout,
+    /// The width and height of the diamond of a single tile in world units
+    pub tile_size: UVec2,
+}
+
+impl Default for IsoMapData {
+    fn default() -> Self {
+        Self {
+            max_chunk_size: UVec2::default(),
+            layout: IsoLayout::default(),
+            tile_size: UVec2::new(64, 32),
+        }
+    }
+}
+
+impl IsoMapData {
+    /// Returns the world position of the center of the given [`Cell`]
+    pub fn cell_to_world(&self, cell: Cell) -> Vec2 {
+        let half_tile = self.tile_size.as_vec2() / 2.0;
+        match self.layout {
+            IsoLayout::Diamond => Vec2::new(
+                (cell.x - cell.y) as f32 * half_tile.x,
+                -(cell.x + cell.y) as f32 * half_tile.y,
+            ),
+            IsoLayout::Staggered => Vec2::new(
+                cell.x as f32 * self.tile_size.x as f32 + cell.y.rem_euclid(2) as f32 * half_tile.x,
+                -cell.y as f32 * half_tile.y,
+            ),
+        }
+    }
+
+    /// Returns the [`Cell`] whose tile diamond contains the given world position
+    pub fn world_to_cell(&self, world_position: Vec2) -> Cell {
+        let half_tile = self.tile_size.as_vec2() / 2.0;
+        // Position in the diamond grid, where the diagonals of the tiles are the axes
+        let across = world_position.x / half_tile.x;
+        let down = -world_position.y / half_tile.y;
+        let x = ((down + across) / 2.0).round() as i32;
+        let y = ((down - across) / 2.0).round() as i32;
+        match self.layout {
+            IsoLayout::Diamond => Cell::new(x, y),
+            IsoLayout::Staggered => Self::diamond_to_staggered(Cell::new(x, y)),
+        }
+    }
+
+    /// Returns the key that the given [`Cell`] should be sorted by when drawing the map. Cells with a larger key are
+    /// in front of cells with a smaller key, cells with the same key don't overlap
+    pub fn depth_sort_key(&self, cell: Cell) -> i32 {
+        match self.layout {
+            IsoLayout::Diamond => cell.x + cell.y,
+            IsoLayout::Staggered => cell.y,
+        }
+    }
+
+    /// Converts a staggered [`Cell`] into the diamond [`Cell`] at the same world position
+    fn staggered_to_diamond(cell: Cell) -> Cell {
+        let across = 2 * cell.x + cell.y.rem_euclid(2);
+        Cell::new((cell.y + across) / 2, (cell.y - across) / 2)
+    }
+
+    /// Converts a diamond [`Cell`] into the staggered [`Cell`] at the same world position
+    fn diamond_to_staggered(cell: Cell) -> Cell {
+        let row = cell.x + cell.y;
+        Cell::new((cell.x - cell.y - row.rem_euclid(2)).div_euclid(2), row)
+    }
+
+    /// The [`SquareMapData`] that the grid of the map is split into chunks with
+    fn grid(&self) -> SquareMapData {
+        SquareMapData {
+            max_chunk_size: self.max_chunk_size,
+        }
+    }
+}
+
+impl From<&IsoMapData> for SquareChunkSettings {
+    fn from(map: &IsoMapData) -> Self {
+        Self {
+            max_chunk_size: map.max_chunk_size,
+        }
+    }
+}
+
+impl MapData for IsoMapData {
+    fn into_chunk_pos(&self, cell: Cell) -> ChunkPos {
+        self.grid().into_chunk_pos(cell)
+    }
+
+    fn into_cell(&self, chunk_pos: ChunkPos, chunk_cell: ChunkCell) -> Cell {
+        self.grid().into_cell(chunk_pos, chunk_cell)
+    }
+
+    fn cell_to_offset_coordinates(&self, cell: Cell) -> [i32; 2] {
+        [cell.x, cell.y]
+    }
+
+    fn offset_coordinates_to_cell(&self, offset_coordinates: [i32; 2]) -> Cell {
+        Cell::new(offset_coordinates[0], offset_coordinates[1])
+    }
+
+    /// The neighbours of a cell are the cells whose tiles share an edge or a corner with its tile, like in a square
+    /// grid turned by 45 degrees
+    fn neighbours(&self, cell: Cell, connectivity: Connectivity) -> Vec<Cell> {
+        match self.layout {
+            IsoLayout::Diamond => self.grid().neighbours(cell, connectivity),
+            IsoLayout::Staggered => self
+                .grid()
+                .neighbours(Self::staggered_to_diamond(cell), connectivity)
+                .into_iter()
+                .map(Self::diamond_to_staggered)
+                .collect(),
+        }
+    }
+
+    fn distance(&self, from: Cell, to: Cell, connectivity: Connectivity) -> u32 {
+        match self.layout {
+            IsoLayout::Diamond => self.grid().distance(from, to, connectivity),
+            IsoLayout::Staggered => self.grid().distance(
+                Self::staggered_to_diamond(from),
+                Self::staggered_to_diamond(to),
+                connectivity,
+            ),
+        }
+    }
+
+    fn max_chunk_size(&self) -> UVec2 {
+        self.max_chunk_size
+    }
+
+    fn set_max_chunk_size(&mut self, max_chunk_size: UVec2) {
+        self.max_chunk_size = max_chunk_size;
+    }
+
+    fn break_data_vecs_down_into_chunk_data<TileData>(
+        &self,
+        data: &[Vec<TileData>],
+        chunk_pos: ChunkPos,
+        max_chunk_size: UVec2,
+    ) -> Vec<Vec<TileData>>
+    where
+        TileData: Clone + Sized + Send + Sync + 'static,
+    {
+        self.grid()
+            .break_data_vecs_down_into_chunk_data(data, chunk_pos, max_chunk_size)
+    }
+
+    fn break_data_vecs_into_chunks<TileData, MapChunk>(
+        &self,
+        data: &[Vec<TileData>],
+        origin: ChunkPos,
+        max_chunk_size: UVec2,
+        chunk_settings: MapChunk::ChunkSettings,
+    ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
+    where
+        TileData: Clone + Sized + Send + Sync + 'static,
+        MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    {
+        self.grid()
+            .break_data_vecs_into_chunks(data, origin, max_chunk_size, chunk_settings)
+    }
+
+    fn break_hashmap_into_chunks<TileData, MapChunk>(
+        &self,
+        map_layer: impl MapLayer,
+        data: &bevy::utils::HashMap<Cell, TileData>,
+        origin: ChunkPos,
+        map_size: UVec2,
+        max_chunk_size: UVec2,
+        chunk_settings: MapChunk::ChunkSettings,
+    ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
+    where
+        TileData: Clone + Sized + Send + Sync + 'static,
+        MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    {
+        self.grid().break_hashmap_into_chunks(
+            map_layer,
+            data,
+            origin,
+            map_size,
+            max_chunk_size,
+            chunk_settings,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::iso::map_data::IsoMapData;
+    use crate::iso::{IsoChunkSettings, IsoLayout, IsoTilemapBuilder, IsoTilemapManager};
+    use crate::map::{Connectivity, MapData};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::{UVec2, Vec2};
+    use bevy::prelude::World;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    fn map_data(layout: IsoLayout) -> IsoMapData {
+        IsoMapData {
+            max_chunk_size: UVec2::new(4, 4),
+            layout,
+            tile_size: UVec2::new(64, 32),
+        }
+    }
+
+    #[test]
+    fn world_positions_round_trip() {
+        for layout in [IsoLayout::Diamond, IsoLayout::Staggered] {
+            let map_data = map_data(layout);
+            for y in -6..6 {
+                for x in -6..6 {
+                    let cell = Cell::new(x, y);
+                    let center = map_data.cell_to_world(cell);
+                    assert_eq!(map_data.world_to_cell(center), cell);
+                    // Points just inside of each corner of the tile diamond are still in the tile
+                    for offset in [
+                        Vec2::new(31.0, 0.0),
+                        Vec2::new(-31.0, 0.0),
+                        Vec2::new(0.0, 15.0),
+                        Vec2::new(0.0, -15.0),
+                    ] {
+                        assert_eq!(map_data.world_to_cell(center + offset), cell);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cell_world_positions() {
+        let diamond = map_data(IsoLayout::Diamond);
+        assert_eq!(
+            diamond.cell_to_world(Cell::new(1, 0)),
+            Vec2::new(32.0, -16.0)
+        );
+        assert_eq!(
+            diamond.cell_to_world(Cell::new(0, 1)),
+            Vec2::new(-32.0, -16.0)
+        );
+        assert_eq!(
+            diamond.cell_to_world(Cell::new(1, 1)),
+            Vec2::new(0.0, -32.0)
+        );
+
+        let staggered = map_data(IsoLayout::Staggered);
+        assert_eq!(
+            staggered.cell_to_world(Cell::new(1, 0)),
+            Vec2::new(64.0, 0.0)
+        );
+        assert_eq!(
+            staggered.cell_to_world(Cell::new(0, 1)),
+            Vec2::new(32.0, -16.0)
+        );
+        assert_eq!(
+            staggered.cell_to_world(Cell::new(0, 2)),
+            Vec2::new(0.0, -32.0)
+        );
+        assert_eq!(
+            staggered.cell_to_world(Cell::new(0, -1)),
+            Vec2::new(32.0, 16.0)
+        );
+    }
+
+    #[test]
+    fn depth_sort_keys_put_lower_cells_in_front() {
+        for layout in [IsoLayout::Diamond, IsoLayout::Staggered] {
+            let map_data = map_data(layout);
+            for y in -4..4 {
+                for x in -4..4 {
+                    let cell = Cell::new(x, y);
+                    let below = map_data.cell_to_world(cell) - Vec2::new(0.0, 32.0);
+                    assert!(
+                        map_data.depth_sort_key(map_data.world_to_cell(below))
+                            > map_data.depth_sort_key(cell)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_touch_the_tile() {
+        for layout in [IsoLayout::Diamond, IsoLayout::Staggered] {
+            let map_data = map_data(layout);
+            for cell in [Cell::new(0, 0), Cell::new(3, 1), Cell::new(-2, -3)] {
+                let center = map_data.cell_to_world(cell);
+                let mut offsets: Vec<(i32, i32)> = map_data
+                    .neighbours(cell, Connectivity::Vertices)
+                    .into_iter()
+                    .map(|neighbour| {
+                        let offset = map_data.cell_to_world(neighbour) - center;
+                        (offset.x as i32, offset.y as i32)
+                    })
+                    .collect();
+                offsets.sort();
+                // The 4 tiles sharing an edge and the 4 tiles sharing a corner
+                assert_eq!(
+                    offsets,
+                    vec![
+                        (-64, 0),
+                        (-32, -16),
+                        (-32, 16),
+                        (0, -32),
+                        (0, 32),
+                        (32, -16),
+                        (32, 16),
+                        (64, 0)
+                    ]
+                );
+                for neighbour in map_data.neighbours(cell, Connectivity::Edges) {
+                    let offset = map_data.cell_to_world(neighbour) - center;
+                    assert_eq!(offset.abs(), Vec2::new(32.0, 16.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn iso_tilemap_access() {
+        let vecs: Vec<Vec<(i32, i32)>> =
+            (0..10).map(|y| (0..10).map(|x| (x, y)).collect()).collect();
+
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, IsoTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let tilemap_builder = IsoTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vecs),
+            map_data(IsoLayout::Staggered),
+            IsoChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        )
+        .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(10, 10));
+        let cell =
+            map_data(IsoLayout::Staggered).world_to_cell(Vec2::new(5.0 * 64.0 + 32.0, -7.0 * 16.0));
+        assert_eq!(cell, Cell::new(5, 7));
+        assert_eq!(tilemap_manager.get_tile_data(cell).unwrap(), &(5, 7));
+    }
+}
@@ -0,0 +1,48 @@
+use map_data::IsoMapData;
+
+use crate::{
+    map::chunk::Chunk,
+    square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings},
+    tilemap_builder::TilemapBuilder,
+    tilemap_manager::TilemapManager,
+};
+
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Implements [`MapData`](crate::map::MapData) for an isometric map
+pub mod map_data;
+
+/// Type alias for the [`ChunkLayer`](crate::map::chunk::ChunkLayer) of an isometric map. Both isometric layouts
+/// store their cells in a grid so they use the same chunk layer as a square map
+pub type IsoChunkLayer<TileData> = SquareChunkLayer<TileData>;
+
+/// Type alias for the chunk settings of an [`IsoChunkLayer`]
+pub type IsoChunkSettings = SquareChunkSettings;
+
+/// Type alias for [`TilemapManager`] for the built in isometric map types.
+pub type IsoTilemapManager<'w, 's, TileData, MapLayers> =
+    TilemapManager<'w, 's, TileData, MapLayers, IsoChunkLayer<TileData>, IsoMapData>;
+
+/// Type alias for [`Chunk`] using the built in [`IsoChunkLayer`]
+pub type IsoChunk<TileData> = Chunk<IsoChunkLayer<TileData>, TileData>;
+
+/// Type alias for [`TilemapBuilder`] for the built in isometric map types
+pub type IsoTilemapBuilder<TileData, MapLayers> =
+    TilemapBuilder<TileData, MapLayers, IsoChunkLayer<TileData>, IsoMapData>;
+
+/// How the cells of an isometric map are placed in the world
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub enum IsoLayout {
+    /// The grid is rotated 45 degrees so the map is a diamond. Moving along x goes down and to the right in the world,
+    /// moving along y goes down and to the left
+    #[default]
+    Diamond,
+    /// Rows of tiles are stacked half a tile apart with every odd row shifted right by half a tile so the map is a
+    /// rectangle. Moving along x goes right in the world, moving along y goes down
+    Staggered,
+}
@@ -7,6 +7,7 @@
     unused_qualifications,
     clippy::unwrap_used
 )]
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::type_complexity))]
 #![doc = include_str!("../README.md")]
 //! # Usage Docs
 //!
@@ -44,7 +45,7 @@
 //! #     Secondary,
 //! # }
 //! #
-//! # #[derive(Default, Copy, Clone, Reflect, Hash)]
+//! # #[derive(Default, Copy, Clone, Reflect, Hash, PartialEq)]
 //! # struct TileData(u8, u8);
 //! #
 //! #
@@ -60,7 +61,7 @@
 //!         SquareChunkSettings {
 //!             max_chunk_size: UVec2 { x: 100, y: 100 },
 //!             }
-//!     );
+//!     ).unwrap();
 //!
 //!     let Some(tilemap) = tilemap_builder.spawn_tilemap(&mut commands)
 //!         else {
@@ -92,7 +93,7 @@
 //! #     Secondary,
 //! # }
 //! #
-//! # #[derive(Default, Copy, Clone, Reflect, Hash)]
+//! # #[derive(Default, Copy, Clone, Reflect, Hash, PartialEq)]
 //! # struct TileData(u8, u8);
 //! # #[derive(Resource)]
 //! # pub struct MapEntity(Entity);
@@ -111,17 +112,29 @@
 //! ```
 //!
 
+pub mod geometry;
 /// Implements a hexagonal map type. See the [Hexagon Example](https://github.com/NoahShomette/bevy_sparse_tilemap/blob/main/examples/hexagon.rs) for an overview of how to use it
 #[cfg(feature = "hex")]
 pub mod hex;
+/// Implements an isometric map type with diamond and staggered layouts. Requires the `iso` feature
+#[cfg(feature = "iso")]
+pub mod iso;
 pub mod map;
+/// Pathfinding across tilemaps. See [`TilemapManager::find_path`](crate::tilemap_manager::TilemapManager::find_path) and the [`HierarchicalPathfinder`](crate::pathfinding::HierarchicalPathfinder) for more details
+pub mod pathfinding;
+/// Flood fill and connected region labelling. See [`TilemapManager::flood_fill`](crate::tilemap_manager::TilemapManager::flood_fill) and [`TilemapManager::label_regions`](crate::tilemap_manager::TilemapManager::label_regions) for more details
+pub mod regions;
 /// Implements a square map type. See the [Square Example](https://github.com/NoahShomette/bevy_sparse_tilemap/blob/main/examples/square.rs) for an overview of how to use it
 #[cfg(feature = "square")]
 pub mod square;
+pub mod streaming;
 /// A helper used to construct new tilemaps. See [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder) for more details
 pub mod tilemap_builder;
 /// A system param used to interact with tilemaps. See [`TilemapManager`](crate::tilemap_manager::TilemapManager) for more details
 pub mod tilemap_manager;
+/// Implements a map type of alternating up and down pointing triangles. Requires the `triangle` feature
+#[cfg(feature = "triangle")]
+pub mod triangle;
 
 pub use bst_map_layer_derive::MapLayer;
 /// Re-export [Lettuces](https://crates.io/crates/lettuces)
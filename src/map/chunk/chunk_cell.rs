@@ -21,7 +21,7 @@ pub struct ChunkCell(Cell);
 impl ChunkCell {
     /// Constructs a new ChunkTilePos from the given x and y
     pub fn new(x: i32, y: i32) -> ChunkCell {
-        Self { 0: Cell { x, y } }
+        Self(Cell { x, y })
     }
     /// Returns the x position of Self
     pub fn x(&self) -> i32 {
@@ -31,27 +31,33 @@ impl ChunkCell {
     pub fn y(&self) -> i32 {
         self.0.y
     }
+    /// Packs the x and y position of Self into a single u64. Used as the key for sparse chunk storage
+    pub fn as_u64(&self) -> u64 {
+        ((self.0.x as u32 as u64) << 32) | self.0.y as u32 as u64
+    }
+    /// Unpacks a [`ChunkCell`] from a u64 created with [`as_u64`](ChunkCell::as_u64)
+    pub fn from_u64(number: u64) -> ChunkCell {
+        Self::new((number >> 32) as u32 as i32, number as u32 as i32)
+    }
 }
 
 impl From<IVec2> for ChunkCell {
     fn from(value: IVec2) -> Self {
-        Self {
-            0: Cell {
-                x: value.x,
-                y: value.y,
-            },
-        }
+        Self(Cell {
+            x: value.x,
+            y: value.y,
+        })
     }
 }
 
-impl Into<(usize, usize)> for ChunkCell {
-    fn into(self) -> (usize, usize) {
-        (self.0.x as usize, self.0.y as usize)
+impl From<ChunkCell> for (usize, usize) {
+    fn from(value: ChunkCell) -> Self {
+        (value.0.x as usize, value.0.y as usize)
     }
 }
 
 impl Display for ChunkCell {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&*format!("x:{}, y:{}", self.0.x, self.0.y))
+        f.write_str(&format!("x:{}, y:{}", self.0.x, self.0.y))
     }
 }
@@ -5,16 +5,99 @@ use lettuces::cell::Cell;
 
 use super::ChunkCell;
 
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Compares two `TileData` for equality.
+///
+/// `TileData` does not have to implement `PartialEq`, so layers that have to compare tiles are handed the comparison
+/// by the functions that need it, usually `TileData::eq`
+pub type TileDataEq<T> = fn(&T, &T) -> bool;
+
 /// The data for a specific chunk. Contains only the data for that chunk
 pub enum ChunkLayerType<T> {
     /// A layer where ***NOT*** every position on the chunk has data
     Dense(Vec<Vec<T>>),
     /// A layer where ***EVERY***  position on the chunk must have data
     Sparse(HashMap<ChunkCell, T>),
+    /// A layer where every position has data, stored as a palette of the unique values in the chunk. Tiles that
+    /// are equal according to the [`TileDataEq`] share a palette entry
+    Palette(Vec<Vec<T>>, TileDataEq<T>),
+}
+
+/// The kind of storage that a chunk layer uses. See [`ChunkLayerType`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub enum ChunkLayerStorage {
+    /// Every position on the chunk has data
+    Dense,
+    /// Not every position on the chunk has data
+    Sparse,
+    /// Every position on the chunk has data, stored as a palette of the unique values in the chunk
+    Palette,
+}
+
+/// Policy used to automatically convert a chunk layer between sparse and dense storage.
+///
+/// The fill ratio of a layer is the amount of tiles holding data divided by the amount of tiles in the chunk.
+/// A tile in a dense layer only counts as holding data if it is not equal to the
+/// [default tile data](ChunkLayer::default_tile_data) of the layer.
+///
+/// # Note
+/// - Converting a layer to sparse storage drops every tile equal to the default tile data
+/// - Converting a layer to dense storage fills every tile without data with the default tile data
+/// - Layers without default tile data are never converted
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub struct ConversionPolicy {
+    /// Sparse layers with a fill ratio above this are converted into dense storage
+    pub dense_above: f32,
+    /// Dense layers with a fill ratio below this are converted into sparse storage
+    pub sparse_below: f32,
+}
+
+impl Default for ConversionPolicy {
+    fn default() -> Self {
+        Self {
+            dense_above: 0.75,
+            sparse_below: 0.25,
+        }
+    }
+}
+
+impl ConversionPolicy {
+    /// Creates a new [`ConversionPolicy`] with the given thresholds
+    ///
+    /// # Panics
+    /// - If `sparse_below` is not less than `dense_above`
+    pub fn new(dense_above: f32, sparse_below: f32) -> Self {
+        assert!(
+            sparse_below < dense_above,
+            "sparse_below must be less than dense_above"
+        );
+        Self {
+            dense_above,
+            sparse_below,
+        }
+    }
+
+    /// Returns true if a sparse layer with `filled_tiles` out of `total_tiles` holding data should be converted to dense storage
+    pub fn should_convert_to_dense(&self, filled_tiles: usize, total_tiles: usize) -> bool {
+        total_tiles != 0 && filled_tiles as f32 / total_tiles as f32 > self.dense_above
+    }
+
+    /// Returns true if a dense layer with `filled_tiles` out of `total_tiles` holding data should be converted to sparse storage
+    pub fn should_convert_to_sparse(&self, filled_tiles: usize, total_tiles: usize) -> bool {
+        total_tiles != 0 && (filled_tiles as f32 / total_tiles as f32) < self.sparse_below
+    }
 }
 
 /// Trait that controls access to a specific layer of a tilemap chunk.
-pub trait ChunkLayer<TileData>: Hash + MapEntities {
+pub trait ChunkLayer<TileData>: MapEntities {
     /// Settings needed by the chunk to:
     ///
     /// - Convert a [`Cell`] into a [`ChunkCell`]
@@ -24,6 +107,12 @@ pub trait ChunkLayer<TileData>: Hash + MapEntities {
     /// Converts a [`Cell`] into a [`ChunkCell`]
     fn into_chunk_cell(cell: Cell, chunk_settings: &Self::ChunkSettings) -> ChunkCell;
 
+    /// Returns a copy of the given chunk settings that uses the given maximum chunk size
+    fn with_max_chunk_size(
+        chunk_settings: Self::ChunkSettings,
+        max_chunk_size: UVec2,
+    ) -> Self::ChunkSettings;
+
     /// Creates a new chunk out of the given [`ChunkLayerType`]. The `TileData` contained in the layer is only the data that this chunk should contain.
     fn new(
         layer_type: ChunkLayerType<TileData>,
@@ -34,6 +123,9 @@ pub trait ChunkLayer<TileData>: Hash + MapEntities {
     /// Returns the dimensions of this specific chunk
     fn get_chunk_dimensions(&self) -> UVec2;
 
+    /// Returns the kind of storage the layer currently uses
+    fn storage(&self) -> ChunkLayerStorage;
+
     /// Gets mutable access to the `TileData` at the given [`ChunkCell`]
     fn get_tile_data_mut(&mut self, chunk_cell: ChunkCell) -> Option<&mut TileData>;
 
@@ -43,9 +135,111 @@ pub trait ChunkLayer<TileData>: Hash + MapEntities {
     /// Sets the `TileData` at the given [`ChunkCell`]
     fn set_tile_data(&mut self, chunk_cell: ChunkCell, tile_data: TileData);
 
+    /// Returns an iterator over every tile in the layer that holds data along with its [`ChunkCell`].
+    ///
+    /// Sparse layers only visit occupied cells while dense layers visit every cell in the chunk
+    fn iter(&self) -> Box<dyn Iterator<Item = (ChunkCell, &TileData)> + '_>;
+
+    /// Calls `f` with a mutable reference to every tile in the layer that holds data along with its [`ChunkCell`].
+    /// See [`iter`](ChunkLayer::iter)
+    ///
+    /// Palette layers share one value between many tiles so they can't hand out a reference to each tile. They hand
+    /// out a copy of each tile instead and only write the tiles that changed back, so they stay palette compressed
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(ChunkCell, &mut TileData));
+
+    /// Removes the `TileData` at the given [`ChunkCell`] and returns it
+    ///
+    /// # Note
+    /// - Dense and palette layers must have data at every position so the tile is reset to the
+    ///   [default tile data](ChunkLayer::default_tile_data) of the layer instead. Their tiles can't be removed
+    ///   when the layer has no default tile data, so `None` is returned and the tile is left untouched
+    fn take_tile_data(&mut self, chunk_cell: ChunkCell) -> Option<TileData>;
+
+    /// Removes the `TileData` at the given [`ChunkCell`]. See [`take_tile_data`](ChunkLayer::take_tile_data)
+    fn remove_tile_data(&mut self, chunk_cell: ChunkCell) {
+        self.take_tile_data(chunk_cell);
+    }
+
+    /// Removes all the `TileData` in the layer. Dense and palette layers are reset to the
+    /// [default tile data](ChunkLayer::default_tile_data) of the layer instead
+    ///
+    /// # Note
+    /// - Tile entities are left untouched
+    /// - Dense and palette layers without default tile data are left untouched
+    fn clear_tile_data(&mut self);
+
+    /// Returns the `TileData` that tiles of dense and palette layers are reset to when their data is removed, if
+    /// the layer has one
+    fn default_tile_data(&self) -> Option<&TileData>;
+
+    /// Sets the `TileData` that tiles of dense and palette layers are reset to when their data is removed. See
+    /// [`default_tile_data`](ChunkLayer::default_tile_data)
+    fn set_default_tile_data(&mut self, tile_data: Option<TileData>);
+
+    /// Returns an iterator over every [`ChunkCell`] whose `TileData` was set, taken, cleared or mutably
+    /// accessed since the dirty cells were last cleared
+    ///
+    /// # Note
+    /// - Only layers that [track their dirty cells](ChunkLayer::set_track_dirty_cells) record any
+    /// - Cells are only cleared through [`clear_dirty_cells`](ChunkLayer::clear_dirty_cells). The
+    ///   [`send_tile_changed_events`](crate::map::send_tile_changed_events) system does this every time it runs
+    fn dirty_cells(&self) -> Box<dyn Iterator<Item = ChunkCell> + '_>;
+
+    /// Clears the dirty cells of the layer. See [`dirty_cells`](ChunkLayer::dirty_cells)
+    fn clear_dirty_cells(&mut self);
+
+    /// Sets whether the layer records its [dirty cells](ChunkLayer::dirty_cells). Off by default so layers that
+    /// nothing reads the changes of don't collect them, turning it off clears the recorded dirty cells
+    fn set_track_dirty_cells(&mut self, track_dirty_cells: bool);
+
+    /// Returns whether the layer records its [dirty cells](ChunkLayer::dirty_cells)
+    fn tracks_dirty_cells(&self) -> bool;
+
     /// Gets the [`Entity`] at the given [`ChunkCell`]
     fn get_tile_entity(&self, chunk_cell: ChunkCell) -> Option<Entity>;
 
     /// Sets the [`Entity`] at the given [`ChunkCell`]
     fn set_tile_entity(&mut self, chunk_cell: ChunkCell, entity: Entity);
+
+    /// Removes the [`Entity`] mapping at the given [`ChunkCell`] and returns it. Does not despawn the entity
+    fn remove_tile_entity(&mut self, chunk_cell: ChunkCell) -> Option<Entity>;
+
+    /// Returns an iterator over every tile [`Entity`] mapped in the layer along with its [`ChunkCell`]
+    fn iter_tile_entities(&self) -> Box<dyn Iterator<Item = (ChunkCell, Entity)> + '_>;
+
+    /// Sets the [`ConversionPolicy`] that is checked during [`set_tile_data`](ChunkLayer::set_tile_data) to
+    /// convert the layer between sparse and dense storage. `None` disables automatic conversion.
+    ///
+    /// Tiles are compared to the [default tile data](ChunkLayer::default_tile_data) of the layer with
+    /// `TileData::eq`, see [`set_conversion_policy_with`](ChunkLayer::set_conversion_policy_with)
+    fn set_conversion_policy(&mut self, conversion_policy: Option<ConversionPolicy>)
+    where
+        TileData: PartialEq,
+    {
+        self.set_conversion_policy_with(conversion_policy, TileData::eq);
+    }
+
+    /// Sets the [`ConversionPolicy`] of the layer along with the [`TileDataEq`] used to compare tiles to the
+    /// [default tile data](ChunkLayer::default_tile_data) of the layer. See
+    /// [`set_conversion_policy`](ChunkLayer::set_conversion_policy)
+    ///
+    /// # Note
+    /// - Does nothing by default. Layers that support conversion must override this
+    fn set_conversion_policy_with(
+        &mut self,
+        _conversion_policy: Option<ConversionPolicy>,
+        _tile_data_eq: TileDataEq<TileData>,
+    ) {
+    }
+
+    /// Returns the [`ConversionPolicy`] of the layer if it has one
+    fn conversion_policy(&self) -> Option<ConversionPolicy> {
+        None
+    }
+
+    /// Returns the [`TileDataEq`] the layer compares tiles with, if it was given one by a
+    /// [`ConversionPolicy`] or a palette
+    fn tile_data_eq(&self) -> Option<TileDataEq<TileData>> {
+        None
+    }
 }
@@ -3,16 +3,17 @@
 mod chunk_cell;
 mod chunk_pos;
 mod layer_data;
+mod palette;
 
 pub use crate::map::chunk::chunk_cell::ChunkCell;
 pub use crate::map::chunk::chunk_pos::ChunkPos;
 use crate::map::MapLayer;
 use bevy::ecs::entity::{EntityMapper, MapEntities};
 use bevy::prelude::{Component, Entity, UVec2};
-use bevy::utils::hashbrown::HashMap;
-pub use layer_data::{ChunkLayer, ChunkLayerType};
+pub use layer_data::{ChunkLayer, ChunkLayerStorage, ChunkLayerType, ConversionPolicy, TileDataEq};
 use lettuces::cell::Cell;
 use lettuces::storage::grid::Grid;
+pub use palette::PaletteStorage;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 
@@ -25,20 +26,25 @@ use bevy::ecs::reflect::ReflectMapEntities;
 use bevy::prelude::{Reflect, ReflectComponent};
 
 /// The chunks of a tilemap. Does not contain the actual data but contains mappings from [`ChunkPos`] -> chunk entity
+///
+/// Every [`ChunkPos`] inside the bounds of the map has a slot. A slot is `None` while the chunk is not
+/// loaded, eg when chunks are streamed in and out with [`stream_chunks`](crate::streaming::stream_chunks)
 #[derive(Clone, Component, Hash, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "reflect", derive(Reflect))]
 #[cfg_attr(feature = "reflect", reflect(Hash, MapEntities))]
 pub struct Chunks {
-    /// A grid of [`Entity`] references pointing to each chunks entity
-    chunk_entities: Grid<Entity>,
+    /// A grid of [`Entity`] references pointing to each chunks entity. `None` if the chunk is not loaded
+    chunk_entities: Grid<Option<Entity>>,
     /// The max size that a chunk can be
     max_chunk_size: UVec2,
+    /// The [`ChunkPos`] of the chunk stored at the start of `chunk_entities`
+    origin: ChunkPos,
 }
 
 impl MapEntities for Chunks {
     fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
-        for tile_entity in self.chunk_entities.iter_mut() {
+        for tile_entity in self.chunk_entities.iter_mut().flatten() {
             *tile_entity = entity_mapper.map_entity(*tile_entity);
         }
     }
@@ -47,15 +53,16 @@ impl MapEntities for Chunks {
 impl Default for Chunks {
     fn default() -> Self {
         Self {
-            chunk_entities: Grid::<Entity>::init(0, 0, Entity::PLACEHOLDER),
+            chunk_entities: Grid::<Option<Entity>>::init(0, 0, None),
             max_chunk_size: Default::default(),
+            origin: Default::default(),
         }
     }
 }
 
 impl Chunks {
-    /// Creates a new [`Grid<Entity>`] out of a vector of vectors of [`Entity`]
-    pub fn new_chunk_entity_grid(chunk_entities: Vec<Vec<Entity>>) -> Grid<Entity> {
+    /// Creates a new [`Grid<Option<Entity>>`] out of a vector of vectors of [`Entity`]
+    pub fn new_chunk_entity_grid(chunk_entities: Vec<Vec<Entity>>) -> Grid<Option<Entity>> {
         let mut counted_chunks = 0u64;
 
         for chunk_row in chunk_entities.iter() {
@@ -67,11 +74,8 @@ impl Chunks {
             counted_chunks
         );
 
-        let mut grid: Grid<Entity> = Grid::init(
-            chunk_entities.len(),
-            chunk_entities[0].len(),
-            Entity::PLACEHOLDER,
-        );
+        let mut grid: Grid<Option<Entity>> =
+            Grid::init(chunk_entities.len(), chunk_entities[0].len(), None);
         let mut current_x = 0usize;
         let mut current_y = 0usize;
         let row_length = chunk_entities[0].len();
@@ -82,29 +86,123 @@ impl Chunks {
                 current_x = 0;
                 current_y += 1;
             }
-            tile
+            Some(tile)
         });
         grid
     }
 
-    /// Creates a new Chunks component
-    pub fn new(chunk_entity_grid: Grid<Entity>, max_chunk_size: UVec2) -> Self {
+    /// Creates a new Chunks component whose first chunk is at [`ChunkPos`] (0, 0)
+    pub fn new(chunk_entity_grid: Grid<Option<Entity>>, max_chunk_size: UVec2) -> Self {
+        Self::new_with_origin(chunk_entity_grid, max_chunk_size, ChunkPos::default())
+    }
+
+    /// Creates a new Chunks component whose first chunk is at the given origin [`ChunkPos`]
+    pub fn new_with_origin(
+        chunk_entity_grid: Grid<Option<Entity>>,
+        max_chunk_size: UVec2,
+        origin: ChunkPos,
+    ) -> Self {
         Self {
             chunk_entities: chunk_entity_grid,
             max_chunk_size,
+            origin,
         }
     }
 
+    /// Creates a new Chunks component covering `chunk_counts` chunks starting at the given origin
+    /// [`ChunkPos`] where no chunks are loaded yet. Streamed tilemaps are built this way by
+    /// [`TilemapBuilder::new_streamed`](crate::tilemap_builder::TilemapBuilder::new_streamed)
+    pub fn new_unloaded(chunk_counts: UVec2, max_chunk_size: UVec2, origin: ChunkPos) -> Self {
+        Self::new_with_origin(
+            Grid::init(chunk_counts.y as usize, chunk_counts.x as usize, None),
+            max_chunk_size,
+            origin,
+        )
+    }
+
     /// Returns the max_chunk_size
     pub fn max_chunk_size(&self) -> UVec2 {
         self.max_chunk_size
     }
 
-    /// Gets the chunk entity for the given [`ChunkPos`] if it exists
+    /// Returns the [`ChunkPos`] of the first chunk in the map
+    pub fn origin(&self) -> ChunkPos {
+        self.origin
+    }
+
+    /// Gets the chunk entity for the given [`ChunkPos`] if it exists and is loaded
     pub fn get_chunk(&self, chunk_pos: ChunkPos) -> Option<Entity> {
+        let (row, col) = self.grid_index(chunk_pos)?;
+        self.chunk_entities.get(row, col).cloned().flatten()
+    }
+
+    /// Returns true if the given [`ChunkPos`] is inside the bounds of the map, whether it is loaded or not
+    pub fn contains(&self, chunk_pos: ChunkPos) -> bool {
+        self.grid_index(chunk_pos).is_some()
+    }
+
+    /// Returns true if the chunk at the given [`ChunkPos`] is loaded
+    pub fn is_loaded(&self, chunk_pos: ChunkPos) -> bool {
+        self.get_chunk(chunk_pos).is_some()
+    }
+
+    /// Sets the chunk entity for the given [`ChunkPos`], returning the previous chunk entity if there was one.
+    /// Setting `None` marks the chunk as unloaded
+    ///
+    /// # Panics
+    /// - If the [`ChunkPos`] is outside the bounds of the map
+    pub fn set_chunk(
+        &mut self,
+        chunk_pos: ChunkPos,
+        chunk_entity: Option<Entity>,
+    ) -> Option<Entity> {
+        let (row, col) = self
+            .grid_index(chunk_pos)
+            .expect("ChunkPos must be inside the bounds of the map");
+        std::mem::replace(&mut self.chunk_entities[(row, col)], chunk_entity)
+    }
+
+    /// Returns an iterator over the [`ChunkPos`] and entity of every loaded chunk
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = (ChunkPos, Entity)> + '_ {
+        self.iter_slots()
+            .filter_map(|(chunk_pos, entity)| entity.map(|entity| (chunk_pos, entity)))
+    }
+
+    /// Returns an iterator over the [`ChunkPos`] of every chunk inside the bounds of the map that is not loaded
+    pub fn unloaded_chunks(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        self.iter_slots()
+            .filter(|(_, entity)| entity.is_none())
+            .map(|(chunk_pos, _)| chunk_pos)
+    }
+
+    fn iter_slots(&self) -> impl Iterator<Item = (ChunkPos, Option<Entity>)> + '_ {
+        let cols = self.chunk_entities.cols();
         self.chunk_entities
-            .get(chunk_pos.y() as usize, chunk_pos.x() as usize)
-            .cloned()
+            .iter()
+            .enumerate()
+            .map(move |(index, entity)| {
+                (
+                    ChunkPos::new(
+                        self.origin.x() + (index % cols) as i32,
+                        self.origin.y() + (index / cols) as i32,
+                    ),
+                    *entity,
+                )
+            })
+    }
+
+    /// Converts the [`ChunkPos`] into a (row, column) index into the chunk grid if it is in bounds
+    fn grid_index(&self, chunk_pos: ChunkPos) -> Option<(usize, usize)> {
+        let x = chunk_pos.x().checked_sub(self.origin.x())?;
+        let y = chunk_pos.y().checked_sub(self.origin.y())?;
+        if x.is_negative()
+            || y.is_negative()
+            || y as usize >= self.chunk_entities.rows()
+            || x as usize >= self.chunk_entities.cols()
+        {
+            return None;
+        }
+        Some((y as usize, x as usize))
     }
 
     /// Returns the x and y count of chunks
@@ -122,16 +220,16 @@ impl Chunks {
 #[derive(Component)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "reflect", derive(Reflect))]
-#[cfg_attr(feature = "reflect", reflect(Component, Hash, MapEntities))]
+#[cfg_attr(feature = "reflect", reflect(Component, MapEntities))]
 pub struct Chunk<MapChunk, TileData>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    TileData: Clone + Sized + Send + Sync,
     MapChunk: ChunkLayer<TileData> + Send + Sync + Default,
 {
     /// The position of the Chunk in the map
     pub chunk_pos: ChunkPos,
-    /// Chunk tile data mapped to layers
-    pub data: HashMap<u32, MapChunk>,
+    /// Chunk tile data indexed by [`MapLayer::to_index`]. Layers that do not exist in the chunk are `None`
+    pub data: Vec<Option<MapChunk>>,
     /// Settings related to the chunk
     pub chunk_settings: MapChunk::ChunkSettings,
     #[cfg_attr(feature = "reflect", reflect(ignore))]
@@ -140,24 +238,23 @@ where
 
 impl<MapChunk, TileData> MapEntities for Chunk<MapChunk, TileData>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    TileData: Clone + Sized + Send + Sync,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
 {
     fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
-        for datum in self.data.iter_mut() {
-            datum.1.map_entities(entity_mapper);
+        for (_, layer) in self.layers_mut() {
+            layer.map_entities(entity_mapper);
         }
     }
 }
 
 impl<MapChunk, TileData> Hash for Chunk<MapChunk, TileData>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync,
-    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    TileData: Clone + Sized + Send + Sync,
+    MapChunk: ChunkLayer<TileData> + Hash + Send + Sync + 'static + Default,
 {
     fn hash<H: Hasher>(&self, h: &mut H) {
-        let mut pairs: Vec<_> = self.data.iter().collect();
-        pairs.sort_by_key(|i| i.0);
+        let pairs: Vec<_> = self.layers().collect();
         Hash::hash(&pairs, h);
         Hash::hash(&self.chunk_pos, h);
     }
@@ -165,13 +262,13 @@ where
 
 impl<MapChunk, TileData> Default for Chunk<MapChunk, TileData>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    TileData: Clone + Sized + Send + Sync,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
 {
     fn default() -> Self {
         Self {
             chunk_pos: Default::default(),
-            data: HashMap::default(),
+            data: Vec::new(),
             chunk_settings: MapChunk::ChunkSettings::default(),
             ph: Default::default(),
         }
@@ -180,7 +277,7 @@ where
 
 impl<MapChunk, TileData> Chunk<MapChunk, TileData>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    TileData: Clone + Sized + Send + Sync,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
 {
     /// Creates a new chunk with the given data. chunk size represents the actual size of the chunk object.
@@ -190,11 +287,9 @@ where
         tile_data: ChunkLayerType<TileData>,
         chunk_settings: MapChunk::ChunkSettings,
     ) -> Chunk<MapChunk, TileData> {
-        let mut hashmap = HashMap::new();
-        hashmap.insert(1u32, MapChunk::new(tile_data, chunk_size, &chunk_settings));
         Self {
             chunk_pos,
-            data: hashmap,
+            data: vec![Some(MapChunk::new(tile_data, chunk_size, &chunk_settings))],
             chunk_settings,
             ph: Default::default(),
         }
@@ -204,28 +299,80 @@ where
     ///
     /// # Note
     /// - Overwrites the layer if it already exists
-    pub fn add_layer(&mut self, map_layer: u32, tile_data: ChunkLayerType<TileData>) {
-        self.data.insert(
-            map_layer,
-            MapChunk::new(tile_data, self.get_chunk_dimensions(), &self.chunk_settings),
-        );
+    pub fn add_layer(&mut self, map_layer: usize, tile_data: ChunkLayerType<TileData>) {
+        let layer = MapChunk::new(tile_data, self.get_chunk_dimensions(), &self.chunk_settings);
+        self.insert_layer(map_layer, layer);
+    }
+
+    /// Inserts the given [`ChunkLayer`] into the chunk at the given layer index, returning the previous layer if
+    /// there was one.
+    pub fn insert_layer(&mut self, map_layer: usize, layer: MapChunk) -> Option<MapChunk> {
+        if self.data.len() <= map_layer {
+            self.data.resize_with(map_layer + 1, || None);
+        }
+        self.data[map_layer].replace(layer)
+    }
+
+    /// Removes the layer at the given layer index from the chunk and returns it if it existed.
+    pub fn remove_layer(&mut self, map_layer: usize) -> Option<MapChunk> {
+        let layer = self.data.get_mut(map_layer)?.take();
+        while let Some(None) = self.data.last() {
+            self.data.pop();
+        }
+        layer
     }
 }
 
 impl<MapChunk, TileData> Chunk<MapChunk, TileData>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    TileData: Clone + Sized + Send + Sync,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
 {
-    /// Returns the actual dimensions for the given [`MapLayer`] in the [`Chunk`].
+    /// Returns the layer at the given layer index if it exists in the chunk
+    pub fn layer(&self, map_layer: usize) -> Option<&MapChunk> {
+        self.data.get(map_layer)?.as_ref()
+    }
+
+    /// Returns the layer at the given layer index mutably if it exists in the chunk
+    pub fn layer_mut(&mut self, map_layer: usize) -> Option<&mut MapChunk> {
+        self.data.get_mut(map_layer)?.as_mut()
+    }
+
+    /// Returns true if the layer at the given layer index exists in the chunk
+    pub fn has_layer(&self, map_layer: usize) -> bool {
+        self.layer(map_layer).is_some()
+    }
+
+    /// Returns the number of layers that exist in the chunk
+    pub fn layer_count(&self) -> usize {
+        self.data.iter().flatten().count()
+    }
+
+    /// Returns an iterator over every layer in the chunk along with its layer index, in ascending index order
+    pub fn layers(&self) -> impl Iterator<Item = (usize, &MapChunk)> {
+        self.data
+            .iter()
+            .enumerate()
+            .filter_map(|(index, layer)| Some((index, layer.as_ref()?)))
+    }
+
+    /// Returns a mutable iterator over every layer in the chunk along with its layer index, in ascending index order
+    pub fn layers_mut(&mut self) -> impl Iterator<Item = (usize, &mut MapChunk)> {
+        self.data
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, layer)| Some((index, layer.as_mut()?)))
+    }
+
+    /// Returns the actual dimensions of the [`Chunk`]. Every layer in a chunk has the same dimensions.
     ///
     /// # Panics
-    /// - If the [`MapLayer`] does not exist in the chunk
+    /// - If the chunk has no layers
     pub fn get_chunk_dimensions(&self) -> UVec2 {
-        if let Some(tiles) = self.data.get(&1u32) {
-            return tiles.get_chunk_dimensions();
+        if let Some((_, tiles)) = self.layers().next() {
+            tiles.get_chunk_dimensions()
         } else {
-            panic!("MapLayer does not exist in chunk")
+            panic!("Chunk does not have any layers")
         }
     }
 
@@ -234,7 +381,7 @@ where
     /// # Panics
     /// - If the [`ChunkCell`] does not exist in the [`Chunk`]
     /// - If the [`MapLayer`] does not exist in the chunk
-    pub fn set_tile_data_from_cell(&mut self, map_layer: u32, cell: Cell, tile_data: TileData) {
+    pub fn set_tile_data_from_cell(&mut self, map_layer: usize, cell: Cell, tile_data: TileData) {
         self.set_tile_data(
             map_layer,
             MapChunk::into_chunk_cell(cell, &self.chunk_settings),
@@ -247,46 +394,120 @@ where
     /// # Panics
     /// - If the [`ChunkCell`] does not exist in the [`Chunk`]
     /// - If the [`MapLayer`] does not exist in the chunk
-    pub fn set_tile_data(&mut self, map_layer: u32, chunk_cell: ChunkCell, tile_data: TileData) {
-        if let Some(tiles) = self.data.get_mut(&map_layer) {
+    pub fn set_tile_data(&mut self, map_layer: usize, chunk_cell: ChunkCell, tile_data: TileData) {
+        if let Some(tiles) = self.layer_mut(map_layer) {
             tiles.set_tile_data(chunk_cell, tile_data);
         } else {
             panic!("MapLayer does not exist in chunk")
         }
     }
 
-    /// Returns a clone of the TileData at the given world [`Cell`] if it exists in this chunk
+    /// Removes the tile data at the given [`Cell`] and returns it. See [`ChunkLayer::take_tile_data`]
+    ///
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
+    pub fn take_tile_data_from_cell(&mut self, map_layer: usize, cell: Cell) -> Option<TileData> {
+        self.take_tile_data(
+            map_layer,
+            MapChunk::into_chunk_cell(cell, &self.chunk_settings),
+        )
+    }
+
+    /// Removes the tile data at the given [`ChunkCell`] and returns it. See [`ChunkLayer::take_tile_data`]
+    ///
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
+    pub fn take_tile_data(&mut self, map_layer: usize, chunk_cell: ChunkCell) -> Option<TileData> {
+        self.layer_mut(map_layer)?.take_tile_data(chunk_cell)
+    }
+
+    /// Removes the tile data at the given [`Cell`]. See [`ChunkLayer::take_tile_data`]
+    ///
+    /// # Panics
+    /// - If the [`MapLayer`] does not exist in the chunk
+    pub fn remove_tile_data_from_cell(&mut self, map_layer: usize, cell: Cell) {
+        self.remove_tile_data(
+            map_layer,
+            MapChunk::into_chunk_cell(cell, &self.chunk_settings),
+        )
+    }
+
+    /// Removes the tile data at the given [`ChunkCell`]. See [`ChunkLayer::take_tile_data`]
+    ///
+    /// # Panics
+    /// - If the [`MapLayer`] does not exist in the chunk
+    pub fn remove_tile_data(&mut self, map_layer: usize, chunk_cell: ChunkCell) {
+        self.layer_mut(map_layer)
+            .expect("MapLayer does not exist in chunk")
+            .remove_tile_data(chunk_cell)
+    }
+
+    /// Removes all the tile data in the given layer. See [`ChunkLayer::clear_tile_data`]
     ///
     /// # Panics
     /// - If the [`MapLayer`] does not exist in the chunk
+    pub fn clear_layer(&mut self, map_layer: usize) {
+        self.layer_mut(map_layer)
+            .expect("MapLayer does not exist in chunk")
+            .clear_tile_data()
+    }
+
+    /// Clears the dirty cells of every layer in the chunk. See [`ChunkLayer::dirty_cells`]
+    pub fn clear_dirty_cells(&mut self) {
+        for (_, layer) in self.layers_mut() {
+            layer.clear_dirty_cells();
+        }
+    }
+
+    /// Returns a reference to the TileData at the given world [`Cell`] if it exists in this chunk
+    ///
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
     pub fn get_tile_data_from_cell(
         &self,
         map_layer: impl MapLayer,
         cell: Cell,
-    ) -> Option<TileData> {
+    ) -> Option<&TileData> {
         self.get_tile_data(
             map_layer,
             MapChunk::into_chunk_cell(cell, &self.chunk_settings),
         )
     }
 
-    /// Returns a clone of the TileData at the given [`ChunkCell`] if it exists
+    /// Returns a reference to the TileData at the given [`ChunkCell`] if it exists
     ///
-    /// # Panics
-    /// - If the [`MapLayer`] does not exist in the chunk
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
     pub fn get_tile_data(
         &self,
         map_layer: impl MapLayer,
         chunk_cell: ChunkCell,
-    ) -> Option<TileData> {
-        self.data
-            .get(&map_layer.to_bits())
-            .expect("MapLayer does not exist in chunk")
-            .get_tile_data(chunk_cell)
-            .cloned()
+    ) -> Option<&TileData> {
+        self.layer(map_layer.to_index())?.get_tile_data(chunk_cell)
+    }
+
+    /// Returns a mutable reference to the TileData at the given world [`Cell`] if it exists in this chunk
+    ///
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
+    pub fn get_tile_data_mut_from_cell(
+        &mut self,
+        map_layer: impl MapLayer,
+        cell: Cell,
+    ) -> Option<&mut TileData> {
+        let chunk_cell = MapChunk::into_chunk_cell(cell, &self.chunk_settings);
+        self.get_tile_data_mut(map_layer, chunk_cell)
+    }
+
+    /// Returns a mutable reference to the TileData at the given [`ChunkCell`] if it exists
+    ///
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
+    pub fn get_tile_data_mut(
+        &mut self,
+        map_layer: impl MapLayer,
+        chunk_cell: ChunkCell,
+    ) -> Option<&mut TileData> {
+        self.layer_mut(map_layer.to_index())?
+            .get_tile_data_mut(chunk_cell)
     }
 
-    /// Gets the entity for the tile at the given cell if it exists
+    /// Gets the entity for the tile at the given cell if it exists. Returns `None` if the [`MapLayer`] does not
+    /// exist in the chunk
     pub fn get_tile_entity_from_cell(
         &self,
         map_layer: impl MapLayer,
@@ -298,20 +519,22 @@ where
         )
     }
 
-    /// Gets the entity for the tile at the given chunk cell if it exists
+    /// Gets the entity for the tile at the given chunk cell if it exists. Returns `None` if the [`MapLayer`] does
+    /// not exist in the chunk
     pub fn get_tile_entity(
         &self,
         map_layer: impl MapLayer,
         chunk_cell: ChunkCell,
     ) -> Option<Entity> {
-        self.data
-            .get(&map_layer.to_bits())
-            .expect("MapLayer does not exist in chunk")
+        self.layer(map_layer.to_index())?
             .get_tile_entity(chunk_cell)
     }
 
     /// Sets the [`Entity`] for the given [`Cell`] to the given Entity.
-    pub fn set_tile_entity_from_cell(&mut self, map_layer: u32, cell: Cell, entity: Entity) {
+    ///
+    /// # Panics
+    /// - If the [`MapLayer`] does not exist in the chunk
+    pub fn set_tile_entity_from_cell(&mut self, map_layer: usize, cell: Cell, entity: Entity) {
         self.set_tile_entity(
             map_layer,
             MapChunk::into_chunk_cell(cell, &self.chunk_settings),
@@ -320,12 +543,62 @@ where
     }
 
     /// Sets the [`Entity`] for the given [`ChunkCell`] to the given Entity.
-    pub fn set_tile_entity(&mut self, map_layer: u32, chunk_cell: ChunkCell, entity: Entity) {
-        self.data
-            .get_mut(&map_layer)
+    ///
+    /// # Panics
+    /// - If the [`MapLayer`] does not exist in the chunk
+    pub fn set_tile_entity(&mut self, map_layer: usize, chunk_cell: ChunkCell, entity: Entity) {
+        self.layer_mut(map_layer)
             .expect("MapLayer does not exist in chunk")
             .set_tile_entity(chunk_cell, entity);
     }
+
+    /// Removes the [`Entity`] mapping for the given [`Cell`] and returns it. Does not despawn the entity
+    ///
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
+    pub fn remove_tile_entity_from_cell(&mut self, map_layer: usize, cell: Cell) -> Option<Entity> {
+        self.remove_tile_entity(
+            map_layer,
+            MapChunk::into_chunk_cell(cell, &self.chunk_settings),
+        )
+    }
+
+    /// Removes the [`Entity`] mapping for the given [`ChunkCell`] and returns it. Does not despawn the entity
+    ///
+    /// Returns `None` if the [`MapLayer`] does not exist in the chunk
+    pub fn remove_tile_entity(
+        &mut self,
+        map_layer: usize,
+        chunk_cell: ChunkCell,
+    ) -> Option<Entity> {
+        self.layer_mut(map_layer)?.remove_tile_entity(chunk_cell)
+    }
+
+    /// Sets the [`ConversionPolicy`] of the given layer. `None` disables automatic conversion.
+    ///
+    /// # Panics
+    /// - If the [`MapLayer`] does not exist in the chunk
+    pub fn set_conversion_policy(
+        &mut self,
+        map_layer: usize,
+        conversion_policy: Option<ConversionPolicy>,
+    ) where
+        TileData: PartialEq,
+    {
+        self.layer_mut(map_layer)
+            .expect("MapLayer does not exist in chunk")
+            .set_conversion_policy(conversion_policy);
+    }
+
+    /// Sets the tile data that tiles of the given dense or palette layer are reset to when their data is removed.
+    /// See [`ChunkLayer::default_tile_data`]
+    ///
+    /// # Panics
+    /// - If the [`MapLayer`] does not exist in the chunk
+    pub fn set_default_tile_data(&mut self, map_layer: usize, tile_data: Option<TileData>) {
+        self.layer_mut(map_layer)
+            .expect("MapLayer does not exist in chunk")
+            .set_default_tile_data(tile_data);
+    }
 }
 
 #[cfg(test)]
@@ -365,7 +638,7 @@ mod tests {
             chunk
                 .get_tile_data(MapLayers::Main, ChunkCell::new(0, 0))
                 .unwrap(),
-            0
+            &0
         );
 
         // Tests a custom struct as a TileData
@@ -386,13 +659,13 @@ mod tests {
             chunk
                 .get_tile_data(MapLayers::Main, ChunkCell::new(0, 0))
                 .unwrap(),
-            TileData(0)
+            &TileData(0)
         );
         assert_eq!(
             chunk
                 .get_tile_data(MapLayers::Main, ChunkCell::new(3, 2))
                 .unwrap(),
-            TileData(11)
+            &TileData(11)
         );
 
         // Tests tuples
@@ -413,13 +686,13 @@ mod tests {
             chunk
                 .get_tile_data(MapLayers::Main, ChunkCell::new(0, 0))
                 .unwrap(),
-            (0, 0)
+            &(0, 0)
         );
         assert_eq!(
             chunk
                 .get_tile_data(MapLayers::Main, ChunkCell::new(2, 2))
                 .unwrap(),
-            (10, 1)
+            &(10, 1)
         );
     }
 
@@ -461,7 +734,7 @@ mod tests {
             chunk
                 .get_tile_data(MapLayers::Main, ChunkCell::new(3, 2))
                 .unwrap(),
-            (11, 4)
+            &(11, 4)
         );
     }
 
@@ -480,12 +753,12 @@ mod tests {
                 max_chunk_size: UVec2 { x: 2, y: 2 },
             },
         );
-        chunk.set_tile_data(MapLayers::Main.to_bits(), ChunkCell::new(0, 0), (50, 60));
+        chunk.set_tile_data(MapLayers::Main.to_index(), ChunkCell::new(0, 0), (50, 60));
         assert_eq!(
             chunk
                 .get_tile_data(MapLayers::Main, ChunkCell::new(0, 0))
                 .unwrap(),
-            (50, 60)
+            &(50, 60)
         );
     }
 
@@ -502,14 +775,14 @@ mod tests {
             },
         );
         chunk.add_layer(
-            MapLayers::Secondary.to_bits(),
+            MapLayers::Secondary.to_index(),
             crate::map::chunk::ChunkLayerType::Sparse(hashmap),
         );
         assert_eq!(
             chunk
                 .get_tile_data(MapLayers::Secondary, ChunkCell::new(0, 0))
                 .unwrap(),
-            (50, 60)
+            &(50, 60)
         );
     }
 
@@ -530,15 +803,98 @@ mod tests {
         ];
 
         chunk.add_layer(
-            MapLayers::Secondary.to_bits(),
+            MapLayers::Secondary.to_index(),
             crate::map::chunk::ChunkLayerType::Dense(vecs),
         );
         assert_eq!(
             chunk
                 .get_tile_data(MapLayers::Secondary, ChunkCell::new(3, 2))
                 .unwrap(),
-            (11, 4)
+            &(11, 4)
+        );
+    }
+
+    #[test]
+    fn test_more_than_32_layers() {
+        use crate::map::LayerMask;
+
+        #[allow(dead_code)]
+        #[derive(MapLayer, Default, Clone, Copy)]
+        enum ManyLayers {
+            #[default]
+            Layer0,
+            Layer1,
+            Layer2,
+            Layer3,
+            Layer4,
+            Layer5,
+            Layer6,
+            Layer7,
+            Layer8,
+            Layer9,
+            Layer10,
+            Layer11,
+            Layer12,
+            Layer13,
+            Layer14,
+            Layer15,
+            Layer16,
+            Layer17,
+            Layer18,
+            Layer19,
+            Layer20,
+            Layer21,
+            Layer22,
+            Layer23,
+            Layer24,
+            Layer25,
+            Layer26,
+            Layer27,
+            Layer28,
+            Layer29,
+            Layer30,
+            Layer31,
+            Layer32,
+            Layer33,
+            Layer34,
+            Layer35,
+            Layer36,
+            Layer37,
+            Layer38,
+            Layer39,
+        }
+
+        assert_eq!(ManyLayers::layer_count(), 40);
+        assert_eq!(ManyLayers::Layer39.to_index(), 39);
+        let mut mask = ManyLayers::Layer0.to_bits() | ManyLayers::Layer39.to_bits();
+        assert!(mask.contains(ManyLayers::Layer39));
+        mask.remove(ManyLayers::Layer0);
+        assert_eq!(mask.indices().collect::<Vec<usize>>(), vec![39]);
+        assert_eq!(ManyLayers::all_bits(), LayerMask((1 << 40) - 1));
+
+        let mut chunk: Chunk<SquareChunkLayer<(i32, i32)>, (i32, i32)> = Chunk::new(
+            ChunkPos::new(0, 0),
+            UVec2 { x: 2, y: 2 },
+            crate::map::chunk::ChunkLayerType::Sparse(HashMap::new()),
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 2, y: 2 },
+            },
+        );
+        chunk.add_layer(
+            ManyLayers::Layer39.to_index(),
+            crate::map::chunk::ChunkLayerType::Dense(vec![vec![(3, 9); 2]; 2]),
+        );
+        assert_eq!(chunk.layer_count(), 2);
+        assert_eq!(
+            chunk
+                .get_tile_data(ManyLayers::Layer39, ChunkCell::new(1, 1))
+                .unwrap(),
+            &(3, 9)
         );
+        assert!(!chunk.has_layer(ManyLayers::Layer20.to_index()));
+
+        assert!(chunk.remove_layer(ManyLayers::Layer39.to_index()).is_some());
+        assert_eq!(chunk.data.len(), 1);
     }
 
     #[cfg(feature = "reflect")]
@@ -575,7 +931,8 @@ mod tests {
             registry.register::<Chunk<SquareChunkLayer<(u32, u32)>, (u32, u32)>>();
             registry.register::<ChunkPos>();
             registry.register::<Cell>();
-            registry.register::<HashMap<u32, SquareChunkLayer<(u32, u32)>>>();
+            registry.register::<Vec<Option<SquareChunkLayer<(u32, u32)>>>>();
+            registry.register::<Option<SquareChunkLayer<(u32, u32)>>>();
             registry.register::<HashMap<u64, Entity>>();
             registry.register::<HashMap<u64, (u32, u32)>>();
             registry.register::<SquareChunkLayer<(u32, u32)>>();
@@ -0,0 +1,430 @@
+use super::TileDataEq;
+use std::hash::Hash;
+
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Storage for a chunk layer that keeps a palette of the unique `T` values in the chunk and a bit packed
+/// palette index for every tile.
+///
+/// Uses far less memory than a [`Grid`](lettuces::storage::grid::Grid) when a chunk only contains a handful
+/// of distinct values. The amount of bits used per index grows as the palette grows and shrinks again when the
+/// palette is compacted.
+///
+/// Two storages with the same tiles hash the same, regardless of the layout of their palettes.
+///
+/// Tiles are compared with the [`TileDataEq`] the storage was created with. A deserialized storage has none until
+/// [`set_tile_data_eq`](Self::set_tile_data_eq) is called and keeps every value it is given in its own entry until
+/// then.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T: Deserialize<'de>")))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub struct PaletteStorage<T> {
+    /// The values referenced by the tiles
+    palette: Vec<T>,
+    /// The amount of tiles using each palette entry. Unused entries are reused for new values
+    palette_uses: Vec<u32>,
+    /// Bit packed palette indices, stored row by row
+    indices: Vec<u64>,
+    bits_per_index: u32,
+    rows: usize,
+    cols: usize,
+    /// Set when [`get_mut`](Self::get_mut) may have left duplicate palette entries behind
+    needs_compaction: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    tile_data_eq: Option<TileDataEq<T>>,
+}
+
+impl<T: Hash> Hash for PaletteStorage<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.rows.hash(state);
+        self.cols.hash(state);
+        for tile in 0..self.rows * self.cols {
+            self.palette[self.read_index(tile)].hash(state);
+        }
+    }
+}
+
+impl<T> PaletteStorage<T> {
+    fn read_index(&self, tile: usize) -> usize {
+        let (word, shift) = self.index_position(tile);
+        ((self.indices[word] >> shift) & self.index_mask()) as usize
+    }
+
+    fn write_index(&mut self, tile: usize, palette_index: usize) {
+        let (word, shift) = self.index_position(tile);
+        let mask = self.index_mask();
+        self.indices[word] =
+            (self.indices[word] & !(mask << shift)) | ((palette_index as u64 & mask) << shift);
+    }
+
+    /// Returns the word and bit offset of the given tiles index. Indices never straddle two words
+    fn index_position(&self, tile: usize) -> (usize, u32) {
+        let indices_per_word = (u64::BITS / self.bits_per_index) as usize;
+        (
+            tile / indices_per_word,
+            (tile % indices_per_word) as u32 * self.bits_per_index,
+        )
+    }
+
+    fn index_mask(&self) -> u64 {
+        (1u64 << self.bits_per_index) - 1
+    }
+}
+
+impl<T> PaletteStorage<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Creates a new [`PaletteStorage`] with all the tiles having the same data as the given tile_data
+    pub fn new_uniform(rows: usize, cols: usize, tile_data: T) -> Self {
+        Self::new_uniform_with(rows, cols, tile_data, T::eq)
+    }
+
+    /// Creates a new [`PaletteStorage`] from the given vectors of vectors of T. Each inner vector is a row
+    ///
+    /// # Panics
+    /// - If `tile_data` is empty
+    pub fn new_from_vecs(tile_data: &[Vec<T>]) -> Self {
+        Self::new_from_vecs_with(tile_data, T::eq)
+    }
+}
+
+impl<T> PaletteStorage<T>
+where
+    T: Clone,
+{
+    /// Creates a new [`PaletteStorage`] with all the tiles having the same data as the given tile_data, that
+    /// compares tiles with the given [`TileDataEq`]
+    pub fn new_uniform_with(
+        rows: usize,
+        cols: usize,
+        tile_data: T,
+        tile_data_eq: TileDataEq<T>,
+    ) -> Self {
+        let bits_per_index = 1;
+        Self {
+            palette: vec![tile_data],
+            palette_uses: vec![(rows * cols) as u32],
+            indices: vec![0; Self::word_count(rows * cols, bits_per_index)],
+            bits_per_index,
+            rows,
+            cols,
+            needs_compaction: false,
+            tile_data_eq: Some(tile_data_eq),
+        }
+    }
+
+    /// Creates a new [`PaletteStorage`] from the given vectors of vectors of T, that compares tiles with the given
+    /// [`TileDataEq`]. Each inner vector is a row
+    ///
+    /// # Panics
+    /// - If `tile_data` is empty
+    pub fn new_from_vecs_with(tile_data: &[Vec<T>], tile_data_eq: TileDataEq<T>) -> Self {
+        let mut storage = Self::new_uniform_with(
+            tile_data.len(),
+            tile_data[0].len(),
+            tile_data[0]
+                .first()
+                .cloned()
+                .expect("Rows must not be empty"),
+            tile_data_eq,
+        );
+        for (row, row_data) in tile_data.iter().enumerate() {
+            for (col, tile) in row_data.iter().enumerate() {
+                storage.set(row, col, tile.clone());
+            }
+        }
+        storage
+    }
+
+    /// Returns the amount of rows in the storage
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the amount of columns in the storage
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the palette of values. May contain values that are no longer used by any tile or the same value more
+    /// than once until the palette is compacted
+    pub fn palette(&self) -> &[T] {
+        &self.palette
+    }
+
+    /// Returns the [`TileDataEq`] the storage compares tiles with
+    pub fn tile_data_eq(&self) -> Option<TileDataEq<T>> {
+        self.tile_data_eq
+    }
+
+    /// Sets the [`TileDataEq`] the storage compares tiles with. Entries with the same value are merged on the next
+    /// [`compact`](Self::compact)
+    pub fn set_tile_data_eq(&mut self, tile_data_eq: TileDataEq<T>) {
+        self.tile_data_eq = Some(tile_data_eq);
+    }
+
+    /// Sets every tile to the given tile data
+    pub fn fill(&mut self, tile_data: T) {
+        let bits_per_index = 1;
+        self.palette = vec![tile_data];
+        self.palette_uses = vec![(self.rows * self.cols) as u32];
+        self.indices = vec![0; Self::word_count(self.rows * self.cols, bits_per_index)];
+        self.bits_per_index = bits_per_index;
+        self.needs_compaction = false;
+    }
+
+    /// Returns the amount of bits currently used to store each palette index
+    pub fn bits_per_index(&self) -> u32 {
+        self.bits_per_index
+    }
+
+    /// Gets immutable access to the tile data at the given row and column
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        let tile = self.tile_index(row, col)?;
+        Some(&self.palette[self.read_index(tile)])
+    }
+
+    /// Gets mutable access to the tile data at the given row and column.
+    ///
+    /// # Note
+    /// - If the value is shared with other tiles the tile is first given its own palette entry so that the
+    ///   change only affects this tile. Entries that end up with the same value are merged on the next
+    ///   [`set`](Self::set)
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        let tile = self.tile_index(row, col)?;
+        let mut palette_index = self.read_index(tile);
+        if self.palette_uses[palette_index] > 1 {
+            self.palette_uses[palette_index] -= 1;
+            palette_index = self.insert_palette_entry(self.palette[palette_index].clone());
+            self.palette_uses[palette_index] += 1;
+            self.write_index(tile, palette_index);
+            self.needs_compaction = true;
+        }
+        Some(&mut self.palette[palette_index])
+    }
+
+    /// Sets the tile data at the given row and column. Does nothing if the position is outside of the storage.
+    ///
+    /// # Note
+    /// - Compacts the palette first if [`get_mut`](Self::get_mut) was used since the last compaction or if less
+    ///   than a quarter of the indices the current bit width can hold are in use
+    pub fn set(&mut self, row: usize, col: usize, tile_data: T) {
+        let Some(tile) = self.tile_index(row, col) else {
+            return;
+        };
+        if self.needs_compaction {
+            self.compact();
+        }
+        let old_palette_index = self.read_index(tile);
+        if self.tiles_eq(&self.palette[old_palette_index], &tile_data) {
+            return;
+        }
+        self.palette_uses[old_palette_index] -= 1;
+
+        let palette_index = match self
+            .palette
+            .iter()
+            .zip(self.palette_uses.iter())
+            .position(|(entry, uses)| *uses > 0 && self.tiles_eq(entry, &tile_data))
+        {
+            Some(palette_index) => palette_index,
+            None => self.insert_palette_entry(tile_data),
+        };
+        self.palette_uses[palette_index] += 1;
+        self.write_index(tile, palette_index);
+        if self.palette_uses[old_palette_index] == 0 && self.bits_per_index > 2 {
+            let used_entries = self.palette_uses.iter().filter(|uses| **uses > 0).count();
+            if used_entries <= 1 << (self.bits_per_index - 2) {
+                self.compact();
+            }
+        }
+    }
+
+    /// Merges palette entries with the same value, drops the unused ones and shrinks the amount of bits used per
+    /// index to the smallest that fits the remaining palette
+    pub fn compact(&mut self) {
+        let mut palette: Vec<T> = vec![];
+        let mut palette_uses: Vec<u32> = vec![];
+        let mut new_indices = vec![0; self.palette.len()];
+        for (palette_index, entry) in self.palette.iter().enumerate() {
+            let uses = self.palette_uses[palette_index];
+            if uses == 0 {
+                continue;
+            }
+            let new_index = match palette
+                .iter()
+                .position(|new_entry| self.tiles_eq(new_entry, entry))
+            {
+                Some(new_index) => new_index,
+                None => {
+                    palette.push(entry.clone());
+                    palette_uses.push(0);
+                    palette.len() - 1
+                }
+            };
+            palette_uses[new_index] += uses;
+            new_indices[palette_index] = new_index;
+        }
+        let tiles: Vec<usize> = (0..self.rows * self.cols)
+            .map(|tile| new_indices[self.read_index(tile)])
+            .collect();
+        let bits_per_index = usize::BITS - (palette.len().max(2) - 1).leading_zeros();
+        self.palette = palette;
+        self.palette_uses = palette_uses;
+        self.bits_per_index = bits_per_index;
+        self.indices = vec![0; Self::word_count(self.rows * self.cols, bits_per_index)];
+        for (tile, palette_index) in tiles.into_iter().enumerate() {
+            self.write_index(tile, palette_index);
+        }
+        self.needs_compaction = false;
+    }
+
+    /// Returns an iterator over every tile, row by row
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.rows * self.cols).map(|tile| &self.palette[self.read_index(tile)])
+    }
+
+    /// Returns whether the tiles are equal. Tiles are never equal without a [`TileDataEq`]
+    fn tiles_eq(&self, tile: &T, other: &T) -> bool {
+        self.tile_data_eq
+            .is_some_and(|tile_data_eq| tile_data_eq(tile, other))
+    }
+
+    fn tile_index(&self, row: usize, col: usize) -> Option<usize> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        Some(row * self.cols + col)
+    }
+
+    /// Adds the value to the palette, reusing an unused entry if there is one, and returns its index
+    fn insert_palette_entry(&mut self, tile_data: T) -> usize {
+        if let Some(palette_index) = self.palette_uses.iter().position(|uses| *uses == 0) {
+            self.palette[palette_index] = tile_data;
+            return palette_index;
+        }
+        self.palette.push(tile_data);
+        self.palette_uses.push(0);
+        if self.palette.len() > 1 << self.bits_per_index {
+            self.repack(self.bits_per_index + 1);
+        }
+        self.palette.len() - 1
+    }
+
+    /// Rewrites every index using the given amount of bits
+    fn repack(&mut self, bits_per_index: u32) {
+        let palette_indices: Vec<usize> = (0..self.rows * self.cols)
+            .map(|tile| self.read_index(tile))
+            .collect();
+        self.bits_per_index = bits_per_index;
+        self.indices = vec![0; Self::word_count(self.rows * self.cols, bits_per_index)];
+        for (tile, palette_index) in palette_indices.into_iter().enumerate() {
+            self.write_index(tile, palette_index);
+        }
+    }
+
+    fn word_count(tiles: usize, bits_per_index: u32) -> usize {
+        tiles.div_ceil((u64::BITS / bits_per_index) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::chunk::PaletteStorage;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    #[test]
+    fn palette_grows_and_reuses_entries() {
+        let mut storage = PaletteStorage::new_uniform(10, 10, 0u32);
+        assert_eq!(storage.bits_per_index(), 1);
+
+        for row in 0..10 {
+            for col in 0..10 {
+                storage.set(row, col, (row * 10 + col) as u32 % 40);
+            }
+        }
+        assert_eq!(storage.palette().len(), 40);
+        assert_eq!(storage.bits_per_index(), 6);
+        for row in 0..10 {
+            for col in 0..10 {
+                assert_eq!(storage.get(row, col), Some(&((row * 10 + col) as u32 % 40)));
+            }
+        }
+        assert_eq!(storage.get(10, 0), None);
+        assert_eq!(storage.get(0, 10), None);
+
+        // The palette shrinks as values stop being used and freed entries are reused
+        for row in 0..10 {
+            for col in 0..10 {
+                storage.set(row, col, 7);
+            }
+        }
+        assert_eq!(storage.bits_per_index(), 2);
+        let palette_len = storage.palette().len();
+        assert!(palette_len <= 4);
+        storage.set(3, 3, 100);
+        assert_eq!(storage.palette().len(), palette_len);
+        assert_eq!(storage.get(3, 3), Some(&100));
+        assert_eq!(storage.iter().filter(|tile| **tile == 7).count(), 99);
+    }
+
+    #[test]
+    fn palette_compacts_after_get_mut() {
+        let mut storage = PaletteStorage::new_uniform(4, 4, 0u32);
+        for col in 0..4 {
+            *storage.get_mut(0, col).unwrap() = 1;
+        }
+        assert_eq!(storage.palette().len(), 5);
+        storage.set(3, 3, 2);
+        assert_eq!(storage.palette(), &[0, 1, 2]);
+        assert_eq!(storage.bits_per_index(), 2);
+        assert_eq!(
+            storage.iter().cloned().collect::<Vec<u32>>(),
+            vec![1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]
+        );
+    }
+
+    #[test]
+    fn palette_hash_only_depends_on_tiles() {
+        let hash = |storage: &PaletteStorage<u32>| {
+            let mut hasher = DefaultHasher::new();
+            storage.hash(&mut hasher);
+            hasher.finish()
+        };
+        let storage = PaletteStorage::new_from_vecs(&[vec![1u32, 1], vec![1, 2]]);
+        // The same tiles with the palette in a different order, a duplicate entry and a wider bit width
+        let mut other = PaletteStorage::new_uniform(2, 2, 2u32);
+        for value in 3..6 {
+            other.set(0, 0, value);
+        }
+        other.set(0, 1, 1);
+        other.set(1, 0, 1);
+        *other.get_mut(1, 0).unwrap() = 0;
+        *other.get_mut(1, 0).unwrap() = 1;
+        other.set(0, 0, 1);
+        assert_ne!(storage.palette(), other.palette());
+        assert_eq!(hash(&storage), hash(&other));
+        other.set(0, 1, 3);
+        assert_ne!(hash(&storage), hash(&other));
+    }
+
+    #[test]
+    fn palette_get_mut_only_changes_one_tile() {
+        let mut storage = PaletteStorage::new_from_vecs(&[vec![1u32, 1, 2], vec![2, 1, 1]]);
+        *storage.get_mut(0, 1).unwrap() = 5;
+        assert_eq!(storage.get(0, 0), Some(&1));
+        assert_eq!(storage.get(0, 1), Some(&5));
+        assert_eq!(storage.get(1, 1), Some(&1));
+        assert_eq!(
+            storage.iter().cloned().collect::<Vec<u32>>(),
+            vec![1, 5, 2, 2, 1, 1]
+        );
+    }
+}
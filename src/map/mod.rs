@@ -1,17 +1,19 @@
 //! This module contains the features that drive the actual map.
-//! 
+//!
 //! ## Broad Overview
-//! 
+//!
 //! There are two main traits that drive bevy sparse tilemap.
-//! 
+//!
 //! - [`MapData`]
 //! - [`ChunkLayer`]
-//! 
+//!
 //! MapData is the high level implementation that drives map construction and cell -> chunk pos conversion.
-//! 
+//!
 //! ChunkLayer is the meat and potatoes of BST and controls all of the access of the map.
 
 pub mod chunk;
+mod tile_changed;
+mod tile_entity;
 mod tilemap;
 
 use bevy::{
@@ -19,95 +21,400 @@ use bevy::{
     prelude::{Component, Entity},
     utils::HashMap,
 };
-use chunk::{Chunk, ChunkLayer, ChunkPos};
+use chunk::{Chunk, ChunkCell, ChunkLayer, ChunkLayerStorage, ChunkPos, TileDataEq};
 use lettuces::cell::Cell;
 use std::hash::Hash;
+use std::ops::{BitAnd, BitOr};
+pub use tile_changed::{send_tile_changed_events, TileChanged};
+pub use tile_entity::{remove_despawned_tile_entities, TileEntity};
 pub use tilemap::Tilemap;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+
 /// A layer used for identifying and accessing multiple layers of a [`Tilemap`]
 ///
-/// This trait can be derived for enums with `#[derive(MapLayer)]`.
+/// This trait can be derived for enums with `#[derive(MapLayer)]`. Layers are identified by a dense index, which is
+/// used to look up the layer in each [`Chunk`], and can be combined into a [`LayerMask`].
+///
+/// Each variant can declare how the layer is stored with `#[layer(dense)]`, `#[layer(sparse)]` or
+/// `#[layer(palette)]`, and the tile data that a dense or palette layer starts out filled with using
+/// `#[layer(default = ...)]`. A layer that only declares a default is dense, and dense or palette layers without a
+/// default are filled with `TileData::default()`. Dense and palette layers need the `TileData` named with
+/// `#[layer(tile_data = ...)]` on the enum, see [`MapLayerDefault`]. The
+/// [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder) creates every layer with a declared storage that is
+/// not supplied to it and checks that supplied layers match their declared storage.
+///
+/// ```
+/// # use bevy_sparse_tilemap::map::chunk::ChunkLayerStorage;
+/// # use bevy_sparse_tilemap::map::MapLayerDefault;
+/// #[derive(bevy_sparse_tilemap::MapLayer, Clone, Copy, Default)]
+/// #[layer(tile_data = u32)]
+/// enum MapLayers {
+///     #[default]
+///     #[layer(dense, default = 1)]
+///     Terrain,
+///     #[layer(sparse)]
+///     Items,
+///     Decorations,
+/// }
+///
+/// assert_eq!(MapLayers::Items.storage(), Some(ChunkLayerStorage::Sparse));
+/// assert_eq!(MapLayers::Decorations.storage(), None);
+/// assert_eq!(MapLayers::Terrain.default_tile_data(), Some(1));
+/// ```
 pub trait MapLayer: Default {
-    /// Converts the layer to a bitmask.
-    fn to_bits(&self) -> u32;
-    /// Creates a layer bitmask with all bits set to 1.
-    fn all_bits() -> u32;
+    /// Returns the index of the layer. Indices are unique per layer and count up from 0.
+    fn to_index(&self) -> usize;
+    /// Returns the total number of layers.
+    fn layer_count() -> usize;
+    /// Returns the layer with the given index.
+    ///
+    /// Returns `None` by default, which means the layers can not be enumerated and none of them are created
+    /// automatically.
+    fn from_index(_index: usize) -> Option<Self> {
+        None
+    }
+    /// Returns the storage declared for the layer with `#[layer(...)]`, if any.
+    fn storage(&self) -> Option<ChunkLayerStorage> {
+        None
+    }
+    /// Converts the layer to a [`LayerMask`] containing only this layer.
+    fn to_bits(&self) -> LayerMask {
+        LayerMask::from_index(self.to_index())
+    }
+    /// Creates a [`LayerMask`] containing every layer.
+    fn all_bits() -> LayerMask {
+        LayerMask::all(Self::layer_count())
+    }
 }
 
 impl<L: MapLayer> MapLayer for &L
 where
     for<'a> &'a L: Default,
 {
-    fn to_bits(&self) -> u32 {
-        L::to_bits(self)
+    fn to_index(&self) -> usize {
+        L::to_index(self)
+    }
+
+    fn layer_count() -> usize {
+        L::layer_count()
+    }
+
+    fn storage(&self) -> Option<ChunkLayerStorage> {
+        L::storage(self)
+    }
+}
+
+/// The tile data that the [`MapLayer`]s of a tilemap with `TileData` tiles start out filled with.
+///
+/// Derived along with [`MapLayer`]. An enum that declares `#[layer(default = ...)]` on any variant must name the
+/// `TileData` of its tilemap with `#[layer(tile_data = ...)]`, and only implements this trait for that type so a
+/// default of the wrong type fails to compile. Enums without a declared `TileData` implement it for every type.
+///
+/// ```compile_fail
+/// #[derive(bevy_sparse_tilemap::MapLayer, Clone, Copy, Default)]
+/// #[layer(tile_data = u32)]
+/// enum MapLayers {
+///     #[default]
+///     #[layer(default = -1)]
+///     Terrain,
+/// }
+/// ```
+pub trait MapLayerDefault<TileData>: MapLayer {
+    /// Returns the tile data the layer starts out filled with, if it declares a dense or palette storage. This is
+    /// the tile data declared with `#[layer(default = ...)]`, or `TileData::default()` without one.
+    ///
+    /// Tiles of the layer are reset to it when their data is removed, see [`ChunkLayer::default_tile_data`].
+    fn default_tile_data(&self) -> Option<TileData> {
+        None
+    }
+
+    /// Returns the [`TileDataEq`] that tiles of the layer are compared with, if it declares a palette storage.
+    /// Derived as `TileData::eq`.
+    fn tile_data_eq(&self) -> Option<TileDataEq<TileData>> {
+        None
+    }
+}
+
+impl<TileData, L: MapLayerDefault<TileData>> MapLayerDefault<TileData> for &L
+where
+    for<'a> &'a L: Default,
+{
+    fn default_tile_data(&self) -> Option<TileData> {
+        L::default_tile_data(self)
+    }
+
+    fn tile_data_eq(&self) -> Option<TileDataEq<TileData>> {
+        L::tile_data_eq(self)
+    }
+}
+
+/// A set of [`MapLayer`]s stored as a bitmask, where bit `n` is the layer with index `n`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub struct LayerMask(pub u64);
+
+impl LayerMask {
+    /// The maximum number of layers a mask can hold
+    pub const MAX_LAYERS: usize = 64;
+
+    /// A mask containing no layers
+    pub const NONE: LayerMask = LayerMask(0);
+
+    /// Creates a mask containing only the layer with the given index.
+    ///
+    /// # Panics
+    /// - If the index is not less than [`LayerMask::MAX_LAYERS`]
+    pub fn from_index(index: usize) -> LayerMask {
+        assert!(
+            index < Self::MAX_LAYERS,
+            "Layer index {index} does not fit into a LayerMask"
+        );
+        LayerMask(1 << index)
+    }
+
+    /// Creates a mask containing the first `layer_count` layers.
+    pub fn all(layer_count: usize) -> LayerMask {
+        if layer_count >= Self::MAX_LAYERS {
+            LayerMask(u64::MAX)
+        } else {
+            LayerMask((1 << layer_count) - 1)
+        }
+    }
+
+    /// Returns true if the mask contains the given layer
+    pub fn contains(&self, map_layer: impl MapLayer) -> bool {
+        self.contains_index(map_layer.to_index())
+    }
+
+    /// Returns true if the mask contains the layer with the given index
+    pub fn contains_index(&self, index: usize) -> bool {
+        index < Self::MAX_LAYERS && self.0 & (1 << index) != 0
+    }
+
+    /// Adds the given layer to the mask
+    pub fn insert(&mut self, map_layer: impl MapLayer) {
+        self.0 |= map_layer.to_bits().0;
+    }
+
+    /// Removes the given layer from the mask
+    pub fn remove(&mut self, map_layer: impl MapLayer) {
+        self.0 &= !map_layer.to_bits().0;
+    }
+
+    /// Returns true if the mask contains no layers
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns an iterator over the indices of every layer in the mask, in ascending order
+    pub fn indices(&self) -> impl Iterator<Item = usize> {
+        let bits = self.0;
+        (0..Self::MAX_LAYERS).filter(move |index| bits & (1 << index) != 0)
     }
+}
+
+impl BitOr for LayerMask {
+    type Output = LayerMask;
 
-    fn all_bits() -> u32 {
-        L::all_bits()
+    fn bitor(self, rhs: Self) -> Self::Output {
+        LayerMask(self.0 | rhs.0)
     }
 }
 
+impl BitAnd for LayerMask {
+    type Output = LayerMask;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        LayerMask(self.0 & rhs.0)
+    }
+}
+
+/// Which cells count as the neighbours of a cell. See [`MapData::neighbours`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+pub enum Connectivity {
+    /// Cells that share an edge with the cell. 4 on square maps, 6 on hexagonal maps and 3 on triangle maps
+    #[default]
+    Edges,
+    /// Cells that share an edge or a corner with the cell. 8 on square maps, 6 on hexagonal maps and 12 on triangle
+    /// maps
+    Vertices,
+}
+
 /// Trait that must be implemented for a map type. It consists of mandatory functions used in building new maps as well as implementing a way to convert a given [`Cell`] into a chunk pos
 pub trait MapData: Hash + Component {
     /// Converts a [`Cell`] (A position on the map) into a [`ChunkPos`] (The position of the chunk that that cell is in)
+    ///
+    /// This must use floor division so that cells with negative coordinates end up in negative chunks
+    #[allow(clippy::wrong_self_convention)]
     fn into_chunk_pos(&self, cell: Cell) -> ChunkPos;
 
+    /// Converts a [`ChunkCell`] inside the chunk at the given [`ChunkPos`] back into a [`Cell`]. The inverse of
+    /// [`into_chunk_pos`](MapData::into_chunk_pos) and [`ChunkLayer::into_chunk_cell`]
+    #[allow(clippy::wrong_self_convention)]
+    fn into_cell(&self, chunk_pos: ChunkPos, chunk_cell: ChunkCell) -> Cell;
+
+    /// Converts a [`Cell`] into the `[column, row]` position it has on the rectangular grid the map is laid out in
+    fn cell_to_offset_coordinates(&self, cell: Cell) -> [i32; 2];
+
+    /// Converts a `[column, row]` position on the rectangular grid the map is laid out in into a [`Cell`]. The
+    /// inverse of [`cell_to_offset_coordinates`](MapData::cell_to_offset_coordinates)
+    fn offset_coordinates_to_cell(&self, offset_coordinates: [i32; 2]) -> Cell;
+
+    /// Returns the neighbours of the [`Cell`] with the given [`Connectivity`], including cells outside of the map.
+    ///
+    /// Defaults to the 4 or 8 neighbours of a square grid
+    fn neighbours(&self, cell: Cell, connectivity: Connectivity) -> Vec<Cell> {
+        let mut neighbours = vec![
+            Cell::new(cell.x + 1, cell.y),
+            Cell::new(cell.x - 1, cell.y),
+            Cell::new(cell.x, cell.y + 1),
+            Cell::new(cell.x, cell.y - 1),
+        ];
+        if connectivity == Connectivity::Vertices {
+            neighbours.extend([
+                Cell::new(cell.x + 1, cell.y + 1),
+                Cell::new(cell.x - 1, cell.y + 1),
+                Cell::new(cell.x + 1, cell.y - 1),
+                Cell::new(cell.x - 1, cell.y - 1),
+            ]);
+        }
+        neighbours
+    }
+
+    /// Returns the fewest steps between two [`Cell`]s when only moving to the [`neighbours`](MapData::neighbours)
+    /// with the given [`Connectivity`]. Map types where that is expensive to work out may return a lower bound
+    /// instead, it is used as the heuristic for [`TilemapManager::find_path`](crate::tilemap_manager::TilemapManager::find_path).
+    ///
+    /// Defaults to the manhattan distance for [`Connectivity::Edges`] and the chebyshev distance for
+    /// [`Connectivity::Vertices`] of a square grid
+    fn distance(&self, from: Cell, to: Cell, connectivity: Connectivity) -> u32 {
+        let (x, y) = (from.x.abs_diff(to.x), from.y.abs_diff(to.y));
+        match connectivity {
+            Connectivity::Edges => x + y,
+            Connectivity::Vertices => x.max(y),
+        }
+    }
+
+    /// Returns whether the [`Cell`] is part of the shape of the map. Tiles outside of the shape are not stored and
+    /// can't be accessed.
+    ///
+    /// Defaults to every cell, which leaves the bounds of the map to its chunks
+    fn contains_cell(&self, _cell: Cell) -> bool {
+        true
+    }
+
+    /// Returns whether any [`Cell`] of the chunk at the given [`ChunkPos`] is part of the shape of the map. Chunks
+    /// outside of the shape are built with empty sparse layers instead of storing tiles that can't be accessed.
+    ///
+    /// Defaults to every chunk
+    fn chunk_in_shape(&self, _chunk_pos: ChunkPos) -> bool {
+        true
+    }
+
+    /// Returns an error if a map of the given size whose first chunk is at the given [`ChunkPos`] does not fit the
+    /// shape of the map.
+    ///
+    /// Defaults to accepting every layout
+    ///
+    /// # Errors
+    /// - [`TilemapManagerError::MapShapeMismatch`](crate::tilemap_manager::TilemapManagerError::MapShapeMismatch)
+    fn check_layout(
+        &self,
+        _map_size: UVec2,
+        _origin: ChunkPos,
+    ) -> Result<(), crate::tilemap_manager::TilemapManagerError> {
+        Ok(())
+    }
+
+    /// Returns the amount of cells that are part of a map laid out in a rectangle of the given dimensions.
+    ///
+    /// Defaults to every cell of the rectangle
+    fn cell_count(&self, dimensions: UVec2) -> usize {
+        (dimensions.x * dimensions.y) as usize
+    }
+
     /// The maximum size that a chunk can be
     fn max_chunk_size(&self) -> UVec2;
 
-    /// Function that breaks a [`Vec<Vec<TileData>>`] down into a [`Vec<Vec<TileData>>`] of the given [`ChunkPos`] chunks data
+    /// Sets the maximum size that a chunk can be. Existing chunks are not changed, see
+    /// [`TilemapManager::rechunk`](crate::tilemap_manager::TilemapManager::rechunk)
+    fn set_max_chunk_size(&mut self, max_chunk_size: UVec2);
+
+    /// Function that breaks a `[Vec<TileData>]` down into a [`Vec<Vec<TileData>>`] of the given [`ChunkPos`] chunks data
+    ///
+    /// The given [`ChunkPos`] is relative to the first chunk of the data, eg (0, 0) is always the chunk holding `data[0][0]`
     fn break_data_vecs_down_into_chunk_data<TileData>(
         &self,
-        data: &Vec<Vec<TileData>>,
+        data: &[Vec<TileData>],
         chunk_pos: ChunkPos,
         max_chunk_size: UVec2,
     ) -> Vec<Vec<TileData>>
     where
-        TileData: Clone + Copy + Sized + Default + Send + Sync + 'static;
+        TileData: Clone + Sized + Send + Sync + 'static;
 
-    /// Function that breaks a [`Vec<Vec<TileData>>`] into [`Vec<Vec<Chunk<TileData>>>`]
+    /// Function that breaks a `[Vec<TileData>]` into [`Vec<Vec<Chunk<TileData>>>`]
     ///
     /// This function should:
     /// - Create new chunks
     /// - Insert the correct data for each chunk
     /// - Return a [`Vec<Vec<Chunk<TileData>>>`] where each chunk is correctly positioned.
     ///     - Correctly positioned meaning chunk 0:0 contains the tiles for cell positions 0:0 -> 0:max chunk size and max chunk size:0 and so forth for each chunk in order
+    ///     - Each chunks [`ChunkPos`] is offset by the given origin, so `data[0][0]` is the first cell of the origin chunk
     fn break_data_vecs_into_chunks<TileData, MapChunk>(
         &self,
-        data: &Vec<Vec<TileData>>,
+        data: &[Vec<TileData>],
+        origin: ChunkPos,
         max_chunk_size: UVec2,
         chunk_settings: MapChunk::ChunkSettings,
     ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
     where
-        TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
         MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default;
 
     /// Function that breaks a [`HashMap<TilePos, TileData>`] into [`Vec<Vec<Chunk<TileData>>>`]
+    ///
+    /// The first chunk is placed at the given origin [`ChunkPos`] and the map extends `map_size` cells from there
     fn break_hashmap_into_chunks<TileData, MapChunk>(
         &self,
         map_layer: impl MapLayer,
         data: &HashMap<Cell, TileData>,
+        origin: ChunkPos,
         map_size: UVec2,
         max_chunk_size: UVec2,
         chunk_settings: MapChunk::ChunkSettings,
     ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
     where
-        TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
         MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default;
 
     /// Adds the given hashmap of entities to the map
+    ///
+    /// The chunks are expected to be laid out starting at the [`ChunkPos`] of `chunks[0][0]`
     fn add_entities_to_layer<TileData, MapChunk>(
         &self,
-        map_layer: u32,
+        map_layer: usize,
         chunks: &mut Vec<Vec<Chunk<MapChunk, TileData>>>,
         entities: &HashMap<Cell, Entity>,
     ) where
-        TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
         MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
     {
-        for (cell, entity) in entities.iter() {
+        let origin = chunks[0][0].chunk_pos;
+        for (cell, entity) in entities
+            .iter()
+            .filter(|(cell, _)| self.contains_cell(**cell))
+        {
             let chunk_pos = self.into_chunk_pos(*cell);
-            let chunk = &mut chunks[chunk_pos.y() as usize][chunk_pos.x() as usize];
+            let chunk = &mut chunks[(chunk_pos.y() - origin.y()) as usize]
+                [(chunk_pos.x() - origin.x()) as usize];
             chunk.set_tile_entity(
                 map_layer,
                 MapChunk::into_chunk_cell(*cell, &chunk.chunk_settings),
@@ -0,0 +1,174 @@
+use crate::map::chunk::{Chunk, ChunkLayer};
+use crate::map::{MapData, Tilemap};
+use bevy::prelude::{Changed, DetectChangesMut, Entity, Event, EventWriter, Query};
+use lettuces::cell::Cell;
+use std::hash::Hash;
+
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Event sent by [`send_tile_changed_events`] for every tile whose data changed.
+///
+/// Opt in by adding the event and the system to your app for every tilemap type that should send it, and by
+/// enabling dirty cell tracking on the layers that should send it with
+/// [`TilemapBuilder::set_track_dirty_cells`](crate::tilemap_builder::TilemapBuilder::set_track_dirty_cells) or
+/// [`TilemapManager::set_track_dirty_cells`](crate::tilemap_manager::TilemapManager::set_track_dirty_cells)
+#[derive(Event, Hash, Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Hash))]
+pub struct TileChanged {
+    /// The [`Tilemap`] entity that the tile is in
+    pub tilemap: Entity,
+    /// The [`Cell`] of the tile
+    pub cell: Cell,
+    /// The index of the [`MapLayer`](crate::map::MapLayer) that the tile is in
+    pub layer: usize,
+}
+
+/// System that sends a [`TileChanged`] event for every dirty cell in the changed chunks of every tilemap and then
+/// clears the dirty cells. See [`ChunkLayer::dirty_cells`]
+///
+/// # Note
+/// - Systems that read the dirty cells of the chunks directly must run before this system
+pub fn send_tile_changed_events<TileData, MapChunk, Map>(
+    tilemap_query: Query<(Entity, &Tilemap, &Map)>,
+    mut chunk_query: Query<&mut Chunk<MapChunk, TileData>, Changed<Chunk<MapChunk, TileData>>>,
+    mut tile_changed_events: EventWriter<TileChanged>,
+) where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    for (tilemap_entity, tilemap, map) in tilemap_query.iter() {
+        for (chunk_pos, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let Ok(mut chunk) = chunk_query.get_mut(chunk_entity) else {
+                continue;
+            };
+            // Clearing the dirty cells is not a change to the tiles themselves
+            for (map_layer, layer) in chunk.bypass_change_detection().layers_mut() {
+                tile_changed_events.send_batch(layer.dirty_cells().map(|chunk_cell| TileChanged {
+                    tilemap: tilemap_entity,
+                    cell: map.into_cell(chunk_pos, chunk_cell),
+                    layer: map_layer,
+                }));
+                layer.clear_dirty_cells();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::map::chunk::{ChunkCell, ChunkLayer, ChunkPos};
+    use crate::map::{send_tile_changed_events, TileChanged};
+    use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use bevy::ecs::event::Events;
+    use bevy::ecs::schedule::Schedule;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::World;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    #[test]
+    fn changed_tiles_send_events() {
+        let mut world = World::new();
+        world.init_resource::<Events<TileChanged>>();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<u32, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let mut tilemap_builder = SquareTilemapBuilder::<u32, MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vec![vec![0u32; 4]; 4]),
+            SquareMapData {
+                max_chunk_size: UVec2::new(2, 2),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(2, 2),
+            },
+        )
+        .unwrap();
+        tilemap_builder.set_track_dirty_cells(MapLayers::Main, true);
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(send_tile_changed_events::<u32, SquareChunkLayer<u32>, SquareMapData>);
+        schedule.run(&mut world);
+        let mut reader = world.resource::<Events<TileChanged>>().get_reader();
+        assert_eq!(
+            reader.read(world.resource::<Events<TileChanged>>()).count(),
+            0
+        );
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        tilemap_manager.sets_tile_data(5, Cell::new(3, 2)).unwrap();
+        tilemap_manager.sets_tile_data(6, Cell::new(0, 1)).unwrap();
+        assert_eq!(
+            tilemap_manager
+                .get_chunk(ChunkPos::new(1, 1))
+                .unwrap()
+                .layer(MapLayers::Main.to_index())
+                .unwrap()
+                .dirty_cells()
+                .collect::<Vec<ChunkCell>>(),
+            vec![ChunkCell::new(1, 0)]
+        );
+
+        schedule.run(&mut world);
+        let mut events: Vec<TileChanged> = reader
+            .read(world.resource::<Events<TileChanged>>())
+            .copied()
+            .collect();
+        events.sort_by_key(|event| (event.cell.x, event.cell.y));
+        assert_eq!(
+            events,
+            vec![
+                TileChanged {
+                    tilemap: map_entity,
+                    cell: Cell::new(0, 1),
+                    layer: MapLayers::Main.to_index(),
+                },
+                TileChanged {
+                    tilemap: map_entity,
+                    cell: Cell::new(3, 2),
+                    layer: MapLayers::Main.to_index(),
+                },
+            ]
+        );
+
+        // The dirty cells were cleared so nothing is sent again
+        schedule.run(&mut world);
+        assert_eq!(
+            reader.read(world.resource::<Events<TileChanged>>()).count(),
+            0
+        );
+
+        // Layers that don't track their dirty cells send nothing
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        tilemap_manager
+            .set_track_dirty_cells(MapLayers::Main, false)
+            .unwrap();
+        tilemap_manager.sets_tile_data(7, Cell::new(1, 1)).unwrap();
+        schedule.run(&mut world);
+        assert_eq!(
+            reader.read(world.resource::<Events<TileChanged>>()).count(),
+            0
+        );
+    }
+}
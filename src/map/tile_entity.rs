@@ -0,0 +1,200 @@
+use crate::map::chunk::{Chunk, ChunkLayer};
+use crate::map::{MapData, Tilemap};
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use bevy::prelude::{Changed, Component, Entity, Local, Query, RemovedComponents, With};
+use bevy::utils::HashMap;
+use lettuces::cell::Cell;
+use std::hash::Hash;
+
+#[cfg(feature = "reflect")]
+use bevy::ecs::reflect::ReflectMapEntities;
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Component added to tile entities spawned through the [`TilemapManager`](crate::tilemap_manager::TilemapManager)
+/// or [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder) that records which tile the entity belongs to.
+///
+/// Used by [`remove_despawned_tile_entities`] to remove the entity from its chunk when it is despawned.
+#[derive(Component, Hash, Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Hash, MapEntities))]
+pub struct TileEntity {
+    /// The [`Tilemap`] entity that the tile is in
+    pub tilemap: Entity,
+    /// The [`Cell`] of the tile
+    pub cell: Cell,
+    /// The index of the [`MapLayer`](crate::map::MapLayer) that the tile is in
+    pub layer: usize,
+}
+
+impl MapEntities for TileEntity {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.tilemap = entity_mapper.map_entity(self.tilemap);
+    }
+}
+
+/// System that removes tile entities from their [`Chunk`] once they are despawned or their [`TileEntity`]
+/// component is removed.
+///
+/// Add it to your app for every tilemap type that uses tile entities.
+///
+/// # Note
+/// - Only entities with a [`TileEntity`] component are tracked
+pub fn remove_despawned_tile_entities<TileData, MapChunk, Map>(
+    mut tracked_entities: Local<HashMap<Entity, TileEntity>>,
+    changed_tile_entities: Query<(Entity, &TileEntity), Changed<TileEntity>>,
+    tile_entity_query: Query<(), With<TileEntity>>,
+    mut removed_tile_entities: RemovedComponents<TileEntity>,
+    tilemap_query: Query<(&Tilemap, &Map)>,
+    mut chunk_query: Query<&mut Chunk<MapChunk, TileData>>,
+) where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    for entity in removed_tile_entities.read() {
+        // The component was removed and inserted again in the same frame
+        if tile_entity_query.contains(entity) {
+            continue;
+        }
+        let Some(tile_entity) = tracked_entities.remove(&entity) else {
+            continue;
+        };
+        let Ok((tilemap, map)) = tilemap_query.get(tile_entity.tilemap) else {
+            continue;
+        };
+        let Some(chunk_entity) = tilemap.get_chunk_for_cell(tile_entity.cell, map) else {
+            continue;
+        };
+        let Ok(mut chunk) = chunk_query.get_mut(chunk_entity) else {
+            continue;
+        };
+        let chunk_cell = MapChunk::into_chunk_cell(tile_entity.cell, &chunk.chunk_settings);
+        let Some(layer) = chunk.layer_mut(tile_entity.layer) else {
+            continue;
+        };
+        // The cell might already have been given a new entity
+        if layer.get_tile_entity(chunk_cell) == Some(entity) {
+            layer.remove_tile_entity(chunk_cell);
+        }
+    }
+
+    for (entity, tile_entity) in changed_tile_entities.iter() {
+        tracked_entities.insert(entity, *tile_entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::map::chunk::ChunkPos;
+    use crate::map::{remove_despawned_tile_entities, TileEntity};
+    use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use crate::tilemap_manager::TilemapManagerError;
+    use bevy::ecs::schedule::Schedule;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::World;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    #[test]
+    fn despawned_tile_entities_are_unmapped() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<u32, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let mut layer = TilemapLayer::new_dense_from_vecs(vec![vec![0u32; 4]; 4]);
+        layer.spawn_entity_at_tile_pos(Cell::new(3, 3), (), &mut commands);
+        let map_entity = SquareTilemapBuilder::<u32, MapLayers>::new(
+            layer,
+            SquareMapData {
+                max_chunk_size: UVec2::new(2, 2),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(2, 2),
+            },
+        )
+        .unwrap()
+        .spawn_tilemap(&mut commands)
+        .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let built_entity = tilemap_manager.get_tile_entity(Cell::new(3, 3)).unwrap();
+        let spawned_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(0, 1))
+            .unwrap();
+        let despawned_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(1, 0))
+            .unwrap();
+        system_state.apply(&mut world);
+        assert_eq!(
+            world.get::<TileEntity>(built_entity),
+            Some(&TileEntity {
+                tilemap: map_entity,
+                cell: Cell::new(3, 3),
+                layer: MapLayers::Main.to_index(),
+            })
+        );
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            remove_despawned_tile_entities::<u32, SquareChunkLayer<u32>, SquareMapData>,
+        );
+        schedule.run(&mut world);
+
+        // Despawning through the manager removes the mapping straight away
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager
+            .despawn_tile_entity(Cell::new(1, 0))
+            .unwrap();
+        assert!(matches!(
+            tilemap_manager.get_tile_entity(Cell::new(1, 0)),
+            Err(TilemapManagerError::TileEntityDoesNotExist)
+        ));
+        system_state.apply(&mut world);
+        assert!(world.get_entity(despawned_entity).is_none());
+
+        // Entities despawned elsewhere are never returned and are unmapped by the system
+        world.despawn(spawned_entity);
+        world.despawn(built_entity);
+        let (_, tilemap_manager) = system_state.get_mut(&mut world);
+        assert!(tilemap_manager.get_tile_entity(Cell::new(0, 1)).is_err());
+        assert_eq!(
+            tilemap_manager
+                .get_chunk(ChunkPos::new(0, 0))
+                .unwrap()
+                .get_tile_entity_from_cell(MapLayers::Main, Cell::new(0, 1)),
+            Some(spawned_entity)
+        );
+
+        schedule.run(&mut world);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        for (chunk_pos, cell) in [
+            (ChunkPos::new(0, 0), Cell::new(0, 1)),
+            (ChunkPos::new(1, 1), Cell::new(3, 3)),
+        ] {
+            let chunk = tilemap_manager.get_chunk(chunk_pos).unwrap();
+            assert_eq!(chunk.get_tile_entity_from_cell(MapLayers::Main, cell), None);
+        }
+        let respawned_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(0, 1))
+            .unwrap();
+        assert_ne!(respawned_entity, spawned_entity);
+    }
+}
@@ -21,7 +21,7 @@ use lettuces::cell::Cell;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use super::MapData;
+use super::{LayerMask, MapData};
 
 /// The data structure containing information for each chunk.
 ///
@@ -35,6 +35,9 @@ use super::MapData;
 pub struct Tilemap {
     /// Struct containing [`Entity`] mappings to the [`Chunk`](super::chunk::Chunk)s that hold tile data
     chunks: Chunks,
+    /// The layers that were added with a different `TileData` than the main layer
+    #[cfg_attr(feature = "serde", serde(default))]
+    typed_layers: LayerMask,
 }
 
 impl MapEntities for Tilemap {
@@ -46,7 +49,10 @@ impl MapEntities for Tilemap {
 impl Tilemap {
     /// Creates a new [`Tilemap`] out of the given chunks struct
     pub fn new(chunks: Chunks) -> Tilemap {
-        Self { chunks }
+        Self {
+            chunks,
+            typed_layers: LayerMask::NONE,
+        }
     }
 
     /// Gets the chunk entity that contains this cell
@@ -73,4 +79,16 @@ impl Tilemap {
     pub fn chunks_mut(&mut self) -> &mut Chunks {
         &mut self.chunks
     }
+
+    /// Returns the layers that were added with
+    /// [`TilemapBuilder::add_typed_layer`](crate::tilemap_builder::TilemapBuilder::add_typed_layer)
+    pub fn typed_layers(&self) -> LayerMask {
+        self.typed_layers
+    }
+
+    /// Sets the layers that were added with
+    /// [`TilemapBuilder::add_typed_layer`](crate::tilemap_builder::TilemapBuilder::add_typed_layer)
+    pub fn set_typed_layers(&mut self, typed_layers: LayerMask) {
+        self.typed_layers = typed_layers;
+    }
 }
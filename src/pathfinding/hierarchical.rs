@@ -0,0 +1,560 @@
+use crate::map::chunk::{Chunk, ChunkLayer, ChunkPos};
+use crate::map::{Connectivity, LayerMask, MapData, Tilemap};
+use crate::pathfinding::{a_star, path_costs, CellCosts, Path};
+use crate::tilemap_manager::{TileReader, TilemapManagerError};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{Changed, Commands, Component, DetectChanges, Entity, Query, Ref, With};
+use bevy::utils::{HashMap, HashSet};
+use lettuces::cell::Cell;
+
+/// The cost of moving across a tilemap for hierarchical pathfinding, used both to build the [`ChunkPathGraph`]s
+/// and to find paths with the [`HierarchicalPathfinder`].
+///
+/// Must be added as a component to the tilemap entity. Changing the component rebuilds every graph of the map.
+pub trait PathCost<TileData>: Component {
+    /// The layers whose tile data is passed to [`cost`](PathCost::cost), in the order of their index
+    fn map_layers(&self) -> LayerMask;
+
+    /// Which neighbours of a cell paths can move to
+    fn connectivity(&self) -> Connectivity;
+
+    /// Returns the cost of entering the [`Cell`] or `None` if it can't be entered. Costs below 1 are raised to 1
+    fn cost(&self, cell: Cell, tiles: &[Option<&TileData>]) -> Option<u32>;
+}
+
+/// The precomputed graph of a chunk used by the [`HierarchicalPathfinder`]. Added to chunk entities and kept up to
+/// date by [`update_chunk_path_graphs`].
+///
+/// Entrances are the cells on the border of the chunk that paths cross into neighbouring chunks through. Every run
+/// of cells along a border where both sides can be entered gets one entrance in its middle, which is picked the same
+/// way from both chunks so their entrances line up.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkPathGraph {
+    /// Every entrance along with the cells in neighbouring chunks that it crosses into
+    entrances: HashMap<Cell, Vec<Cell>>,
+    /// The cost of the cheapest path inside the chunk from each entrance to the other entrances it reaches
+    paths: HashMap<Cell, Vec<(Cell, u32)>>,
+}
+
+impl ChunkPathGraph {
+    /// Returns an iterator over the entrances of the chunk
+    pub fn entrances(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.entrances.keys().copied()
+    }
+
+    /// Returns the cost of the cheapest path that stays inside the chunk between two entrances, if there is one
+    pub fn path_cost(&self, from: Cell, to: Cell) -> Option<u32> {
+        self.paths
+            .get(&from)?
+            .iter()
+            .find(|(entrance, _)| *entrance == to)
+            .map(|(_, cost)| *cost)
+    }
+}
+
+/// System that builds the [`ChunkPathGraph`] of every loaded chunk of every tilemap with a [`PathCost`].
+///
+/// Graphs are only rebuilt for chunks that changed or don't have one yet, along with the chunks around them as they
+/// share entrances on their borders. Every graph of a map is rebuilt when its [`PathCost`] changes.
+pub fn update_chunk_path_graphs<TileData, MapChunk, Map, Cost>(
+    mut commands: Commands,
+    tilemap_query: Query<(&Tilemap, &Map, Ref<Cost>)>,
+    chunk_query: Query<&Chunk<MapChunk, TileData>>,
+    changed_chunk_query: Query<(), Changed<Chunk<MapChunk, TileData>>>,
+    graph_query: Query<(), With<ChunkPathGraph>>,
+) where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+    Cost: PathCost<TileData>,
+{
+    for (tilemap, map, path_cost) in tilemap_query.iter() {
+        let mut outdated_chunks: HashSet<ChunkPos> = HashSet::new();
+        for (chunk_pos, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            if path_cost.is_changed()
+                || changed_chunk_query.contains(chunk_entity)
+                || !graph_query.contains(chunk_entity)
+            {
+                for y in -1..=1 {
+                    for x in -1..=1 {
+                        outdated_chunks.insert(ChunkPos::new(chunk_pos.x() + x, chunk_pos.y() + y));
+                    }
+                }
+            }
+        }
+
+        let connectivity = path_cost.connectivity();
+        let mut cell_costs = CellCosts::new(
+            TileReader::new(tilemap, map, |chunk_entity| {
+                chunk_query.get(chunk_entity).ok()
+            }),
+            path_cost.map_layers().indices().collect(),
+            |cell, tiles| path_cost.cost(cell, tiles),
+        );
+        for chunk_pos in outdated_chunks {
+            let Some(chunk_entity) = tilemap.get_chunk(chunk_pos) else {
+                continue;
+            };
+            commands.entity(chunk_entity).insert(build_chunk_path_graph(
+                &mut cell_costs,
+                connectivity,
+                chunk_pos,
+            ));
+        }
+    }
+}
+
+/// Builds the [`ChunkPathGraph`] of the chunk at the given [`ChunkPos`]
+fn build_chunk_path_graph<TileData, MapChunk, Map, F>(
+    cell_costs: &mut CellCosts<TileData, MapChunk, Map, F>,
+    connectivity: Connectivity,
+    chunk_pos: ChunkPos,
+) -> ChunkPathGraph
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+    F: FnMut(Cell, &[Option<&TileData>]) -> Option<u32>,
+{
+    let map = cell_costs.map();
+    // Every pair of cells across the border where both can be entered, grouped by the chunk they cross into
+    let mut crossings: HashMap<ChunkPos, Vec<(Cell, Cell)>> = HashMap::default();
+    for cell in cell_costs.tile_reader().chunk_cells(chunk_pos) {
+        if cell_costs.cost(cell).is_none() {
+            continue;
+        }
+        for neighbour in map.neighbours(cell, connectivity) {
+            let neighbour_chunk_pos = map.into_chunk_pos(neighbour);
+            if neighbour_chunk_pos != chunk_pos && cell_costs.cost(neighbour).is_some() {
+                crossings
+                    .entry(neighbour_chunk_pos)
+                    .or_default()
+                    .push((cell, neighbour));
+            }
+        }
+    }
+
+    let mut graph = ChunkPathGraph::default();
+    for crossings in crossings.into_values() {
+        for (entrance, exit) in border_entrances(map, connectivity, crossings) {
+            graph.entrances.entry(entrance).or_default().push(exit);
+        }
+    }
+    let entrances: Vec<Cell> = graph.entrances().collect();
+    for entrance in entrances.iter() {
+        let reachable = path_costs(*entrance, |cell| {
+            cell_costs.successors(cell, connectivity, |neighbour| {
+                map.into_chunk_pos(neighbour) == chunk_pos
+            })
+        });
+        graph.paths.insert(
+            *entrance,
+            entrances
+                .iter()
+                .filter(|other| *other != entrance)
+                .filter_map(|other| reachable.get(other).map(|cost| (*other, *cost)))
+                .collect(),
+        );
+    }
+    graph
+}
+
+/// Splits the crossings along the border with one chunk into runs of crossings that touch and returns the crossing
+/// in the middle of each run.
+///
+/// Both chunks of the border find the same runs and pick the same crossings, only with the cells swapped around.
+fn border_entrances<Map: MapData>(
+    map: &Map,
+    connectivity: Connectivity,
+    mut crossings: Vec<(Cell, Cell)>,
+) -> Vec<(Cell, Cell)> {
+    let touches = |a: Cell, b: Cell| a == b || map.neighbours(a, connectivity).contains(&b);
+    let mut entrances = vec![];
+    while let Some(first) = crossings.pop() {
+        let mut run = vec![first];
+        let mut index = 0;
+        while index < run.len() {
+            let (cell, neighbour) = run[index];
+            let (touching, rest) = crossings.into_iter().partition(|(other, other_neighbour)| {
+                touches(cell, *other) || touches(neighbour, *other_neighbour)
+            });
+            crossings = rest;
+            run.extend::<Vec<_>>(touching);
+            index += 1;
+        }
+        run.sort_by_key(|(cell, neighbour)| {
+            let (cell, neighbour) = ((cell.x, cell.y), (neighbour.x, neighbour.y));
+            (cell.min(neighbour), cell.max(neighbour))
+        });
+        entrances.push(run[run.len() / 2]);
+    }
+    entrances
+}
+
+/// A [`SystemParam`] that finds paths across large tilemaps with hierarchical pathfinding (HPA*), using the chunks
+/// of the map as clusters.
+///
+/// Paths are first planned between the entrances of the [`ChunkPathGraph`]s of the chunks and then refined into
+/// cells inside each chunk they cross. Add [`update_chunk_path_graphs`] to your app to keep the graphs up to date.
+///
+/// # Internal [`SystemParam`]s
+/// - `Query<(&Tilemap, &Map, &Cost)>`
+/// - `Query<(&Chunk<MapChunk, TileData>, Option<&ChunkPathGraph>)>`
+#[derive(SystemParam)]
+pub struct HierarchicalPathfinder<'w, 's, TileData, MapChunk, Map, Cost>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+    Cost: PathCost<TileData>,
+{
+    tilemap_query: Query<'w, 's, (&'static Tilemap, &'static Map, &'static Cost)>,
+    chunk_query: Query<
+        'w,
+        's,
+        (
+            &'static Chunk<MapChunk, TileData>,
+            Option<&'static ChunkPathGraph>,
+        ),
+    >,
+}
+
+impl<'w, 's, TileData, MapChunk, Map, Cost>
+    HierarchicalPathfinder<'w, 's, TileData, MapChunk, Map, Cost>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+    Cost: PathCost<TileData>,
+{
+    /// Finds a [`Path`] between two [`Cell`]s of the given tilemap. Returns `None` if there is no path.
+    ///
+    /// The path is close to the cheapest path but not always the cheapest, as it crosses between chunks at their
+    /// entrances.
+    ///
+    /// # Note
+    /// - Paths only cross chunks that have a [`ChunkPathGraph`] and use the graphs from the last time
+    ///   [`update_chunk_path_graphs`] ran
+    /// - The start cell doesn't count towards the cost of the path
+    pub fn find_path(
+        &self,
+        tilemap_entity: Entity,
+        start: Cell,
+        goal: Cell,
+    ) -> Result<Option<Path>, TilemapManagerError> {
+        let (tilemap, map, path_cost) = self.tilemap_query.get(tilemap_entity)?;
+        let connectivity = path_cost.connectivity();
+        let mut cell_costs = CellCosts::new(
+            TileReader::new(tilemap, map, |chunk_entity| {
+                self.chunk_query
+                    .get(chunk_entity)
+                    .ok()
+                    .map(|(chunk, _)| chunk)
+            }),
+            path_cost.map_layers().indices().collect(),
+            |cell, tiles| path_cost.cost(cell, tiles),
+        );
+        if !cell_costs.tile_reader().contains(start) || cell_costs.cost(goal).is_none() {
+            return Ok(None);
+        }
+        let graph = |chunk_pos: ChunkPos| {
+            tilemap
+                .get_chunk(chunk_pos)
+                .and_then(|chunk_entity| self.chunk_query.get(chunk_entity).ok())
+                .and_then(|(_, graph)| graph)
+        };
+        let in_chunk =
+            |chunk_pos: ChunkPos| move |cell: Cell| map.into_chunk_pos(cell) == chunk_pos;
+        let (start_chunk, goal_chunk) = (map.into_chunk_pos(start), map.into_chunk_pos(goal));
+
+        // The start and goal are joined to the entrances of their chunks for the search
+        let from_start = path_costs(start, |cell| {
+            cell_costs.successors(cell, connectivity, in_chunk(start_chunk))
+        });
+        let mut start_edges: Vec<(Cell, u32)> = graph(start_chunk)
+            .into_iter()
+            .flat_map(|graph| graph.entrances())
+            .filter_map(|entrance| from_start.get(&entrance).map(|cost| (entrance, *cost)))
+            .collect();
+        if let Some(cost) = from_start.get(&goal) {
+            start_edges.push((goal, *cost));
+        }
+        // Searching backwards from the goal, where stepping back from a cell costs what entering it does
+        let to_goal = path_costs(goal, |cell| {
+            let Some(cost) = cell_costs.cost(cell) else {
+                return vec![];
+            };
+            cell_costs
+                .successors(cell, connectivity, in_chunk(goal_chunk))
+                .into_iter()
+                .map(|(neighbour, _)| (neighbour, cost))
+                .collect()
+        });
+        let goal_edges: HashMap<Cell, u32> = graph(goal_chunk)
+            .into_iter()
+            .flat_map(|graph| graph.entrances())
+            .filter_map(|entrance| to_goal.get(&entrance).map(|cost| (entrance, *cost)))
+            .collect();
+
+        let Some(abstract_path) = a_star(
+            start,
+            goal,
+            |cell| {
+                let mut edges = vec![];
+                if cell == start {
+                    edges.extend(start_edges.iter().copied());
+                }
+                if let Some(cost) = goal_edges.get(&cell) {
+                    edges.push((goal, *cost));
+                }
+                let Some(chunk_graph) = graph(map.into_chunk_pos(cell)) else {
+                    return edges;
+                };
+                if let Some(paths) = chunk_graph.paths.get(&cell) {
+                    edges.extend(paths.iter().copied());
+                }
+                for exit in chunk_graph.entrances.get(&cell).into_iter().flatten() {
+                    let exit_is_entrance = graph(map.into_chunk_pos(*exit))
+                        .is_some_and(|exit_graph| exit_graph.entrances.contains_key(exit));
+                    if let (true, Some(cost)) = (exit_is_entrance, cell_costs.cost(*exit)) {
+                        edges.push((*exit, cost));
+                    }
+                }
+                edges
+            },
+            |cell| map.distance(cell, goal, connectivity),
+        ) else {
+            return Ok(None);
+        };
+
+        // Steps between chunks are single cells, steps inside a chunk are refined into the cells between them
+        let mut cells = vec![start];
+        for step in abstract_path.cells.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            let chunk_pos = map.into_chunk_pos(from);
+            if map.into_chunk_pos(to) != chunk_pos {
+                cells.push(to);
+                continue;
+            }
+            let Some(refined) = a_star(
+                from,
+                to,
+                |cell| cell_costs.successors(cell, connectivity, in_chunk(chunk_pos)),
+                |cell| map.distance(cell, to, connectivity),
+            ) else {
+                return Ok(None);
+            };
+            cells.extend(refined.cells.into_iter().skip(1));
+        }
+        Ok(Some(Path {
+            cells,
+            cost: abstract_path.cost,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::map::chunk::ChunkPos;
+    use crate::map::{Connectivity, LayerMask, MapData, Tilemap};
+    use crate::pathfinding::{
+        update_chunk_path_graphs, ChunkPathGraph, HierarchicalPathfinder, PathCost,
+    };
+    use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use bevy::ecs::schedule::Schedule;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::{Component, Entity, World};
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Terrain,
+        Walls,
+    }
+
+    /// Entering a tile costs its terrain and walls can't be entered
+    #[derive(Component)]
+    struct TerrainCost;
+
+    impl PathCost<u32> for TerrainCost {
+        fn map_layers(&self) -> LayerMask {
+            MapLayers::Terrain.to_bits() | MapLayers::Walls.to_bits()
+        }
+
+        fn connectivity(&self) -> Connectivity {
+            Connectivity::Edges
+        }
+
+        fn cost(&self, _: Cell, tiles: &[Option<&u32>]) -> Option<u32> {
+            match tiles {
+                [Some(terrain), None] => Some(**terrain),
+                _ => None,
+            }
+        }
+    }
+
+    type Pathfinder<'w, 's> =
+        HierarchicalPathfinder<'w, 's, u32, SquareChunkLayer<u32>, SquareMapData, TerrainCost>;
+
+    /// Spawns a 12x12 square map with chunks of 4x4 and a wall down column 5 that only leaves the bottom row open
+    fn spawn_walled_map(world: &mut World) -> Entity {
+        let mut system_state: SystemState<Commands> = SystemState::new(world);
+        let mut commands = system_state.get_mut(world);
+        let mut tilemap_builder = SquareTilemapBuilder::<u32, MapLayers>::new(
+            TilemapLayer::new_dense_uniform(12, 12, 1),
+            SquareMapData {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(
+                    12,
+                    12,
+                    (0..11).map(|y| (Cell::new(5, y), 1)).collect(),
+                ),
+                MapLayers::Walls,
+            )
+            .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        commands.entity(map_entity).insert(TerrainCost);
+        system_state.apply(world);
+        map_entity
+    }
+
+    fn update_schedule() -> Schedule {
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            update_chunk_path_graphs::<u32, SquareChunkLayer<u32>, SquareMapData, TerrainCost>,
+        );
+        schedule
+    }
+
+    #[test]
+    fn hierarchical_paths_cross_chunks() {
+        let mut world = World::new();
+        let map_entity = spawn_walled_map(&mut world);
+        update_schedule().run(&mut world);
+
+        let mut system_state: SystemState<Pathfinder> = SystemState::new(&mut world);
+        let pathfinder = system_state.get(&world);
+        let path = pathfinder
+            .find_path(map_entity, Cell::new(0, 0), Cell::new(11, 0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.cells.first(), Some(&Cell::new(0, 0)));
+        assert_eq!(path.cells.last(), Some(&Cell::new(11, 0)));
+        assert!(path.cells.contains(&Cell::new(5, 11)));
+        // Every tile costs 1, so the cost is the amount of steps
+        assert_eq!(path.cost as usize, path.cells.len() - 1);
+        let map_data = SquareMapData {
+            max_chunk_size: UVec2::new(4, 4),
+        };
+        for step in path.cells.windows(2) {
+            assert!(map_data
+                .neighbours(step[0], Connectivity::Edges)
+                .contains(&step[1]));
+        }
+
+        // Never cheaper than the cheapest path
+        let mut manager_state: SystemState<SquareTilemapManager<u32, MapLayers>> =
+            SystemState::new(&mut world);
+        let mut tilemap_manager = manager_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let cheapest = tilemap_manager
+            .find_path(
+                Cell::new(0, 0),
+                Cell::new(11, 0),
+                Connectivity::Edges,
+                &[MapLayers::Terrain, MapLayers::Walls],
+                |cell, tiles| TerrainCost.cost(cell, tiles),
+            )
+            .unwrap()
+            .unwrap();
+        assert!(path.cost >= cheapest.cost);
+
+        // Paths inside a single chunk and to cells that can't be entered
+        let pathfinder = system_state.get(&world);
+        let path = pathfinder
+            .find_path(map_entity, Cell::new(0, 0), Cell::new(2, 3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(
+            pathfinder
+                .find_path(map_entity, Cell::new(0, 0), Cell::new(5, 0))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn graphs_only_update_around_changed_chunks() {
+        let mut world = World::new();
+        let map_entity = spawn_walled_map(&mut world);
+        let mut schedule = update_schedule();
+        schedule.run(&mut world);
+
+        let chunk_entity = |world: &World, chunk_pos: ChunkPos| {
+            world
+                .get::<Tilemap>(map_entity)
+                .unwrap()
+                .get_chunk(chunk_pos)
+                .unwrap()
+        };
+        let graph = |world: &World, chunk_pos: ChunkPos| {
+            world
+                .get::<ChunkPathGraph>(chunk_entity(world, chunk_pos))
+                .unwrap()
+                .clone()
+        };
+        // Whether a path inside chunk (1, 2) joins its left and right sides, around the end of the wall
+        let crosses_wall = |graph: ChunkPathGraph| {
+            graph.entrances().filter(|left| left.x == 4).any(|left| {
+                graph
+                    .entrances()
+                    .any(|right| right.x == 7 && graph.path_cost(left, right).is_some())
+            })
+        };
+        assert!(crosses_wall(graph(&world, ChunkPos::new(1, 2))));
+        // A graph that would be rebuilt if the update reached it
+        let far_chunk = chunk_entity(&world, ChunkPos::new(0, 0));
+        world
+            .entity_mut(far_chunk)
+            .insert(ChunkPathGraph::default());
+
+        // Closing the gap in the wall
+        let mut manager_state: SystemState<SquareTilemapManager<u32, MapLayers>> =
+            SystemState::new(&mut world);
+        let mut tilemap_manager = manager_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        tilemap_manager.set_layer(MapLayers::Walls);
+        tilemap_manager.sets_tile_data(1, Cell::new(5, 11)).unwrap();
+        schedule.run(&mut world);
+
+        assert!(!crosses_wall(graph(&world, ChunkPos::new(1, 2))));
+        assert_eq!(
+            graph(&world, ChunkPos::new(0, 0)),
+            ChunkPathGraph::default()
+        );
+        let mut system_state: SystemState<Pathfinder> = SystemState::new(&mut world);
+        assert_eq!(
+            system_state
+                .get(&world)
+                .find_path(map_entity, Cell::new(0, 0), Cell::new(11, 0))
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,528 @@
+mod hierarchical;
+
+pub use hierarchical::{
+    update_chunk_path_graphs, ChunkPathGraph, HierarchicalPathfinder, PathCost,
+};
+
+use crate::map::chunk::ChunkLayer;
+use crate::map::{Connectivity, MapData, MapLayer};
+use crate::tilemap_manager::{TileReader, TilemapManager, TilemapManagerError};
+use bevy::utils::HashMap;
+use lettuces::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+/// A path across a tilemap. See [`TilemapManager::find_path`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    /// The cells of the path in order, from the start cell to the goal cell
+    pub cells: Vec<Cell>,
+    /// The total cost of entering every cell of the path after the start cell
+    pub cost: u32,
+}
+
+impl<'w, 's, TileData, MapLayers, MapChunk, Map>
+    TilemapManager<'w, 's, TileData, MapLayers, MapChunk, Map>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + Default + Clone + Copy + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    /// Finds the cheapest [`Path`] between two [`Cell`]s with A*, moving between the
+    /// [`neighbours`](MapData::neighbours) with the given [`Connectivity`]. Returns `None` if there is no path.
+    ///
+    /// `cost` is called with every cell the search wants to enter along with its tile data in each of the given
+    /// `map_layers`, in the same order. It returns the cost of entering the cell or `None` if the cell can't be
+    /// entered.
+    ///
+    /// Tiles are read straight from the chunks, so each chunk is only looked up once for the whole search.
+    ///
+    /// # Note
+    /// - Entering a cell costs at least 1, lower costs are raised to 1
+    /// - The start cell is never passed to `cost` and doesn't count towards the cost of the path
+    /// - Cells outside of the map or in chunks that are not loaded can't be entered
+    pub fn find_path(
+        &self,
+        start: Cell,
+        goal: Cell,
+        connectivity: Connectivity,
+        map_layers: &[MapLayers],
+        cost: impl FnMut(Cell, &[Option<&TileData>]) -> Option<u32>,
+    ) -> Result<Option<Path>, TilemapManagerError> {
+        let mut cell_costs = CellCosts::new(
+            self.tile_reader()?,
+            map_layers
+                .iter()
+                .map(|map_layer| map_layer.to_index())
+                .collect(),
+            cost,
+        );
+        if !cell_costs.tile_reader().contains(start) || !cell_costs.tile_reader().contains(goal) {
+            return Ok(None);
+        }
+        let map = cell_costs.map();
+        Ok(a_star(
+            start,
+            goal,
+            |cell| cell_costs.successors(cell, connectivity, |_| true),
+            |cell| map.distance(cell, goal, connectivity),
+        ))
+    }
+}
+
+/// Works out the cost of entering cells for a search from their tiles in the given layers, asking for each cell
+/// only once.
+pub(crate) struct CellCosts<'a, TileData, MapChunk, Map, F>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+    F: FnMut(Cell, &[Option<&TileData>]) -> Option<u32>,
+{
+    tile_reader: TileReader<'a, TileData, MapChunk, Map>,
+    map_layers: Vec<usize>,
+    cost: F,
+    tiles: Vec<Option<&'a TileData>>,
+    costs: HashMap<Cell, Option<u32>>,
+}
+
+impl<'a, TileData, MapChunk, Map, F> CellCosts<'a, TileData, MapChunk, Map, F>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+    F: FnMut(Cell, &[Option<&TileData>]) -> Option<u32>,
+{
+    pub(crate) fn new(
+        tile_reader: TileReader<'a, TileData, MapChunk, Map>,
+        map_layers: Vec<usize>,
+        cost: F,
+    ) -> Self {
+        Self {
+            tile_reader,
+            tiles: Vec::with_capacity(map_layers.len()),
+            map_layers,
+            cost,
+            costs: HashMap::default(),
+        }
+    }
+
+    pub(crate) fn map(&self) -> &'a Map {
+        self.tile_reader.map
+    }
+
+    pub(crate) fn tile_reader(&mut self) -> &mut TileReader<'a, TileData, MapChunk, Map> {
+        &mut self.tile_reader
+    }
+
+    /// Returns the cost of entering the [`Cell`], raised to at least 1, or `None` if it can't be entered
+    pub(crate) fn cost(&mut self, cell: Cell) -> Option<u32> {
+        if let Some(cost) = self.costs.get(&cell) {
+            return *cost;
+        }
+        let cost = if self.tile_reader.contains(cell) {
+            self.tiles.clear();
+            for map_layer in &self.map_layers {
+                self.tiles
+                    .push(self.tile_reader.tile_data(*map_layer, cell));
+            }
+            (self.cost)(cell, &self.tiles).map(|cost| cost.max(1))
+        } else {
+            None
+        };
+        self.costs.insert(cell, cost);
+        cost
+    }
+
+    /// Returns the neighbours of the [`Cell`] that pass the filter and can be entered along with the cost of
+    /// entering them
+    pub(crate) fn successors(
+        &mut self,
+        cell: Cell,
+        connectivity: Connectivity,
+        filter: impl Fn(Cell) -> bool,
+    ) -> Vec<(Cell, u32)> {
+        self.map()
+            .neighbours(cell, connectivity)
+            .into_iter()
+            .filter(|neighbour| filter(*neighbour))
+            .filter_map(|neighbour| self.cost(neighbour).map(|cost| (neighbour, cost)))
+            .collect()
+    }
+}
+
+/// Finds the cheapest [`Path`] from `start` to `goal` with A*.
+///
+/// `successors` returns the cells that can be moved to from a cell along with the cost of moving there.
+/// `heuristic` must never return more than the cost left to the goal for the path to be the cheapest.
+pub(crate) fn a_star(
+    start: Cell,
+    goal: Cell,
+    mut successors: impl FnMut(Cell) -> Vec<(Cell, u32)>,
+    heuristic: impl Fn(Cell) -> u32,
+) -> Option<Path> {
+    let mut path_costs: HashMap<Cell, u32> = HashMap::default();
+    let mut came_from: HashMap<Cell, Cell> = HashMap::default();
+    // Ties are broken towards the cell furthest along its path, which finishes straight paths faster
+    let mut open = BinaryHeap::new();
+    path_costs.insert(start, 0);
+    open.push(Reverse((heuristic(start), Reverse(0), start.x, start.y)));
+
+    while let Some(Reverse((_, Reverse(path_cost), x, y))) = open.pop() {
+        let cell = Cell::new(x, y);
+        if cell == goal {
+            let mut cells = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from.get(&current) {
+                cells.push(*previous);
+                current = *previous;
+            }
+            cells.reverse();
+            return Some(Path {
+                cells,
+                cost: path_cost,
+            });
+        }
+        if path_costs.get(&cell).is_some_and(|best| *best < path_cost) {
+            continue;
+        }
+        for (successor, step_cost) in successors(cell) {
+            let next_cost = path_cost.saturating_add(step_cost);
+            if path_costs
+                .get(&successor)
+                .is_some_and(|best| *best <= next_cost)
+            {
+                continue;
+            }
+            path_costs.insert(successor, next_cost);
+            came_from.insert(successor, cell);
+            open.push(Reverse((
+                next_cost.saturating_add(heuristic(successor)),
+                Reverse(next_cost),
+                successor.x,
+                successor.y,
+            )));
+        }
+    }
+    None
+}
+
+/// Returns the cost of the cheapest path from `start` to every cell it can reach with Dijkstra.
+///
+/// `successors` returns the cells that can be moved to from a cell along with the cost of moving there
+pub(crate) fn path_costs(
+    start: Cell,
+    mut successors: impl FnMut(Cell) -> Vec<(Cell, u32)>,
+) -> HashMap<Cell, u32> {
+    let mut path_costs: HashMap<Cell, u32> = HashMap::default();
+    let mut open = BinaryHeap::new();
+    path_costs.insert(start, 0);
+    open.push(Reverse((0, start.x, start.y)));
+
+    while let Some(Reverse((path_cost, x, y))) = open.pop() {
+        let cell = Cell::new(x, y);
+        if path_costs.get(&cell).is_some_and(|best| *best < path_cost) {
+            continue;
+        }
+        for (successor, step_cost) in successors(cell) {
+            let next_cost = path_cost.saturating_add(step_cost);
+            if path_costs
+                .get(&successor)
+                .is_some_and(|best| *best <= next_cost)
+            {
+                continue;
+            }
+            path_costs.insert(successor, next_cost);
+            open.push(Reverse((next_cost, successor.x, successor.y)));
+        }
+    }
+    path_costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Path;
+    use crate as bevy_sparse_tilemap;
+    use crate::hex::map_chunk_layer::HexagonChunkSettings;
+    use crate::hex::map_data::HexMapData;
+    use crate::hex::{cell_to_hex, HexMapShape, HexOffset, HexTilemapBuilder, HexTilemapManager};
+    use crate::map::{Connectivity, MapData};
+    use crate::square::map_chunk_layer::SquareChunkSettings;
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::{Entity, World};
+    use bevy::utils::HashMap;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+    use lettuces::HexOrientation;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Terrain,
+        Walls,
+    }
+
+    /// Entering a tile costs its terrain and walls can't be entered
+    fn terrain_cost(_: Cell, tiles: &[Option<&u32>]) -> Option<u32> {
+        match tiles {
+            [Some(terrain), None] => Some(**terrain),
+            _ => None,
+        }
+    }
+
+    /// Spawns a square map with chunks of 3x3 where the terrain layer holds the given costs and the sparse walls
+    /// layer holds the given walls
+    fn spawn_square_map(
+        terrain: Vec<Vec<u32>>,
+        walls: &[Cell],
+    ) -> (
+        World,
+        SystemState<(
+            Commands<'static, 'static>,
+            SquareTilemapManager<'static, 'static, u32, MapLayers>,
+        )>,
+        Entity,
+    ) {
+        let (width, height) = (terrain[0].len(), terrain.len());
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<u32, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let mut tilemap_builder = SquareTilemapBuilder::<u32, MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(terrain),
+            SquareMapData {
+                max_chunk_size: UVec2::new(3, 3),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(3, 3),
+            },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(
+                    width,
+                    height,
+                    walls.iter().map(|cell| (*cell, 1)).collect(),
+                ),
+                MapLayers::Walls,
+            )
+            .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+        (world, system_state, map_entity)
+    }
+
+    /// Asserts that every cell of the path is a neighbour of the one before it
+    fn assert_connected(map_data: &impl MapData, cells: &[Cell], connectivity: Connectivity) {
+        for step in cells.windows(2) {
+            assert!(map_data
+                .neighbours(step[0], connectivity)
+                .contains(&step[1]));
+        }
+    }
+
+    #[test]
+    fn square_paths_go_around_walls() {
+        // A wall down column 3 that only leaves the bottom row open
+        let walls: Vec<Cell> = (0..4).map(|y| Cell::new(3, y)).collect();
+        let (mut world, mut system_state, map_entity) =
+            spawn_square_map(vec![vec![1; 7]; 5], &walls);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let map_data = SquareMapData {
+            max_chunk_size: UVec2::new(3, 3),
+        };
+        let layers = [MapLayers::Terrain, MapLayers::Walls];
+
+        let path = tilemap_manager
+            .find_path(
+                Cell::new(0, 0),
+                Cell::new(6, 0),
+                Connectivity::Edges,
+                &layers,
+                terrain_cost,
+            )
+            .unwrap()
+            .unwrap();
+        // Down 4, across 6 and back up 4
+        assert_eq!(path.cost, 14);
+        assert_eq!(path.cells.len(), 15);
+        assert_eq!(path.cells.first(), Some(&Cell::new(0, 0)));
+        assert_eq!(path.cells.last(), Some(&Cell::new(6, 0)));
+        assert!(path.cells.contains(&Cell::new(3, 4)));
+        assert_connected(&map_data, &path.cells, Connectivity::Edges);
+
+        // Moving diagonally cuts the corners of the detour
+        let path = tilemap_manager
+            .find_path(
+                Cell::new(0, 0),
+                Cell::new(6, 0),
+                Connectivity::Vertices,
+                &layers,
+                terrain_cost,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.cost, 8);
+        assert_eq!(path.cells.len(), 9);
+        assert_connected(&map_data, &path.cells, Connectivity::Vertices);
+
+        // Ignoring the walls layer goes straight through
+        let path = tilemap_manager
+            .find_path(
+                Cell::new(0, 0),
+                Cell::new(6, 0),
+                Connectivity::Edges,
+                &[MapLayers::Terrain],
+                |_, tiles| tiles[0].copied(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.cost, 6);
+    }
+
+    #[test]
+    fn square_paths_avoid_expensive_tiles() {
+        // A swamp along the middle of row 1
+        let mut terrain = vec![vec![1; 7]; 3];
+        terrain[1][1..6].fill(9);
+        let (mut world, mut system_state, map_entity) = spawn_square_map(terrain, &[]);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        let path = tilemap_manager
+            .find_path(
+                Cell::new(0, 1),
+                Cell::new(6, 1),
+                Connectivity::Edges,
+                &[MapLayers::Terrain, MapLayers::Walls],
+                terrain_cost,
+            )
+            .unwrap()
+            .unwrap();
+        // Stepping out of the swamp row and back in is cheaper than the 46 it costs to cross it
+        assert_eq!(path.cost, 8);
+        assert!(path.cells[1..path.cells.len() - 1]
+            .iter()
+            .all(|cell| cell.y != 1));
+    }
+
+    #[test]
+    fn square_paths_that_do_not_exist() {
+        // A wall across the whole map
+        let walls: Vec<Cell> = (0..5).map(|y| Cell::new(3, y)).collect();
+        let (mut world, mut system_state, map_entity) =
+            spawn_square_map(vec![vec![1; 7]; 5], &walls);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let layers = [MapLayers::Terrain, MapLayers::Walls];
+
+        let find_path = |start: Cell, goal: Cell| {
+            tilemap_manager
+                .find_path(start, goal, Connectivity::Vertices, &layers, terrain_cost)
+                .unwrap()
+        };
+        assert_eq!(find_path(Cell::new(0, 0), Cell::new(6, 4)), None);
+        // Goals outside of the map, including in the unused part of the last chunks
+        assert_eq!(find_path(Cell::new(0, 0), Cell::new(-1, 0)), None);
+        assert_eq!(find_path(Cell::new(0, 0), Cell::new(0, 5)), None);
+        assert_eq!(
+            find_path(Cell::new(1, 1), Cell::new(1, 1)),
+            Some(Path {
+                cells: vec![Cell::new(1, 1)],
+                cost: 0
+            })
+        );
+    }
+
+    #[test]
+    fn hex_paths() {
+        for orientation in [HexOrientation::Pointy, HexOrientation::Flat] {
+            for offset in [HexOffset::Odd, HexOffset::Even] {
+                let map_data = HexMapData {
+                    max_chunk_size: UVec2::new(3, 3),
+                    orientation,
+                    offset,
+                    shape: HexMapShape::Rectangle,
+                };
+                let start = map_data.offset_coordinates_to_cell([0, 0]);
+                let goal = map_data.offset_coordinates_to_cell([6, 5]);
+                // A wall across every row of column 3 except the last one
+                let walls: HashMap<Cell, u32> = (0..5)
+                    .map(|row| (map_data.offset_coordinates_to_cell([3, row]), 1))
+                    .collect();
+
+                let mut world = World::new();
+                let mut system_state: SystemState<(Commands, HexTilemapManager<u32, MapLayers>)> =
+                    SystemState::new(&mut world);
+                let (mut commands, _) = system_state.get_mut(&mut world);
+                let mut tilemap_builder = HexTilemapBuilder::<u32, MapLayers>::new(
+                    TilemapLayer::new_dense_uniform(7, 6, 1),
+                    HexMapData {
+                        max_chunk_size: UVec2::new(3, 3),
+                        orientation,
+                        offset,
+                        shape: HexMapShape::Rectangle,
+                    },
+                    HexagonChunkSettings {
+                        orientation,
+                        offset,
+                        max_chunk_size: UVec2::new(3, 3),
+                    },
+                )
+                .unwrap();
+                tilemap_builder
+                    .add_layer(
+                        TilemapLayer::new_sparse_from_hashmap(7, 6, walls),
+                        MapLayers::Walls,
+                    )
+                    .unwrap();
+                let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+                system_state.apply(&mut world);
+                let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+                tilemap_manager.set_tilemap_entity(map_entity);
+
+                // Without the walls the path is as long as the distance between the hexes
+                let path = tilemap_manager
+                    .find_path(
+                        start,
+                        goal,
+                        Connectivity::Edges,
+                        &[MapLayers::Terrain],
+                        |_, tiles| tiles[0].copied(),
+                    )
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(
+                    path.cost,
+                    cell_to_hex(start).unsigned_distance_to(cell_to_hex(goal))
+                );
+                assert_connected(&map_data, &path.cells, Connectivity::Edges);
+
+                let path = tilemap_manager
+                    .find_path(
+                        start,
+                        goal,
+                        Connectivity::Edges,
+                        &[MapLayers::Terrain, MapLayers::Walls],
+                        terrain_cost,
+                    )
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(path.cells.first(), Some(&start));
+                assert_eq!(path.cells.last(), Some(&goal));
+                assert!(path
+                    .cells
+                    .contains(&map_data.offset_coordinates_to_cell([3, 5])));
+                assert_connected(&map_data, &path.cells, Connectivity::Edges);
+            }
+        }
+    }
+}
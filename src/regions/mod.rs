@@ -0,0 +1,436 @@
+use crate::map::chunk::ChunkLayer;
+use crate::map::{Connectivity, MapData, MapLayer};
+use crate::tilemap_manager::{TileReader, TilemapManager, TilemapManagerError};
+use bevy::utils::{HashMap, HashSet};
+use lettuces::cell::Cell;
+
+/// The connected regions of a layer found by [`TilemapManager::label_regions`]. Every [`Cell`] with tile data in the
+/// layer is in exactly one region and regions are numbered from 0 in the order they were found
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Regions {
+    labels: HashMap<Cell, u32>,
+    sizes: Vec<usize>,
+}
+
+impl Regions {
+    /// Returns the region the [`Cell`] is in or `None` if the cell had no tile data
+    pub fn region(&self, cell: Cell) -> Option<u32> {
+        self.labels.get(&cell).copied()
+    }
+
+    /// Returns the number of regions
+    pub fn region_count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Returns the number of cells in the region, or 0 if the region doesn't exist
+    pub fn region_size(&self, region: u32) -> usize {
+        self.sizes.get(region as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns true if both [`Cell`]s are in the same region
+    pub fn is_connected(&self, from: Cell, to: Cell) -> bool {
+        self.region(from)
+            .is_some_and(|region| self.region(to) == Some(region))
+    }
+
+    /// Returns an iterator over the [`Cell`]s of the region
+    pub fn cells(&self, region: u32) -> impl Iterator<Item = Cell> + '_ {
+        self.iter()
+            .filter(move |(_, cell_region)| *cell_region == region)
+            .map(|(cell, _)| cell)
+    }
+
+    /// Returns an iterator over every labelled [`Cell`] along with its region
+    pub fn iter(&self) -> impl Iterator<Item = (Cell, u32)> + '_ {
+        self.labels.iter().map(|(cell, region)| (*cell, *region))
+    }
+
+    /// Returns the region of every labelled [`Cell`]
+    pub fn labels(&self) -> &HashMap<Cell, u32> {
+        &self.labels
+    }
+}
+
+impl<'w, 's, TileData, MapLayers, MapChunk, Map>
+    TilemapManager<'w, 's, TileData, MapLayers, MapChunk, Map>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + Default + Clone + Copy + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    /// Returns every [`Cell`] in the current layer that can be reached from the start cell by moving between the
+    /// [`neighbours`](MapData::neighbours) with the given [`Connectivity`] whose tile data `matches` returns true
+    /// for, including the start cell.
+    ///
+    /// Square maps fill 4-way with [`Connectivity::Edges`] and 8-way with [`Connectivity::Vertices`], hexagon maps
+    /// always fill 6-way. Tiles are read straight from the chunks, so each chunk is only looked up once.
+    ///
+    /// # Note
+    /// - Returns no cells if the start cell has no tile data or it doesn't match
+    /// - Cells without tile data, outside of the map or in chunks that are not loaded are never filled
+    pub fn flood_fill(
+        &self,
+        start: Cell,
+        connectivity: Connectivity,
+        matches: impl Fn(&TileData) -> bool,
+    ) -> Result<HashSet<Cell>, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer().to_index();
+        if !tile_reader
+            .tile_data(map_layer, start)
+            .is_some_and(&matches)
+        {
+            return Ok(HashSet::new());
+        }
+        let mut filled = HashSet::new();
+        fill(
+            &mut tile_reader,
+            map_layer,
+            start,
+            connectivity,
+            &mut filled,
+            |_, tile_data| matches(tile_data),
+        );
+        Ok(filled)
+    }
+
+    /// Returns every [`Cell`] in the current layer that can be reached from the start cell through neighbours with
+    /// tile data equal to the tile data of the start cell. See [`flood_fill`](Self::flood_fill)
+    pub fn flood_fill_equal(
+        &self,
+        start: Cell,
+        connectivity: Connectivity,
+    ) -> Result<HashSet<Cell>, TilemapManagerError>
+    where
+        TileData: PartialEq,
+    {
+        let Some(start_tile) = self
+            .tile_reader()?
+            .tile_data(self.layer().to_index(), start)
+        else {
+            return Ok(HashSet::new());
+        };
+        self.flood_fill(start, connectivity, |tile_data| tile_data == start_tile)
+    }
+
+    /// Flood fills the current layer from the start cell like [`flood_fill`](Self::flood_fill) and replaces the
+    /// tile data of every filled [`Cell`] with a clone of the given tile data. Returns the filled cells
+    pub fn flood_fill_replace(
+        &mut self,
+        start: Cell,
+        connectivity: Connectivity,
+        matches: impl Fn(&TileData) -> bool,
+        tile_data: TileData,
+    ) -> Result<HashSet<Cell>, TilemapManagerError> {
+        let filled = self.flood_fill(start, connectivity, matches)?;
+        self.set_tiles(filled.iter().copied(), tile_data)?;
+        Ok(filled)
+    }
+
+    /// Splits every [`Cell`] with tile data in the current layer into connected [`Regions`]. Two
+    /// [`neighbours`](MapData::neighbours) with the given [`Connectivity`] are in the same region when `connected`
+    /// returns true for their tile data.
+    ///
+    /// Use it to find rooms, lakes or islands ahead of time and check whether two cells can reach each other with
+    /// [`Regions::is_connected`]. Write the regions into a layer with [`write_regions`](Self::write_regions).
+    ///
+    /// # Note
+    /// - `connected` should behave like an equality, regions follow the order the cells are visited in otherwise
+    /// - Only the loaded chunks are labelled and cells in sparse layers without tile data are skipped
+    pub fn label_regions(
+        &self,
+        connectivity: Connectivity,
+        connected: impl Fn(&TileData, &TileData) -> bool,
+    ) -> Result<Regions, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer().to_index();
+        let mut labelled = HashSet::new();
+        let mut regions = Regions::default();
+        for (cell, _) in self.iter_layer()? {
+            if labelled.contains(&cell) {
+                continue;
+            }
+            let region = regions.sizes.len() as u32;
+            let cells = fill(
+                &mut tile_reader,
+                map_layer,
+                cell,
+                connectivity,
+                &mut labelled,
+                &connected,
+            );
+            regions.sizes.push(cells.len());
+            regions
+                .labels
+                .extend(cells.into_iter().map(|cell| (cell, region)));
+        }
+        Ok(regions)
+    }
+
+    /// Sets the tile data of every labelled [`Cell`] in the given [`MapLayer`] to the tile data returned for its
+    /// region. The current layer is left unchanged.
+    ///
+    /// To write the regions into a layer with a different `TileData` type, call this on a manager for that type.
+    pub fn write_regions(
+        &mut self,
+        regions: &Regions,
+        map_layer: MapLayers,
+        mut tile_data: impl FnMut(u32) -> TileData,
+    ) -> Result<(), TilemapManagerError> {
+        let current_layer = self.layer();
+        self.set_layer(map_layer);
+        let result = self.set_tiles_with(regions.labels.keys().copied(), |cell| {
+            tile_data(regions.labels[&cell])
+        });
+        self.set_layer(current_layer);
+        result
+    }
+}
+
+/// Adds the start [`Cell`] and every cell reachable from it to `visited` and returns them, moving from a cell to each
+/// unvisited neighbour with tile data that `connected` returns true for. `connected` is called with the tile data of
+/// the cell being left and the neighbour
+fn fill<'a, TileData, MapChunk, Map>(
+    tile_reader: &mut TileReader<'a, TileData, MapChunk, Map>,
+    map_layer: usize,
+    start: Cell,
+    connectivity: Connectivity,
+    visited: &mut HashSet<Cell>,
+    connected: impl Fn(&TileData, &TileData) -> bool,
+) -> Vec<Cell>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    let Some(start_tile) = tile_reader.tile_data(map_layer, start) else {
+        return vec![];
+    };
+    visited.insert(start);
+    let mut cells = vec![start];
+    let mut open = vec![(start, start_tile)];
+    while let Some((cell, tile_data)) = open.pop() {
+        for neighbour in tile_reader.map.neighbours(cell, connectivity) {
+            if visited.contains(&neighbour) {
+                continue;
+            }
+            let Some(neighbour_tile) = tile_reader.tile_data(map_layer, neighbour) else {
+                continue;
+            };
+            if connected(tile_data, neighbour_tile) {
+                visited.insert(neighbour);
+                cells.push(neighbour);
+                open.push((neighbour, neighbour_tile));
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::geometry;
+    use crate::hex::map_chunk_layer::HexagonChunkSettings;
+    use crate::hex::map_data::HexMapData;
+    use crate::hex::{HexMapShape, HexOffset, HexTilemapBuilder, HexTilemapManager};
+    use crate::map::{Connectivity, MapData};
+    use crate::square::map_chunk_layer::SquareChunkSettings;
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::{Entity, World};
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+    use lettuces::HexOrientation;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Terrain,
+        Regions,
+    }
+
+    /// Spawns a square map with chunks of 3x3 where 0 is floor and 1 is wall. Cut by walls into two rooms at the top
+    /// and two at the bottom, the top right room only touches the bottom rooms diagonally through (3, 2)
+    fn spawn_square_map() -> (
+        World,
+        SystemState<(
+            Commands<'static, 'static>,
+            SquareTilemapManager<'static, 'static, u32, MapLayers>,
+        )>,
+        Entity,
+    ) {
+        let terrain = vec![
+            vec![0, 0, 1, 0, 0, 0, 0],
+            vec![0, 0, 1, 0, 0, 0, 0],
+            vec![1, 1, 1, 0, 1, 1, 1],
+            vec![0, 0, 0, 1, 0, 0, 0],
+            vec![0, 0, 0, 1, 0, 0, 0],
+        ];
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<u32, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let mut tilemap_builder = SquareTilemapBuilder::<u32, MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(terrain),
+            SquareMapData {
+                max_chunk_size: UVec2::new(3, 3),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(3, 3),
+            },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(TilemapLayer::new_dense_default(7, 5), MapLayers::Regions)
+            .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+        (world, system_state, map_entity)
+    }
+
+    #[test]
+    fn square_flood_fill() {
+        let (mut world, mut system_state, map_entity) = spawn_square_map();
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let floor = |tile_data: &u32| *tile_data == 0;
+
+        let room = tilemap_manager
+            .flood_fill(Cell::new(0, 0), Connectivity::Edges, floor)
+            .unwrap();
+        assert_eq!(room.len(), 4);
+        assert!(room.contains(&Cell::new(1, 1)));
+        assert_eq!(
+            tilemap_manager
+                .flood_fill_equal(Cell::new(6, 4), Connectivity::Edges)
+                .unwrap()
+                .len(),
+            6
+        );
+        // Moving diagonally joins the top right room with both bottom rooms
+        assert_eq!(
+            tilemap_manager
+                .flood_fill_equal(Cell::new(6, 4), Connectivity::Vertices)
+                .unwrap()
+                .len(),
+            21
+        );
+        assert!(tilemap_manager
+            .flood_fill(Cell::new(2, 0), Connectivity::Edges, floor)
+            .unwrap()
+            .is_empty());
+        assert!(tilemap_manager
+            .flood_fill(Cell::new(-1, 0), Connectivity::Edges, floor)
+            .unwrap()
+            .is_empty());
+
+        let filled = tilemap_manager
+            .flood_fill_replace(Cell::new(0, 0), Connectivity::Edges, floor, 2)
+            .unwrap();
+        assert_eq!(filled, room);
+        assert_eq!(tilemap_manager.get_tile_data(Cell::new(1, 1)).unwrap(), &2);
+        assert_eq!(tilemap_manager.get_tile_data(Cell::new(3, 0)).unwrap(), &0);
+    }
+
+    #[test]
+    fn square_region_labels() {
+        let (mut world, mut system_state, map_entity) = spawn_square_map();
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        let regions = tilemap_manager
+            .label_regions(Connectivity::Edges, |a, b| a == b)
+            .unwrap();
+        // Four rooms and three walls
+        assert_eq!(regions.region_count(), 7);
+        assert_eq!(regions.labels().len(), 35);
+        assert!(regions.is_connected(Cell::new(0, 0), Cell::new(1, 1)));
+        assert!(!regions.is_connected(Cell::new(0, 0), Cell::new(6, 4)));
+        assert!(!regions.is_connected(Cell::new(3, 2), Cell::new(4, 3)));
+        let region = regions.region(Cell::new(3, 2)).unwrap();
+        assert_eq!(regions.region_size(region), 9);
+        assert_eq!(regions.cells(region).count(), 9);
+        assert_eq!(regions.region(Cell::new(7, 0)), None);
+
+        let regions = tilemap_manager
+            .label_regions(Connectivity::Vertices, |a, b| a == b)
+            .unwrap();
+        assert_eq!(regions.region_count(), 3);
+        assert!(regions.is_connected(Cell::new(3, 2), Cell::new(4, 3)));
+        assert!(regions.is_connected(Cell::new(0, 2), Cell::new(6, 2)));
+
+        tilemap_manager
+            .write_regions(&regions, MapLayers::Regions, |region| region + 10)
+            .unwrap();
+        assert_eq!(tilemap_manager.layer(), MapLayers::Terrain);
+        assert_eq!(tilemap_manager.get_tile_data(Cell::new(6, 4)).unwrap(), &0);
+        tilemap_manager.set_layer(MapLayers::Regions);
+        for (cell, region) in regions.iter() {
+            assert_eq!(tilemap_manager.get_tile_data(cell).unwrap(), &(region + 10));
+        }
+    }
+
+    #[test]
+    fn hex_flood_fill() {
+        let map_data = HexMapData {
+            max_chunk_size: UVec2::new(3, 3),
+            orientation: HexOrientation::Pointy,
+            offset: HexOffset::Odd,
+            shape: HexMapShape::Rectangle,
+        };
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, HexTilemapManager<u32, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let map_entity = HexTilemapBuilder::<u32, MapLayers>::new(
+            TilemapLayer::new_dense_default(9, 9),
+            HexMapData {
+                max_chunk_size: UVec2::new(3, 3),
+                orientation: HexOrientation::Pointy,
+                offset: HexOffset::Odd,
+                shape: HexMapShape::Rectangle,
+            },
+            HexagonChunkSettings {
+                orientation: HexOrientation::Pointy,
+                offset: HexOffset::Odd,
+                max_chunk_size: UVec2::new(3, 3),
+            },
+        )
+        .unwrap()
+        .spawn_tilemap(&mut commands)
+        .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        // A ring of land around a lake of 7 hexes
+        let center = map_data.offset_coordinates_to_cell([4, 4]);
+        tilemap_manager
+            .set_tiles(geometry::hex_ring(center, 2), 1)
+            .unwrap();
+
+        for connectivity in [Connectivity::Edges, Connectivity::Vertices] {
+            let lake = tilemap_manager
+                .flood_fill_equal(center, connectivity)
+                .unwrap();
+            assert_eq!(lake.len(), 7);
+            assert!(lake
+                .iter()
+                .all(|cell| map_data.distance(center, *cell, connectivity) <= 1));
+        }
+
+        let regions = tilemap_manager
+            .label_regions(Connectivity::Edges, |a, b| a == b)
+            .unwrap();
+        // The lake, the ring and the sea around it
+        assert_eq!(regions.region_count(), 3);
+        assert_eq!(regions.region_size(regions.region(center).unwrap()), 7);
+        assert!(!regions.is_connected(center, map_data.offset_coordinates_to_cell([0, 0])));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::geometry::line;
+use crate::map::MapLayer;
+use crate::square::SquareTilemapManager;
+use crate::tilemap_manager::TilemapManagerError;
+use bevy::utils::HashSet;
+use lettuces::cell::Cell;
+
+/// The `[xx, xy, yx, yy]` multipliers that turn the cells of the first octant into the cells of each octant
+const OCTANTS: [[i32; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+/// Field of view and line of sight for square maps. Sight is blocked by the tiles in the current layer that the
+/// `blocks_sight` predicate returns true for and by the edges of the map
+impl<'w, 's, TileData, MapLayers> SquareTilemapManager<'w, 's, TileData, MapLayers>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + Default + Clone + Copy + Send + Sync + 'static,
+{
+    /// Returns every [`Cell`] that can be seen from the origin within the given radius using recursive
+    /// shadowcasting, including the origin and the tiles that block sight.
+    ///
+    /// Tiles are read straight from the chunks, so each chunk is only looked up once.
+    ///
+    /// # Note
+    /// - The radius is a circle, cells are within it when their euclidean distance to the origin is at most the radius
+    /// - Cells without tile data don't block sight
+    pub fn field_of_view(
+        &self,
+        origin: Cell,
+        radius: u32,
+        blocks_sight: impl Fn(&TileData) -> bool,
+    ) -> Result<HashSet<Cell>, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer().to_index();
+        let mut shadowcaster = Shadowcaster {
+            origin,
+            radius: radius as i32,
+            sight: |cell: Cell| {
+                tile_reader.contains(cell).then(|| {
+                    tile_reader
+                        .tile_data(map_layer, cell)
+                        .is_some_and(&blocks_sight)
+                })
+            },
+            visible: HashSet::new(),
+        };
+        if (shadowcaster.sight)(origin).is_none() {
+            return Ok(HashSet::new());
+        }
+        shadowcaster.visible.insert(origin);
+        for octant in OCTANTS {
+            shadowcaster.cast_light(1, 1.0, 0.0, octant);
+        }
+        Ok(shadowcaster.visible)
+    }
+
+    /// Returns true if no tile that blocks sight is on the line between the two [`Cell`]s. The cells themselves
+    /// can block sight, only the cells between them are checked.
+    ///
+    /// # Note
+    /// - Returns false if either cell is outside of the map
+    /// - The line is a bresenham line and can disagree with [`field_of_view`](Self::field_of_view) around corners
+    pub fn has_line_of_sight(
+        &self,
+        from: Cell,
+        to: Cell,
+        blocks_sight: impl Fn(&TileData) -> bool,
+    ) -> Result<bool, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer().to_index();
+        if !tile_reader.contains(from) || !tile_reader.contains(to) {
+            return Ok(false);
+        }
+        let line: Vec<Cell> = line(from, to).collect();
+        Ok(line[1..line.len() - 1].iter().all(|cell| {
+            tile_reader.contains(*cell)
+                && !tile_reader
+                    .tile_data(map_layer, *cell)
+                    .is_some_and(&blocks_sight)
+        }))
+    }
+}
+
+/// The state of a recursive shadowcast. `sight` returns whether a cell blocks sight or `None` if it is outside of the
+/// map
+struct Shadowcaster<F: FnMut(Cell) -> Option<bool>> {
+    origin: Cell,
+    radius: i32,
+    sight: F,
+    visible: HashSet<Cell>,
+}
+
+impl<F: FnMut(Cell) -> Option<bool>> Shadowcaster<F> {
+    /// Lights the cells of one octant from the given row outwards between the start and end slopes, starting a new
+    /// scan past every run of cells that blocks sight
+    fn cast_light(&mut self, row: i32, mut start: f32, end: f32, octant: [i32; 4]) {
+        if start < end {
+            return;
+        }
+        let [xx, xy, yx, yy] = octant;
+        let mut new_start = 0.0;
+        for distance in row..=self.radius {
+            let mut blocked = false;
+            let dy = -distance;
+            for dx in -distance..=0 {
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+                let cell = Cell::new(
+                    self.origin.x + dx * xx + dy * xy,
+                    self.origin.y + dx * yx + dy * yy,
+                );
+                let sight = (self.sight)(cell);
+                if sight.is_some() && dx * dx + dy * dy <= self.radius * self.radius {
+                    self.visible.insert(cell);
+                }
+                // The edges of the map block sight
+                let blocks_sight = sight.unwrap_or(true);
+                if blocked {
+                    if blocks_sight {
+                        new_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = new_start;
+                    }
+                } else if blocks_sight && distance < self.radius {
+                    blocked = true;
+                    self.cast_light(distance + 1, start, left_slope, octant);
+                    new_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::square::map_chunk_layer::SquareChunkSettings;
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::World;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    #[test]
+    fn square_field_of_view() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<bool, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let map_entity = SquareTilemapBuilder::<bool, MapLayers>::new(
+            TilemapLayer::new_dense_default(11, 11),
+            SquareMapData {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        )
+        .unwrap()
+        .spawn_tilemap(&mut commands)
+        .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        // A wall two cells above the origin
+        tilemap_manager
+            .sets_tile_data(true, Cell::new(5, 7))
+            .unwrap();
+        let origin = Cell::new(5, 5);
+        let blocks_sight = |wall: &bool| *wall;
+
+        let visible = tilemap_manager
+            .field_of_view(origin, 4, blocks_sight)
+            .unwrap();
+        assert!(visible.contains(&origin));
+        assert!(visible.contains(&Cell::new(5, 7)));
+        assert!(visible.contains(&Cell::new(4, 7)));
+        assert!(visible.contains(&Cell::new(9, 5)));
+        assert!(visible.contains(&Cell::new(8, 7)));
+        // Behind the wall
+        assert!(!visible.contains(&Cell::new(5, 8)));
+        assert!(!visible.contains(&Cell::new(5, 9)));
+        // Outside of the radius
+        assert!(!visible.contains(&Cell::new(9, 9)));
+        assert!(visible
+            .iter()
+            .all(|cell| (cell.x - 5).pow(2) + (cell.y - 5).pow(2) <= 16));
+
+        // The edges of the map
+        let visible = tilemap_manager
+            .field_of_view(Cell::new(0, 0), 3, blocks_sight)
+            .unwrap();
+        assert!(visible.contains(&Cell::new(3, 0)));
+        assert!(visible.iter().all(|cell| cell.x >= 0 && cell.y >= 0));
+        assert!(tilemap_manager
+            .field_of_view(Cell::new(-1, 0), 3, blocks_sight)
+            .unwrap()
+            .is_empty());
+
+        assert!(tilemap_manager
+            .has_line_of_sight(origin, Cell::new(5, 7), blocks_sight)
+            .unwrap());
+        assert!(!tilemap_manager
+            .has_line_of_sight(origin, Cell::new(5, 9), blocks_sight)
+            .unwrap());
+        assert!(tilemap_manager
+            .has_line_of_sight(origin, Cell::new(7, 9), blocks_sight)
+            .unwrap());
+        assert!(!tilemap_manager
+            .has_line_of_sight(origin, Cell::new(5, 11), blocks_sight)
+            .unwrap());
+    }
+}
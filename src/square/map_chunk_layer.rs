@@ -1,8 +1,11 @@
-use crate::map::chunk::{ChunkCell, ChunkLayer, ChunkLayerType};
+use crate::map::chunk::{
+    ChunkCell, ChunkLayer, ChunkLayerStorage, ChunkLayerType, ConversionPolicy, PaletteStorage,
+    TileDataEq,
+};
 use bevy::ecs::entity::{EntityMapper, MapEntities};
 use bevy::math::UVec2;
 use bevy::prelude::{Component, Entity};
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use lettuces::storage::grid::Grid;
 use std::hash::{Hash, Hasher};
 
@@ -33,21 +36,62 @@ impl Default for SquareChunkSettings {
 }
 
 /// A struct that holds the chunk map data for the given layer
-#[derive(Clone, Component, Default)]
+#[derive(Clone, Component)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T: Deserialize<'de>")))]
 #[cfg_attr(feature = "reflect", derive(Reflect))]
-#[cfg_attr(feature = "reflect", reflect(Hash, MapEntities, Component))]
+#[cfg_attr(feature = "reflect", reflect(MapEntities, Component))]
 pub struct SquareChunkLayer<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     layer_type_data: SquareChunkLayerData<T>,
     tile_entities: HashMap<u64, Entity>,
+    default_tile_data: Option<T>,
+    conversion_policy: Option<ConversionPolicy>,
+    /// Whether the layer records its dirty cells, off unless enabled with `set_track_dirty_cells`
+    #[cfg_attr(feature = "serde", serde(default))]
+    track_dirty_cells: bool,
+    /// Compares tiles to the default tile data. Given along with the conversion policy or the palette
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    tile_data_eq: Option<TileDataEq<T>>,
+    /// The amount of non default tiles in a dense layer. `None` when it has to be recounted
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    filled_tiles: Option<usize>,
+    /// A tile handed out by `get_tile_data_mut` and whether it held data before, counted on the next change
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    unsettled_tile: Option<(ChunkCell, bool)>,
+    /// The cells whose data changed since the dirty cells were last cleared
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    dirty_cells: HashSet<u64>,
+}
+
+impl<T> Default for SquareChunkLayer<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    fn default() -> Self {
+        Self {
+            layer_type_data: Default::default(),
+            tile_entities: Default::default(),
+            default_tile_data: None,
+            conversion_policy: None,
+            track_dirty_cells: false,
+            tile_data_eq: None,
+            filled_tiles: None,
+            unsettled_tile: None,
+            dirty_cells: Default::default(),
+        }
+    }
 }
 
 impl<T> MapEntities for SquareChunkLayer<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
         for tile_entity in self.tile_entities.iter_mut() {
@@ -58,7 +102,7 @@ where
 
 impl<T> Hash for SquareChunkLayer<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Hash + Clone + Sized + Send + Sync,
 {
     fn hash<H: Hasher>(&self, h: &mut H) {
         let mut pairs: Vec<_> = self.tile_entities.iter().collect();
@@ -67,9 +111,102 @@ where
         Hash::hash(&self.layer_type_data, h);
     }
 }
+impl<T> SquareChunkLayer<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    /// Records the cell as dirty if the layer tracks its dirty cells
+    fn mark_dirty(&mut self, chunk_cell: ChunkCell) {
+        if self.track_dirty_cells {
+            self.dirty_cells.insert(chunk_cell.as_u64());
+        }
+    }
+
+    /// Returns whether the tile holds data, which it does unless it is equal to the default tile data of the layer.
+    /// `None` if the layer has no default tile data or no way to compare tiles
+    fn holds_data(&self, tile_data: &T) -> Option<bool> {
+        let (Some(default_tile_data), Some(tile_data_eq)) =
+            (&self.default_tile_data, self.tile_data_eq)
+        else {
+            return None;
+        };
+        Some(!tile_data_eq(tile_data, default_tile_data))
+    }
+
+    /// Counts the change made to the tile last handed out by `get_tile_data_mut`, if the filled tiles are tracked
+    fn settle_filled_tiles(&mut self) {
+        let Some((chunk_tile_pos, was_filled)) = self.unsettled_tile.take() else {
+            return;
+        };
+        let (Some(filled_tiles), Some(holds_data)) = (
+            self.filled_tiles,
+            self.layer_type_data
+                .get_tile_data(chunk_tile_pos)
+                .and_then(|tile_data| self.holds_data(tile_data)),
+        ) else {
+            return;
+        };
+        self.filled_tiles = Some(filled_tiles + usize::from(holds_data) - usize::from(was_filled));
+    }
+
+    /// Keeps the filled tile count of a dense layer up to date with a tile that is about to be set
+    fn track_filled_tiles(&mut self, chunk_tile_pos: ChunkCell, holds_data: bool) {
+        self.settle_filled_tiles();
+        let (Some(filled_tiles), SquareChunkLayerData::Dense(_)) =
+            (self.filled_tiles, &self.layer_type_data)
+        else {
+            return;
+        };
+        let Some(held_data) = self
+            .layer_type_data
+            .get_tile_data(chunk_tile_pos)
+            .and_then(|tile_data| self.holds_data(tile_data))
+        else {
+            return;
+        };
+        self.filled_tiles = Some(filled_tiles + usize::from(holds_data) - usize::from(held_data));
+    }
+
+    /// Converts the layer between sparse and dense storage if its [`ConversionPolicy`] says it should be
+    fn apply_conversion_policy(&mut self) {
+        self.settle_filled_tiles();
+        let (Some(conversion_policy), Some(default_tile_data), Some(tile_data_eq)) = (
+            self.conversion_policy,
+            &self.default_tile_data,
+            self.tile_data_eq,
+        ) else {
+            return;
+        };
+        let holds_data = |tile_data: &T| !tile_data_eq(tile_data, default_tile_data);
+        let dimensions = self.layer_type_data.get_dimensions();
+        let total_tiles = (dimensions.x * dimensions.y) as usize;
+        match &self.layer_type_data {
+            SquareChunkLayerData::Sparse(layer_data, ..) => {
+                if conversion_policy.should_convert_to_dense(layer_data.len(), total_tiles) {
+                    self.layer_type_data =
+                        std::mem::take(&mut self.layer_type_data).into_dense(default_tile_data);
+                    self.filled_tiles = None;
+                }
+            }
+            SquareChunkLayerData::Dense(_) => {
+                let filled_tiles = *self
+                    .filled_tiles
+                    .get_or_insert_with(|| self.layer_type_data.filled_tiles(holds_data));
+                if conversion_policy.should_convert_to_sparse(filled_tiles, total_tiles) {
+                    self.layer_type_data =
+                        std::mem::take(&mut self.layer_type_data).into_sparse(holds_data);
+                    self.filled_tiles = None;
+                }
+            }
+            // Palette layers are already compact so they are left as they are
+            SquareChunkLayerData::Palette(_) => {}
+        }
+    }
+}
+
 impl<T> ChunkLayer<T> for SquareChunkLayer<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     type ChunkSettings = SquareChunkSettings;
 
@@ -77,14 +214,16 @@ where
         cell: lettuces::cell::Cell,
         chunk_settings: &Self::ChunkSettings,
     ) -> ChunkCell {
-        let chunk_pos_x = cell.x / chunk_settings.max_chunk_size.x as i32;
-        let chunk_pos_y = cell.y / chunk_settings.max_chunk_size.y as i32;
         ChunkCell::new(
-            cell.x - (chunk_pos_x * chunk_settings.max_chunk_size.x as i32),
-            cell.y - (chunk_pos_y * chunk_settings.max_chunk_size.y as i32),
+            cell.x.rem_euclid(chunk_settings.max_chunk_size.x as i32),
+            cell.y.rem_euclid(chunk_settings.max_chunk_size.y as i32),
         )
     }
 
+    fn with_max_chunk_size(_: Self::ChunkSettings, max_chunk_size: UVec2) -> Self::ChunkSettings {
+        Self::ChunkSettings { max_chunk_size }
+    }
+
     fn new(
         layer_type: ChunkLayerType<T>,
         chunk_dimensions: UVec2,
@@ -93,22 +232,29 @@ where
         match layer_type {
             ChunkLayerType::Dense(dense_data) => Self {
                 layer_type_data: SquareChunkLayerData::new_dense_from_vecs(&dense_data),
-                tile_entities: Default::default(),
+                ..Default::default()
             },
             ChunkLayerType::Sparse(hashmap) => {
                 let sparse_data = hashmap
                     .iter()
                     .map(|(chunk_tile_pos, tile_data)| {
-                        let number =
-                            ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+                        let number = chunk_tile_pos.as_u64();
                         (number, tile_data.clone())
                     })
                     .collect();
                 SquareChunkLayer {
                     layer_type_data: SquareChunkLayerData::Sparse(sparse_data, chunk_dimensions),
-                    tile_entities: Default::default(),
+                    ..Default::default()
                 }
             }
+            ChunkLayerType::Palette(palette_data, tile_data_eq) => Self {
+                layer_type_data: SquareChunkLayerData::Palette(PaletteStorage::new_from_vecs_with(
+                    &palette_data,
+                    tile_data_eq,
+                )),
+                tile_data_eq: Some(tile_data_eq),
+                ..Default::default()
+            },
         }
     }
 
@@ -116,7 +262,27 @@ where
         self.layer_type_data.get_dimensions()
     }
 
+    fn storage(&self) -> ChunkLayerStorage {
+        match self.layer_type_data {
+            SquareChunkLayerData::Sparse(..) => ChunkLayerStorage::Sparse,
+            SquareChunkLayerData::Dense(_) => ChunkLayerStorage::Dense,
+            SquareChunkLayerData::Palette(_) => ChunkLayerStorage::Palette,
+        }
+    }
+
     fn get_tile_data_mut(&mut self, chunk_tile_pos: ChunkCell) -> Option<&mut T> {
+        // Changes made through the reference are counted on the next change to the layer
+        self.settle_filled_tiles();
+        let was_filled = self
+            .layer_type_data
+            .get_tile_data(chunk_tile_pos)
+            .map(|tile_data| self.holds_data(tile_data).unwrap_or(true))?;
+        if self.filled_tiles.is_some()
+            && matches!(self.layer_type_data, SquareChunkLayerData::Dense(_))
+        {
+            self.unsettled_tile = Some((chunk_tile_pos, was_filled));
+        }
+        self.mark_dirty(chunk_tile_pos);
         self.layer_type_data.get_tile_data_mut(chunk_tile_pos)
     }
 
@@ -125,29 +291,144 @@ where
     }
 
     fn set_tile_data(&mut self, chunk_tile_pos: ChunkCell, tile_data: T) {
+        let holds_data = self.holds_data(&tile_data).unwrap_or(true);
+        self.track_filled_tiles(chunk_tile_pos, holds_data);
+        if self
+            .layer_type_data
+            .set_tile_data(chunk_tile_pos, tile_data)
+        {
+            self.mark_dirty(chunk_tile_pos);
+        }
+        self.apply_conversion_policy();
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (ChunkCell, &T)> + '_> {
+        self.layer_type_data.iter()
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(ChunkCell, &mut T)) {
+        // Changes made through the references can't be tracked so recount on the next set
+        self.filled_tiles = None;
+        let mut dirty_cells = self.track_dirty_cells.then_some(&mut self.dirty_cells);
         self.layer_type_data
-            .set_tile_data(chunk_tile_pos, tile_data);
+            .for_each_mut(&mut |chunk_cell, tile_data| {
+                if let Some(dirty_cells) = dirty_cells.as_mut() {
+                    dirty_cells.insert(chunk_cell.as_u64());
+                }
+                f(chunk_cell, tile_data);
+            });
+    }
+
+    fn take_tile_data(&mut self, chunk_tile_pos: ChunkCell) -> Option<T> {
+        self.track_filled_tiles(chunk_tile_pos, false);
+        let tile_data = self
+            .layer_type_data
+            .take_tile_data(chunk_tile_pos, self.default_tile_data.as_ref());
+        if tile_data.is_some() {
+            self.mark_dirty(chunk_tile_pos);
+        }
+        self.apply_conversion_policy();
+        tile_data
+    }
+
+    fn clear_tile_data(&mut self) {
+        let default_tile_data = self.default_tile_data.as_ref();
+        if self.track_dirty_cells
+            && (default_tile_data.is_some()
+                || matches!(self.layer_type_data, SquareChunkLayerData::Sparse(..)))
+        {
+            for (chunk_cell, tile_data) in self.layer_type_data.iter() {
+                if self.holds_data(tile_data).unwrap_or(true) {
+                    self.dirty_cells.insert(chunk_cell.as_u64());
+                }
+            }
+        }
+        self.layer_type_data.clear(default_tile_data);
+        self.filled_tiles = None;
+        self.apply_conversion_policy();
+    }
+
+    fn default_tile_data(&self) -> Option<&T> {
+        self.default_tile_data.as_ref()
+    }
+
+    fn set_default_tile_data(&mut self, tile_data: Option<T>) {
+        self.default_tile_data = tile_data;
+        self.filled_tiles = None;
+    }
+
+    fn dirty_cells(&self) -> Box<dyn Iterator<Item = ChunkCell> + '_> {
+        Box::new(
+            self.dirty_cells
+                .iter()
+                .map(|number| ChunkCell::from_u64(*number)),
+        )
+    }
+
+    fn clear_dirty_cells(&mut self) {
+        self.dirty_cells.clear();
+    }
+
+    fn set_track_dirty_cells(&mut self, track_dirty_cells: bool) {
+        self.track_dirty_cells = track_dirty_cells;
+        if !track_dirty_cells {
+            self.dirty_cells = Default::default();
+        }
+    }
+
+    fn tracks_dirty_cells(&self) -> bool {
+        self.track_dirty_cells
     }
 
     fn get_tile_entity(&self, chunk_tile_pos: ChunkCell) -> Option<Entity> {
-        let number = ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+        let number = chunk_tile_pos.as_u64();
         self.tile_entities.get(&number).cloned()
     }
 
     fn set_tile_entity(&mut self, chunk_tile_pos: ChunkCell, entity: Entity) {
-        let number = ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+        let number = chunk_tile_pos.as_u64();
         self.tile_entities.insert(number, entity);
     }
+
+    fn remove_tile_entity(&mut self, chunk_tile_pos: ChunkCell) -> Option<Entity> {
+        let number = chunk_tile_pos.as_u64();
+        self.tile_entities.remove(&number)
+    }
+
+    fn iter_tile_entities(&self) -> Box<dyn Iterator<Item = (ChunkCell, Entity)> + '_> {
+        Box::new(
+            self.tile_entities
+                .iter()
+                .map(|(number, entity)| (ChunkCell::from_u64(*number), *entity)),
+        )
+    }
+
+    fn set_conversion_policy_with(
+        &mut self,
+        conversion_policy: Option<ConversionPolicy>,
+        tile_data_eq: TileDataEq<T>,
+    ) {
+        self.conversion_policy = conversion_policy;
+        self.tile_data_eq = Some(tile_data_eq);
+        self.filled_tiles = None;
+    }
+
+    fn conversion_policy(&self) -> Option<ConversionPolicy> {
+        self.conversion_policy
+    }
+
+    fn tile_data_eq(&self) -> Option<TileDataEq<T>> {
+        self.tile_data_eq
+    }
 }
 
 /// The data of a square chunk layer
 #[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "reflect", derive(Reflect))]
-#[cfg_attr(feature = "reflect", reflect(Hash))]
 pub enum SquareChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// A layer where ***NOT*** every position on the chunk has data
     ///
@@ -156,11 +437,14 @@ where
     Sparse(HashMap<u64, T>, UVec2),
     /// A layer where ***EVERY***  position on the chunk must have data
     Dense(Grid<T>),
+    /// A layer where every position on the chunk has data, stored as a palette of unique values and bit
+    /// packed indices into it
+    Palette(PaletteStorage<T>),
 }
 
 impl<T> Hash for SquareChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Hash + Clone + Sized + Send + Sync,
 {
     fn hash<H: Hasher>(&self, h: &mut H) {
         match self {
@@ -173,26 +457,32 @@ where
             SquareChunkLayerData::Dense(grid) => {
                 Hash::hash(grid, h);
             }
+            SquareChunkLayerData::Palette(palette) => {
+                Hash::hash(palette, h);
+            }
         }
     }
 }
 
 impl<T> Default for SquareChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     fn default() -> Self {
-        Self::Dense(Grid::<T>::new(0, 0))
+        Self::Dense(Grid::from_vec(vec![], 0))
     }
 }
 
 impl<T> SquareChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// Creates a new [`SquareChunkLayerData::Dense`] with all the tiles having the same data as the default
     /// for T
-    pub fn new_dense_default(chunk_size_x: usize, chunk_size_y: usize) -> Self {
+    pub fn new_dense_default(chunk_size_x: usize, chunk_size_y: usize) -> Self
+    where
+        T: Default,
+    {
         let grid: Grid<T> = Grid::new(chunk_size_x, chunk_size_y);
         Self::Dense(grid)
     }
@@ -205,7 +495,7 @@ where
     }
 
     /// Creates a new [`SquareChunkLayerData::Dense`]from the given vectors of vectors of T
-    pub fn new_dense_from_vecs(tile_data: &Vec<Vec<T>>) -> Self {
+    pub fn new_dense_from_vecs(tile_data: &[Vec<T>]) -> Self {
         let mut given_tile_count = 0u64;
 
         for tile_data in tile_data.iter() {
@@ -217,27 +507,13 @@ where
             given_tile_count
         );
 
-        let mut grid: Grid<T> = Grid::init(tile_data.len(), tile_data[0].len(), T::default());
-        let mut current_x = 0usize;
-        let mut current_y = 0usize;
-        let row_length = tile_data[0].len();
-        grid.fill_with(|| {
-            let tile = tile_data[current_y][current_x];
-            current_x += 1;
-            if current_x == row_length {
-                current_x = 0;
-                current_y += 1;
-            }
-            tile
-        });
-
-        Self::Dense(grid)
+        Self::Dense(Grid::from_vec(tile_data.concat(), tile_data[0].len()))
     }
 }
 
 impl<T> SquareChunkLayerData<T>
 where
-    T: Hash + Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// Returns the actual dimensions of the chunk
     pub fn get_dimensions(&self) -> UVec2 {
@@ -246,49 +522,518 @@ where
             SquareChunkLayerData::Dense(grid) => {
                 UVec2::new(grid.size().1 as u32, grid.size().0 as u32)
             }
+            SquareChunkLayerData::Palette(palette) => {
+                UVec2::new(palette.cols() as u32, palette.rows() as u32)
+            }
         }
     }
 
-    /// Sets the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk
-    pub fn set_tile_data(&mut self, chunk_tile_pos: ChunkCell, tile_data: T) {
+    /// Sets the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk,
+    /// returns whether the tile was set
+    pub fn set_tile_data(&mut self, chunk_tile_pos: ChunkCell, tile_data: T) -> bool {
         match self {
             SquareChunkLayerData::Sparse(layer_data, ..) => {
-                let number = ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+                let number = chunk_tile_pos.as_u64();
                 layer_data.insert(number, tile_data);
+                true
             }
             SquareChunkLayerData::Dense(layer_data) => {
-                if let Some(tile) =
+                let Some(tile) =
                     layer_data.get_mut(chunk_tile_pos.y() as usize, chunk_tile_pos.x() as usize)
-                {
-                    *tile = tile_data
+                else {
+                    return false;
                 };
+                *tile = tile_data;
+                true
             }
-        };
+            SquareChunkLayerData::Palette(layer_data) => {
+                let (row, column) = (chunk_tile_pos.y() as usize, chunk_tile_pos.x() as usize);
+                if layer_data.get(row, column).is_none() {
+                    return false;
+                }
+                layer_data.set(row, column, tile_data);
+                true
+            }
+        }
+    }
+
+    /// Removes the tile data at the given [`ChunkCell`] and returns it. Tiles in dense and palette layers are
+    /// reset to the given default tile data instead, and are not removed without one
+    pub fn take_tile_data(
+        &mut self,
+        chunk_tile_pos: ChunkCell,
+        default_tile_data: Option<&T>,
+    ) -> Option<T> {
+        match self {
+            SquareChunkLayerData::Sparse(layer_data, ..) => {
+                layer_data.remove(&chunk_tile_pos.as_u64())
+            }
+            SquareChunkLayerData::Dense(_) | SquareChunkLayerData::Palette(_) => {
+                let default_tile_data = default_tile_data?.clone();
+                let tile_data = self.get_tile_data(chunk_tile_pos).cloned()?;
+                self.set_tile_data(chunk_tile_pos, default_tile_data);
+                Some(tile_data)
+            }
+        }
+    }
+
+    /// Removes all the tile data in the layer. Tiles in dense and palette layers are reset to the given default
+    /// tile data instead, and are left untouched without one
+    pub fn clear(&mut self, default_tile_data: Option<&T>) {
+        match (self, default_tile_data) {
+            (SquareChunkLayerData::Sparse(layer_data, ..), _) => layer_data.clear(),
+            (SquareChunkLayerData::Dense(layer_data), Some(default_tile_data)) => {
+                layer_data.fill(default_tile_data.clone())
+            }
+            (SquareChunkLayerData::Palette(layer_data), Some(default_tile_data)) => {
+                layer_data.fill(default_tile_data.clone())
+            }
+            (_, None) => {}
+        }
     }
 
     /// Gets mutable access to the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk
     pub fn get_tile_data_mut(&mut self, chunk_tile_pos: ChunkCell) -> Option<&mut T> {
-        return match self {
+        match self {
             SquareChunkLayerData::Sparse(layer_data, ..) => {
-                let number = ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+                let number = chunk_tile_pos.as_u64();
                 layer_data.get_mut(&number)
             }
             SquareChunkLayerData::Dense(layer_data) => {
                 layer_data.get_mut(chunk_tile_pos.y() as usize, chunk_tile_pos.x() as usize)
             }
-        };
+            SquareChunkLayerData::Palette(layer_data) => {
+                layer_data.get_mut(chunk_tile_pos.y() as usize, chunk_tile_pos.x() as usize)
+            }
+        }
     }
 
     /// Gets immutable access to the tile data at the given [`ChunkCell`]. Can fail if the given cell is not a valid position in the chunk
     pub fn get_tile_data(&self, chunk_tile_pos: ChunkCell) -> Option<&T> {
-        return match self {
+        match self {
             SquareChunkLayerData::Sparse(layer_data, ..) => {
-                let number = ((chunk_tile_pos.x() as u64) << 32) | chunk_tile_pos.y() as u64;
+                let number = chunk_tile_pos.as_u64();
                 layer_data.get(&number)
             }
             SquareChunkLayerData::Dense(layer_data) => {
                 layer_data.get(chunk_tile_pos.y() as usize, chunk_tile_pos.x() as usize)
             }
+            SquareChunkLayerData::Palette(layer_data) => {
+                layer_data.get(chunk_tile_pos.y() as usize, chunk_tile_pos.x() as usize)
+            }
+        }
+    }
+}
+
+impl<T> SquareChunkLayerData<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    /// Returns the amount of tiles that hold data. Tiles in dense and palette layers only hold data if
+    /// `holds_data` returns true for them
+    pub fn filled_tiles(&self, holds_data: impl Fn(&T) -> bool) -> usize {
+        match self {
+            SquareChunkLayerData::Sparse(layer_data, ..) => layer_data.len(),
+            SquareChunkLayerData::Dense(layer_data) => {
+                layer_data.iter().filter(|tile| holds_data(tile)).count()
+            }
+            SquareChunkLayerData::Palette(layer_data) => {
+                layer_data.iter().filter(|tile| holds_data(tile)).count()
+            }
+        }
+    }
+
+    /// Returns an iterator over every tile that holds data along with its [`ChunkCell`]. Sparse layers only
+    /// visit occupied tiles
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (ChunkCell, &T)> + '_> {
+        let cols = self.get_dimensions().x as usize;
+        let index_to_cell =
+            move |index: usize| ChunkCell::new((index % cols) as i32, (index / cols) as i32);
+        match self {
+            SquareChunkLayerData::Sparse(layer_data, ..) => Box::new(
+                layer_data
+                    .iter()
+                    .map(|(number, tile_data)| (ChunkCell::from_u64(*number), tile_data)),
+            ),
+            SquareChunkLayerData::Dense(layer_data) => Box::new(
+                layer_data
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, tile_data)| (index_to_cell(index), tile_data)),
+            ),
+            SquareChunkLayerData::Palette(layer_data) => Box::new(
+                layer_data
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, tile_data)| (index_to_cell(index), tile_data)),
+            ),
+        }
+    }
+
+    /// Calls `f` with a mutable reference to every tile that holds data along with its [`ChunkCell`]. Palette
+    /// layers hand out a copy of each tile and only write the tiles that changed back into the palette
+    pub fn for_each_mut(&mut self, f: &mut dyn FnMut(ChunkCell, &mut T)) {
+        let cols = self.get_dimensions().x as usize;
+        match self {
+            SquareChunkLayerData::Sparse(layer_data, ..) => {
+                for (number, tile_data) in layer_data.iter_mut() {
+                    f(ChunkCell::from_u64(*number), tile_data);
+                }
+            }
+            SquareChunkLayerData::Dense(layer_data) => {
+                for (index, tile_data) in layer_data.iter_mut().enumerate() {
+                    f(
+                        ChunkCell::new((index % cols) as i32, (index / cols) as i32),
+                        tile_data,
+                    );
+                }
+            }
+            SquareChunkLayerData::Palette(layer_data) => {
+                for row in 0..layer_data.rows() {
+                    for col in 0..layer_data.cols() {
+                        let Some(mut tile_data) = layer_data.get(row, col).cloned() else {
+                            continue;
+                        };
+                        f(ChunkCell::new(col as i32, row as i32), &mut tile_data);
+                        // Unchanged tiles are left in their palette entry
+                        layer_data.set(row, col, tile_data);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts the layer into [`SquareChunkLayerData::Dense`]. Tiles without data are set to the given default
+    /// tile data
+    pub fn into_dense(self, default_tile_data: &T) -> Self {
+        match self {
+            SquareChunkLayerData::Sparse(layer_data, dimensions) => {
+                let mut grid: Grid<T> = Grid::init(
+                    dimensions.y as usize,
+                    dimensions.x as usize,
+                    default_tile_data.clone(),
+                );
+                for (number, tile_data) in layer_data {
+                    let chunk_tile_pos = ChunkCell::from_u64(number);
+                    if let Some(tile) =
+                        grid.get_mut(chunk_tile_pos.y() as usize, chunk_tile_pos.x() as usize)
+                    {
+                        *tile = tile_data;
+                    }
+                }
+                SquareChunkLayerData::Dense(grid)
+            }
+            SquareChunkLayerData::Palette(layer_data) => SquareChunkLayerData::Dense(
+                Grid::from_vec(layer_data.iter().cloned().collect(), layer_data.cols()),
+            ),
+            dense => dense,
+        }
+    }
+
+    /// Converts the layer into [`SquareChunkLayerData::Sparse`]. Tiles that `holds_data` returns false for are
+    /// dropped
+    pub fn into_sparse(self, holds_data: impl Fn(&T) -> bool) -> Self {
+        let dimensions = self.get_dimensions();
+        let tiles: Vec<T> = match self {
+            SquareChunkLayerData::Dense(layer_data) => layer_data.into_vec(),
+            SquareChunkLayerData::Palette(layer_data) => layer_data.iter().cloned().collect(),
+            sparse => return sparse,
         };
+        let sparse_data = tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile_data)| holds_data(tile_data))
+            .map(|(index, tile_data)| {
+                let chunk_tile_pos = ChunkCell::new(
+                    (index % dimensions.x as usize) as i32,
+                    (index / dimensions.x as usize) as i32,
+                );
+                (chunk_tile_pos.as_u64(), tile_data.clone())
+            })
+            .collect();
+        SquareChunkLayerData::Sparse(sparse_data, dimensions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::chunk::{ChunkCell, ChunkLayer, ChunkLayerType, ConversionPolicy};
+    use crate::square::map_chunk_layer::{
+        SquareChunkLayer, SquareChunkLayerData, SquareChunkSettings,
+    };
+    use bevy::math::UVec2;
+    use bevy::utils::HashMap;
+
+    fn new_sparse_layer() -> SquareChunkLayer<u32> {
+        let mut layer = SquareChunkLayer::new(
+            ChunkLayerType::Sparse(HashMap::new()),
+            UVec2::new(4, 4),
+            &SquareChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        );
+        layer.set_default_tile_data(Some(0));
+        layer.set_conversion_policy(Some(ConversionPolicy::new(0.75, 0.25)));
+        layer
+    }
+
+    #[test]
+    fn sparse_layer_converts_to_dense() {
+        let mut layer = new_sparse_layer();
+        for index in 0..12 {
+            layer.set_tile_data(ChunkCell::new(index % 4, index / 4), index as u32 + 1);
+        }
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Sparse(..)
+        ));
+        assert_eq!(layer.get_tile_data(ChunkCell::new(3, 3)), None);
+
+        layer.set_tile_data(ChunkCell::new(0, 3), 13);
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Dense(..)
+        ));
+        assert_eq!(layer.get_chunk_dimensions(), UVec2::new(4, 4));
+        for index in 0..13 {
+            assert_eq!(
+                layer.get_tile_data(ChunkCell::new(index % 4, index / 4)),
+                Some(&(index as u32 + 1))
+            );
+        }
+        assert_eq!(layer.get_tile_data(ChunkCell::new(3, 3)), Some(&0));
+    }
+
+    #[test]
+    fn dense_layer_converts_to_sparse() {
+        let mut layer = new_sparse_layer();
+        for index in 0..16 {
+            layer.set_tile_data(ChunkCell::new(index % 4, index / 4), index as u32 + 1);
+        }
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Dense(..)
+        ));
+
+        // Clearing tiles through a mutable reference must be picked up as well, without recounting the chunk
+        *layer.get_tile_data_mut(ChunkCell::new(0, 0)).unwrap() = 0;
+        *layer.get_tile_data_mut(ChunkCell::new(1, 0)).unwrap() = 0;
+        *layer.get_tile_data_mut(ChunkCell::new(1, 0)).unwrap() = 2;
+        assert!(layer.filled_tiles.is_some());
+        for index in 1..12 {
+            layer.set_tile_data(ChunkCell::new(index % 4, index / 4), 0);
+        }
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Dense(..)
+        ));
+
+        layer.set_tile_data(ChunkCell::new(0, 3), 0);
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Sparse(..)
+        ));
+        assert_eq!(layer.get_chunk_dimensions(), UVec2::new(4, 4));
+        assert_eq!(layer.get_tile_data(ChunkCell::new(0, 0)), None);
+        assert_eq!(layer.get_tile_data(ChunkCell::new(0, 3)), None);
+        for index in 13..16 {
+            assert_eq!(
+                layer.get_tile_data(ChunkCell::new(index % 4, index / 4)),
+                Some(&(index as u32 + 1))
+            );
+        }
+    }
+
+    #[test]
+    fn layer_without_policy_does_not_convert() {
+        let mut layer = new_sparse_layer();
+        layer.set_conversion_policy(None);
+        for index in 0..16 {
+            layer.set_tile_data(ChunkCell::new(index % 4, index / 4), 1);
+        }
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Sparse(..)
+        ));
+    }
+
+    #[test]
+    fn palette_layer_get_and_set() {
+        let mut layer: SquareChunkLayer<u32> = SquareChunkLayer::new(
+            ChunkLayerType::Palette(vec![vec![1, 1, 1, 2]; 4], u32::eq),
+            UVec2::new(4, 4),
+            &SquareChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        );
+        layer.set_conversion_policy(Some(ConversionPolicy::default()));
+        assert_eq!(layer.get_chunk_dimensions(), UVec2::new(4, 4));
+        assert_eq!(layer.get_tile_data(ChunkCell::new(3, 2)), Some(&2));
+        assert_eq!(layer.get_tile_data(ChunkCell::new(4, 0)), None);
+
+        for index in 0..16 {
+            layer.set_tile_data(ChunkCell::new(index % 4, index / 4), 0);
+        }
+        *layer.get_tile_data_mut(ChunkCell::new(1, 1)).unwrap() = 5;
+        // Palette layers are never converted
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Palette(..)
+        ));
+        assert_eq!(layer.get_tile_data(ChunkCell::new(1, 1)), Some(&5));
+        assert_eq!(layer.get_tile_data(ChunkCell::new(2, 1)), Some(&0));
+    }
+
+    #[test]
+    fn take_and_clear_tile_data() {
+        let mut layer = new_sparse_layer();
+        layer.set_conversion_policy(None);
+        layer.set_tile_data(ChunkCell::new(1, 2), 5);
+        assert_eq!(layer.take_tile_data(ChunkCell::new(1, 2)), Some(5));
+        assert_eq!(layer.take_tile_data(ChunkCell::new(1, 2)), None);
+        assert_eq!(layer.get_tile_data(ChunkCell::new(1, 2)), None);
+
+        for layer_type in [
+            ChunkLayerType::Dense(vec![vec![3; 4]; 4]),
+            ChunkLayerType::Palette(vec![vec![3; 4]; 4], u32::eq),
+        ] {
+            let mut layer: SquareChunkLayer<u32> = SquareChunkLayer::new(
+                layer_type,
+                UVec2::new(4, 4),
+                &SquareChunkSettings {
+                    max_chunk_size: UVec2::new(4, 4),
+                },
+            );
+            // Tiles are kept until the layer has default tile data to reset them to
+            assert_eq!(layer.take_tile_data(ChunkCell::new(1, 2)), None);
+            layer.clear_tile_data();
+            assert_eq!(layer.get_tile_data(ChunkCell::new(1, 2)), Some(&3));
+
+            layer.set_default_tile_data(Some(0));
+            assert_eq!(layer.take_tile_data(ChunkCell::new(1, 2)), Some(3));
+            assert_eq!(layer.get_tile_data(ChunkCell::new(1, 2)), Some(&0));
+            assert_eq!(layer.take_tile_data(ChunkCell::new(4, 0)), None);
+
+            layer.clear_tile_data();
+            assert_eq!(layer.get_chunk_dimensions(), UVec2::new(4, 4));
+            assert_eq!(layer.get_tile_data(ChunkCell::new(3, 3)), Some(&0));
+        }
+    }
+
+    #[test]
+    fn cleared_dense_layer_converts_to_sparse() {
+        let mut layer = new_sparse_layer();
+        for index in 0..16 {
+            layer.set_tile_data(ChunkCell::new(index % 4, index / 4), 1);
+        }
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Dense(..)
+        ));
+        layer.clear_tile_data();
+        assert!(matches!(
+            layer.layer_type_data,
+            SquareChunkLayerData::Sparse(..)
+        ));
+        assert_eq!(layer.get_tile_data(ChunkCell::new(0, 0)), None);
+    }
+
+    #[test]
+    fn iter_visits_tiles_with_data() {
+        let mut layer = new_sparse_layer();
+        layer.set_conversion_policy(None);
+        layer.set_tile_data(ChunkCell::new(1, 2), 5);
+        layer.set_tile_data(ChunkCell::new(3, 0), 6);
+        let mut tiles: Vec<(ChunkCell, u32)> = layer
+            .iter()
+            .map(|(chunk_cell, tile_data)| (chunk_cell, *tile_data))
+            .collect();
+        tiles.sort_by_key(|(chunk_cell, _)| (chunk_cell.y(), chunk_cell.x()));
+        assert_eq!(
+            tiles,
+            vec![(ChunkCell::new(3, 0), 6), (ChunkCell::new(1, 2), 5)]
+        );
+
+        for layer_type in [
+            ChunkLayerType::Dense(vec![vec![0, 1, 2], vec![3, 4, 5]]),
+            ChunkLayerType::Palette(vec![vec![0, 1, 2], vec![3, 4, 5]], u32::eq),
+        ] {
+            let mut layer: SquareChunkLayer<u32> = SquareChunkLayer::new(
+                layer_type,
+                UVec2::new(3, 2),
+                &SquareChunkSettings {
+                    max_chunk_size: UVec2::new(3, 2),
+                },
+            );
+            assert_eq!(layer.iter().count(), 6);
+            for (chunk_cell, tile_data) in layer.iter() {
+                assert_eq!(*tile_data as i32, chunk_cell.y() * 3 + chunk_cell.x());
+            }
+
+            let storage = layer.storage();
+            layer.for_each_mut(&mut |_, tile_data| *tile_data += 10);
+            assert_eq!(layer.get_tile_data(ChunkCell::new(2, 1)), Some(&15));
+            assert_eq!(layer.get_tile_data(ChunkCell::new(0, 0)), Some(&10));
+
+            // Every kind of layer visits all of its tiles and palette layers stay palette compressed
+            let mut visited = 0;
+            layer.for_each_mut(&mut |_, _| visited += 1);
+            assert_eq!(visited, 6);
+            assert_eq!(layer.storage(), storage);
+        }
+    }
+
+    #[test]
+    fn changed_tiles_are_dirty() {
+        let dirty_cells = |layer: &SquareChunkLayer<u32>| {
+            let mut dirty_cells: Vec<ChunkCell> = layer.dirty_cells().collect();
+            dirty_cells.sort_by_key(|chunk_cell| (chunk_cell.y(), chunk_cell.x()));
+            dirty_cells
+        };
+        let mut layer = new_sparse_layer();
+        layer.set_conversion_policy(None);
+        // Layers only record dirty cells once they track them
+        layer.set_tile_data(ChunkCell::new(0, 1), 4);
+        assert_eq!(dirty_cells(&layer), vec![]);
+        layer.set_track_dirty_cells(true);
+        layer.set_tile_data(ChunkCell::new(1, 2), 5);
+        layer.set_tile_data(ChunkCell::new(3, 3), 6);
+        assert_eq!(
+            dirty_cells(&layer),
+            vec![ChunkCell::new(1, 2), ChunkCell::new(3, 3)]
+        );
+
+        layer.clear_dirty_cells();
+        assert_eq!(layer.take_tile_data(ChunkCell::new(0, 0)), None);
+        assert!(layer.get_tile_data_mut(ChunkCell::new(0, 0)).is_none());
+        assert_eq!(dirty_cells(&layer), vec![]);
+        layer.take_tile_data(ChunkCell::new(1, 2));
+        assert_eq!(dirty_cells(&layer), vec![ChunkCell::new(1, 2)]);
+
+        layer.clear_dirty_cells();
+        layer.clear_tile_data();
+        assert_eq!(
+            dirty_cells(&layer),
+            vec![ChunkCell::new(0, 1), ChunkCell::new(3, 3)]
+        );
+
+        // Turning tracking off drops the recorded cells
+        layer.set_tile_data(ChunkCell::new(2, 2), 5);
+        layer.set_track_dirty_cells(false);
+        assert!(!layer.tracks_dirty_cells());
+        assert_eq!(dirty_cells(&layer), vec![]);
+
+        // Cells outside of a dense layer are never set so they can't be dirty
+        let mut layer: SquareChunkLayer<u32> = SquareChunkLayer::new(
+            ChunkLayerType::Dense(vec![vec![0; 4]; 4]),
+            UVec2::new(4, 4),
+            &SquareChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        );
+        layer.set_track_dirty_cells(true);
+        layer.set_tile_data(ChunkCell::new(4, 1), 5);
+        layer.set_tile_data(ChunkCell::new(2, 1), 5);
+        assert_eq!(dirty_cells(&layer), vec![ChunkCell::new(2, 1)]);
     }
 }
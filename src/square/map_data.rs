@@ -12,9 +12,11 @@ use bevy::prelude::{Reflect, ReflectComponent};
 use serde::{Deserialize, Serialize};
 
 use crate::map::{
-    chunk::{Chunk, ChunkLayerType, ChunkPos},
+    chunk::{Chunk, ChunkCell, ChunkLayerType, ChunkPos},
     MapData, MapLayer,
 };
+use crate::square::map_chunk_layer::SquareChunkSettings;
+use lettuces::cell::Cell;
 
 /// An implementation of [`MapData`] for a standard square map.
 #[derive(Default, Hash, Component)]
@@ -26,41 +28,68 @@ pub struct SquareMapData {
     pub max_chunk_size: UVec2,
 }
 
+impl From<&SquareMapData> for SquareChunkSettings {
+    fn from(map: &SquareMapData) -> Self {
+        Self {
+            max_chunk_size: map.max_chunk_size,
+        }
+    }
+}
+
 impl MapData for SquareMapData {
-    fn into_chunk_pos(&self, cell: lettuces::cell::Cell) -> ChunkPos {
+    fn into_chunk_pos(&self, cell: Cell) -> ChunkPos {
         ChunkPos::new(
-            cell.x / self.max_chunk_size.x as i32,
-            cell.y / self.max_chunk_size.y as i32,
+            cell.x.div_euclid(self.max_chunk_size.x as i32),
+            cell.y.div_euclid(self.max_chunk_size.y as i32),
         )
     }
 
+    fn into_cell(&self, chunk_pos: ChunkPos, chunk_cell: ChunkCell) -> Cell {
+        Cell::new(
+            chunk_pos.x() * self.max_chunk_size.x as i32 + chunk_cell.x(),
+            chunk_pos.y() * self.max_chunk_size.y as i32 + chunk_cell.y(),
+        )
+    }
+
+    fn cell_to_offset_coordinates(&self, cell: Cell) -> [i32; 2] {
+        [cell.x, cell.y]
+    }
+
+    fn offset_coordinates_to_cell(&self, offset_coordinates: [i32; 2]) -> Cell {
+        Cell::new(offset_coordinates[0], offset_coordinates[1])
+    }
+
     fn max_chunk_size(&self) -> UVec2 {
         self.max_chunk_size
     }
 
+    fn set_max_chunk_size(&mut self, max_chunk_size: UVec2) {
+        self.max_chunk_size = max_chunk_size;
+    }
+
     fn break_data_vecs_down_into_chunk_data<TileData>(
         &self,
-        data: &Vec<Vec<TileData>>,
+        data: &[Vec<TileData>],
         chunk_pos: ChunkPos,
         max_chunk_size: UVec2,
     ) -> Vec<Vec<TileData>>
     where
-        TileData: Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
     {
         let amount_of_x_tiles_done = (chunk_pos.x() * max_chunk_size.x as i32) as usize;
         let amount_of_y_tiles_done = (chunk_pos.y() * max_chunk_size.y as i32) as usize;
         let mut vec: Vec<Vec<TileData>> = vec![];
-        for y in amount_of_y_tiles_done..data.len() {
-            if y >= (amount_of_y_tiles_done + max_chunk_size.y as usize) {
-                continue;
-            }
-            let mut row_vec: Vec<TileData> = vec![];
-            for x in amount_of_x_tiles_done..data[0].len() {
-                if x >= (amount_of_x_tiles_done + max_chunk_size.x as usize) {
-                    continue;
-                }
-                row_vec.push(data[y][x]);
-            }
+        for row in data
+            .iter()
+            .skip(amount_of_y_tiles_done)
+            .take(max_chunk_size.y as usize)
+        {
+            let row_vec: Vec<TileData> = row
+                .iter()
+                .skip(amount_of_x_tiles_done)
+                .take(max_chunk_size.x as usize)
+                .cloned()
+                .collect();
             vec.push(row_vec);
         }
         vec
@@ -68,12 +97,13 @@ impl MapData for SquareMapData {
 
     fn break_data_vecs_into_chunks<TileData, MapChunk>(
         &self,
-        data: &Vec<Vec<TileData>>,
+        data: &[Vec<TileData>],
+        origin: ChunkPos,
         max_chunk_size: UVec2,
         chunk_settings: MapChunk::ChunkSettings,
     ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
     where
-        TileData: std::hash::Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
         MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
     {
         let mut chunks: Vec<Vec<Chunk<MapChunk, TileData>>> = vec![];
@@ -87,12 +117,12 @@ impl MapData for SquareMapData {
             let mut chunks_rows: Vec<Chunk<MapChunk, TileData>> = vec![];
             for x in 0..chunks_on_x {
                 let vec = self.break_data_vecs_down_into_chunk_data(
-                    &data,
+                    data,
                     ChunkPos::new(x, y),
                     max_chunk_size,
                 );
                 let chunk = Chunk::<MapChunk, TileData>::new(
-                    ChunkPos::new(x, y),
+                    ChunkPos::new(origin.x() + x, origin.y() + y),
                     UVec2::new(vec.len() as u32, vec[0].len() as u32),
                     ChunkLayerType::Dense(vec),
                     chunk_settings,
@@ -108,13 +138,14 @@ impl MapData for SquareMapData {
     fn break_hashmap_into_chunks<TileData, MapChunk>(
         &self,
         map_layer: impl MapLayer,
-        data: &bevy::utils::HashMap<lettuces::cell::Cell, TileData>,
+        data: &bevy::utils::HashMap<Cell, TileData>,
+        origin: ChunkPos,
         map_size: UVec2,
         max_chunk_size: UVec2,
         chunk_settings: MapChunk::ChunkSettings,
     ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
     where
-        TileData: std::hash::Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
         MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
     {
         let mut chunks: Vec<Vec<Chunk<MapChunk, TileData>>> = vec![];
@@ -144,7 +175,7 @@ impl MapData for SquareMapData {
                         ((max_chunks_floats.x - x as f32) * max_chunk_size.x as f32).ceil() as u32
                 };
                 chunks_rows.push(Chunk::new(
-                    ChunkPos::new(x, y),
+                    ChunkPos::new(origin.x() + x, origin.y() + y),
                     chunk_size,
                     ChunkLayerType::Sparse(HashMap::new()),
                     chunk_settings,
@@ -155,11 +186,12 @@ impl MapData for SquareMapData {
 
         for (cell, tile_data) in data.iter() {
             let chunk_pos = self.into_chunk_pos(*cell);
-            let chunk = &mut chunks[chunk_pos.y() as usize][chunk_pos.x() as usize];
+            let chunk = &mut chunks[(chunk_pos.y() - origin.y()) as usize]
+                [(chunk_pos.x() - origin.x()) as usize];
             chunk.set_tile_data(
-                map_layer.to_bits(),
+                map_layer.to_index(),
                 MapChunk::into_chunk_cell(*cell, &chunk.chunk_settings),
-                *tile_data,
+                tile_data.clone(),
             );
         }
 
@@ -170,6 +202,7 @@ impl MapData for SquareMapData {
 #[cfg(test)]
 mod tests {
     use crate as bevy_sparse_tilemap;
+    use crate::map::chunk::{ChunkCell, ChunkLayer};
     use crate::map::MapData;
     use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
     use crate::square::map_data::SquareMapData;
@@ -235,7 +268,7 @@ mod tests {
 
         assert_eq!(size, UVec2::new(32, 32));
 
-        assert_eq!(data.get(&Cell::new(1, 1)).is_none(), true);
+        assert!(data.get(&Cell::new(1, 1)).is_none());
         assert_eq!(data.get(&Cell::new(0, 0)).unwrap(), &(0, 0));
         assert_eq!(data.get(&Cell::new(31, 31)).unwrap(), &(31, 31));
     }
@@ -342,6 +375,7 @@ mod tests {
             map_data.break_hashmap_into_chunks(
                 MapLayers::Main,
                 &hashmap,
+                ChunkPos::new(0, 0),
                 UVec2::new(32, 32),
                 mcs,
                 chunk_settings,
@@ -354,25 +388,136 @@ mod tests {
             chunks[0][0]
                 .get_tile_data_from_cell(MapLayers::Main, Cell::new(0, 0))
                 .unwrap(),
-            (0, 0)
+            &(0, 0)
         );
         assert_eq!(
             chunks[3][3]
                 .get_tile_data_from_cell(MapLayers::Main, Cell::new(31, 31))
                 .unwrap(),
-            (31, 31)
+            &(31, 31)
+        );
+        assert_eq!(
+            chunks[0][0]
+                .get_tile_data_from_cell(MapLayers::Main, Cell::new(0, 0))
+                .unwrap(),
+            &(0, 0)
         );
         assert_eq!(
             chunks[0][0]
                 .get_tile_data_from_cell(MapLayers::Main, Cell::new(0, 0))
                 .unwrap(),
-            (0, 0)
+            &(0, 0)
+        );
+    }
+
+    #[test]
+    fn test_negative_cell_mapping() {
+        let map_data = SquareMapData {
+            max_chunk_size: UVec2 { x: 10, y: 10 },
+        };
+        let chunk_settings = SquareChunkSettings {
+            max_chunk_size: UVec2 { x: 10, y: 10 },
+        };
+
+        assert_eq!(
+            map_data.into_chunk_pos(Cell::new(0, 0)),
+            ChunkPos::new(0, 0)
+        );
+        assert_eq!(
+            map_data.into_chunk_pos(Cell::new(9, 9)),
+            ChunkPos::new(0, 0)
+        );
+        assert_eq!(
+            map_data.into_chunk_pos(Cell::new(-1, -1)),
+            ChunkPos::new(-1, -1)
+        );
+        assert_eq!(
+            map_data.into_chunk_pos(Cell::new(-10, -11)),
+            ChunkPos::new(-1, -2)
+        );
+        assert_eq!(
+            map_data.into_chunk_pos(Cell::new(i32::MIN, i32::MAX)),
+            ChunkPos::new(i32::MIN / 10 - 1, i32::MAX / 10)
         );
+
+        assert_eq!(
+            SquareChunkLayer::<u8>::into_chunk_cell(Cell::new(-1, -1), &chunk_settings),
+            ChunkCell::new(9, 9)
+        );
+        assert_eq!(
+            SquareChunkLayer::<u8>::into_chunk_cell(Cell::new(-10, -11), &chunk_settings),
+            ChunkCell::new(0, 9)
+        );
+        assert_eq!(
+            SquareChunkLayer::<u8>::into_chunk_cell(Cell::new(15, 3), &chunk_settings),
+            ChunkCell::new(5, 3)
+        );
+
+        for cell in [Cell::new(-1, -1), Cell::new(-10, -11), Cell::new(15, 3)] {
+            assert_eq!(
+                map_data.into_cell(
+                    map_data.into_chunk_pos(cell),
+                    SquareChunkLayer::<u8>::into_chunk_cell(cell, &chunk_settings)
+                ),
+                cell
+            );
+        }
+    }
+
+    #[test]
+    fn test_hashmap_breakdown_with_origin() {
+        let map_data = SquareMapData {
+            max_chunk_size: UVec2 { x: 10, y: 10 },
+        };
+
+        let chunk_settings = SquareChunkSettings {
+            max_chunk_size: UVec2 { x: 10, y: 10 },
+        };
+
+        let mut hashmap: HashMap<Cell, (i32, i32)> = HashMap::new();
+        hashmap.insert(Cell::new(-20, -20), (-20, -20));
+        hashmap.insert(Cell::new(-1, -1), (-1, -1));
+        hashmap.insert(Cell::new(0, 0), (0, 0));
+        hashmap.insert(Cell::new(19, 19), (19, 19));
+
+        let chunks: Vec<Vec<crate::map::chunk::Chunk<SquareChunkLayer<(i32, i32)>, (i32, i32)>>> =
+            map_data.break_hashmap_into_chunks(
+                MapLayers::Main,
+                &hashmap,
+                ChunkPos::new(-2, -2),
+                UVec2::new(40, 40),
+                UVec2::new(10, 10),
+                chunk_settings,
+            );
+
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[0].len(), 4);
+        assert_eq!(chunks[0][0].chunk_pos, ChunkPos::new(-2, -2));
+        assert_eq!(chunks[3][3].chunk_pos, ChunkPos::new(1, 1));
+
         assert_eq!(
             chunks[0][0]
+                .get_tile_data_from_cell(MapLayers::Main, Cell::new(-20, -20))
+                .unwrap(),
+            &(-20, -20)
+        );
+        assert_eq!(
+            chunks[1][1]
+                .get_tile_data_from_cell(MapLayers::Main, Cell::new(-1, -1))
+                .unwrap(),
+            &(-1, -1)
+        );
+        assert_eq!(
+            chunks[2][2]
                 .get_tile_data_from_cell(MapLayers::Main, Cell::new(0, 0))
                 .unwrap(),
-            (0, 0)
+            &(0, 0)
+        );
+        assert_eq!(
+            chunks[3][3]
+                .get_tile_data_from_cell(MapLayers::Main, Cell::new(19, 19))
+                .unwrap(),
+            &(19, 19)
         );
     }
 }
@@ -3,6 +3,7 @@ use map_data::SquareMapData;
 
 use crate::{map::chunk::Chunk, tilemap_builder::TilemapBuilder, tilemap_manager::TilemapManager};
 
+mod fov;
 /// Implements [`ChunkLayer`](crate::map::chunk::ChunkLayer) for a square map type
 pub mod map_chunk_layer;
 /// Implements [`MapData`](crate::map::MapData) for a square map type
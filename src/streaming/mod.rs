@@ -0,0 +1,295 @@
+//! Chunk streaming. Loads and unloads the chunks of a [`Tilemap`] at runtime around [`ChunkFocus`] entities.
+//!
+//! A streamed tilemap is a [`Tilemap`] whose chunks all start out unloaded, spawned with
+//! [`TilemapBuilder::new_streamed`](crate::tilemap_builder::TilemapBuilder::new_streamed), with a [`ChunkSource`]
+//! component inserted on the tilemap entity. Add [`stream_chunks`] to your app for every streamed map type and
+//! chunks will be requested from the source as they enter the radius of a focus and handed back to it when they
+//! leave every focus.
+
+use crate::map::chunk::{Chunk, ChunkLayer, ChunkPos};
+use crate::map::{MapData, TileEntity, Tilemap};
+use bevy::prelude::{BuildChildren, Commands, Component, DespawnRecursiveExt, Entity, Query};
+use bevy::utils::HashSet;
+use lettuces::cell::Cell;
+use std::hash::Hash;
+
+#[cfg(feature = "reflect")]
+use bevy::prelude::{Reflect, ReflectComponent};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Marks an entity, such as the player or camera, that keeps the chunks around it loaded
+///
+/// # Note
+/// - The focus does not track a transform, keep `cell` updated as the entity moves
+#[derive(Component, Default, Hash, Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Component, Hash))]
+pub struct ChunkFocus {
+    /// The [`Cell`] that the focus is currently at
+    pub cell: Cell,
+    /// The amount of chunks around the focused chunk that are kept loaded
+    pub radius: u32,
+}
+
+impl ChunkFocus {
+    /// Creates a new [`ChunkFocus`] at the given [`Cell`] that keeps `radius` chunks around it loaded
+    pub fn new(cell: Cell, radius: u32) -> Self {
+        Self { cell, radius }
+    }
+}
+
+/// A source of chunks for a streamed [`Tilemap`], such as a world generator or a disk loader.
+///
+/// Must be added as a component to the tilemap entity.
+pub trait ChunkSource<MapChunk, TileData>: Component
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+{
+    /// Creates or loads the chunk at the given [`ChunkPos`]. Returning `None` leaves the chunk unloaded and
+    /// it will be requested again on the next run.
+    ///
+    /// # Note
+    /// - The returned chunk must have its `chunk_pos` set to the given [`ChunkPos`]
+    fn load_chunk(&mut self, chunk_pos: ChunkPos) -> Option<Chunk<MapChunk, TileData>>;
+
+    /// Receives a chunk that is no longer inside the radius of any [`ChunkFocus`]. The chunk entity is
+    /// despawned afterwards.
+    ///
+    /// # Note
+    /// - The tile entities of the chunk, every entity with a [`TileEntity`] in one of its cells, are despawned
+    ///   along with the chunk entity. The chunk still maps them, mapped entities that don't exist are treated as
+    ///   missing once the chunk is loaded again
+    fn unload_chunk(&mut self, chunk: Chunk<MapChunk, TileData>);
+}
+
+/// System that loads the chunks inside the radius of every [`ChunkFocus`] from each tilemaps [`ChunkSource`]
+/// and unloads the chunks outside of it.
+///
+/// Only chunks inside the bounds of the tilemaps [`Chunks`](crate::map::chunk::Chunks) are ever loaded. The tile
+/// entities of unloaded chunks are despawned, see [`ChunkSource::unload_chunk`].
+pub fn stream_chunks<TileData, MapChunk, Map, Source>(
+    mut commands: Commands,
+    focus_query: Query<&ChunkFocus>,
+    mut tilemap_query: Query<(Entity, &mut Tilemap, &Map, &mut Source)>,
+    mut chunk_query: Query<&mut Chunk<MapChunk, TileData>>,
+    tile_entity_query: Query<(Entity, &TileEntity)>,
+) where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+    Source: ChunkSource<MapChunk, TileData>,
+{
+    for (tilemap_entity, mut tilemap, map, mut source) in tilemap_query.iter_mut() {
+        let mut focused_chunks: HashSet<ChunkPos> = HashSet::new();
+        for focus in focus_query.iter() {
+            let center = map.into_chunk_pos(focus.cell);
+            let radius = focus.radius as i32;
+            for y in center.y() - radius..=center.y() + radius {
+                for x in center.x() - radius..=center.x() + radius {
+                    let chunk_pos = ChunkPos::new(x, y);
+                    if tilemap.chunks().contains(chunk_pos) {
+                        focused_chunks.insert(chunk_pos);
+                    }
+                }
+            }
+        }
+
+        let unfocused_chunks: Vec<(ChunkPos, Entity)> = tilemap
+            .chunks()
+            .loaded_chunks()
+            .filter(|(chunk_pos, _)| !focused_chunks.contains(chunk_pos))
+            .collect();
+        if !unfocused_chunks.is_empty() {
+            // Tile entities aren't children of their chunk, this includes the ones of typed layers
+            let unfocused_chunk_positions: HashSet<ChunkPos> = unfocused_chunks
+                .iter()
+                .map(|(chunk_pos, _)| *chunk_pos)
+                .collect();
+            for (entity, tile_entity) in tile_entity_query.iter() {
+                if tile_entity.tilemap == tilemap_entity
+                    && unfocused_chunk_positions.contains(&map.into_chunk_pos(tile_entity.cell))
+                {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+        }
+        for (chunk_pos, chunk_entity) in unfocused_chunks {
+            if let Ok(mut chunk) = chunk_query.get_mut(chunk_entity) {
+                source.unload_chunk(std::mem::take(&mut *chunk));
+            }
+            tilemap.chunks_mut().set_chunk(chunk_pos, None);
+            commands.entity(chunk_entity).despawn_recursive();
+        }
+
+        for chunk_pos in focused_chunks {
+            if tilemap.chunks().is_loaded(chunk_pos) {
+                continue;
+            }
+            let Some(chunk) = source.load_chunk(chunk_pos) else {
+                continue;
+            };
+            let chunk_entity = commands.spawn(chunk).id();
+            commands.entity(tilemap_entity).add_child(chunk_entity);
+            tilemap
+                .chunks_mut()
+                .set_chunk(chunk_pos, Some(chunk_entity));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::map::chunk::{Chunk, ChunkLayerType, ChunkPos};
+    use crate::map::Tilemap;
+    use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::streaming::{stream_chunks, ChunkFocus, ChunkSource};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use crate::tilemap_manager::TilemapManagerError;
+    use bevy::ecs::schedule::Schedule;
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::{Component, World};
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    /// Generates chunks where every tile holds the position of its chunk
+    #[derive(Component, Default)]
+    struct Generator {
+        unloaded: Vec<ChunkPos>,
+    }
+
+    impl ChunkSource<SquareChunkLayer<(i32, i32)>, (i32, i32)> for Generator {
+        fn load_chunk(
+            &mut self,
+            chunk_pos: ChunkPos,
+        ) -> Option<Chunk<SquareChunkLayer<(i32, i32)>, (i32, i32)>> {
+            Some(Chunk::new(
+                chunk_pos,
+                UVec2::new(4, 4),
+                ChunkLayerType::Dense(vec![vec![(chunk_pos.x(), chunk_pos.y()); 4]; 4]),
+                SquareChunkSettings {
+                    max_chunk_size: UVec2::new(4, 4),
+                },
+            ))
+        }
+
+        fn unload_chunk(&mut self, chunk: Chunk<SquareChunkLayer<(i32, i32)>, (i32, i32)>) {
+            self.unloaded.push(chunk.chunk_pos);
+        }
+    }
+
+    #[test]
+    fn stream_chunks_around_focus() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let mut tilemap_builder = SquareTilemapBuilder::<(i32, i32), MapLayers>::new_streamed(
+            UVec2::new(40, 40),
+            SquareMapData {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        )
+        .unwrap();
+        tilemap_builder.set_origin(ChunkPos::new(-5, -5)).unwrap();
+        assert!(matches!(
+            tilemap_builder.add_layer(
+                TilemapLayer::new_dense_from_vecs(vec![vec![(0, 0); 40]; 40]),
+                MapLayers::Main,
+            ),
+            Err(TilemapManagerError::StreamedTilemapLayer)
+        ));
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        commands.entity(map_entity).insert(Generator::default());
+        system_state.apply(&mut world);
+        let focus = world.spawn(ChunkFocus::new(Cell::new(0, 0), 1)).id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            stream_chunks::<(i32, i32), SquareChunkLayer<(i32, i32)>, SquareMapData, Generator>,
+        );
+        schedule.run(&mut world);
+
+        let tilemap = world.get::<Tilemap>(map_entity).unwrap();
+        assert_eq!(tilemap.chunks().loaded_chunks().count(), 9);
+        assert!(tilemap.chunks().is_loaded(ChunkPos::new(-1, -1)));
+        assert!(tilemap.chunks().is_loaded(ChunkPos::new(1, 1)));
+        assert!(!tilemap.chunks().is_loaded(ChunkPos::new(2, 0)));
+        assert_eq!(tilemap.chunks().unloaded_chunks().count(), 91);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(-1, 5)).unwrap(),
+            &(-1, 1)
+        );
+        assert!(matches!(
+            tilemap_manager.get_tile_data(Cell::new(8, 0)),
+            Err(TilemapManagerError::ChunkNotLoaded(chunk_pos)) if chunk_pos == ChunkPos::new(2, 0)
+        ));
+        assert!(matches!(
+            tilemap_manager.get_tile_data(Cell::new(20, 0)),
+            Err(TilemapManagerError::InvalidChunkPos)
+        ));
+
+        let unloaded_tile_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(-2, 0))
+            .unwrap();
+        let loaded_tile_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(0, 0))
+            .unwrap();
+        system_state.apply(&mut world);
+
+        // Moving the focus one chunk to the right unloads the left column and loads the right one
+        world.get_mut::<ChunkFocus>(focus).unwrap().cell = Cell::new(4, 0);
+        schedule.run(&mut world);
+
+        // Tile entities are despawned along with their chunk
+        assert!(world.get_entity(unloaded_tile_entity).is_none());
+        assert!(world.get_entity(loaded_tile_entity).is_some());
+
+        let tilemap = world.get::<Tilemap>(map_entity).unwrap();
+        assert_eq!(tilemap.chunks().loaded_chunks().count(), 9);
+        assert!(!tilemap.chunks().is_loaded(ChunkPos::new(-1, 0)));
+        assert!(tilemap.chunks().is_loaded(ChunkPos::new(2, 0)));
+        let mut unloaded = world.get::<Generator>(map_entity).unwrap().unloaded.clone();
+        unloaded.sort_by_key(|chunk_pos| chunk_pos.y());
+        assert_eq!(
+            unloaded,
+            vec![
+                ChunkPos::new(-1, -1),
+                ChunkPos::new(-1, 0),
+                ChunkPos::new(-1, 1)
+            ]
+        );
+        assert_eq!(
+            world
+                .query::<&Chunk<SquareChunkLayer<(i32, i32)>, (i32, i32)>>()
+                .iter(&world)
+                .count(),
+            9
+        );
+
+        // Chunks outside the bounds of the map are never requested
+        world.get_mut::<ChunkFocus>(focus).unwrap().cell = Cell::new(-21, -21);
+        schedule.run(&mut world);
+        let tilemap = world.get::<Tilemap>(map_entity).unwrap();
+        assert_eq!(tilemap.chunks().loaded_chunks().count(), 1);
+        assert!(tilemap.chunks().is_loaded(ChunkPos::new(-5, -5)));
+    }
+}
@@ -1,24 +1,56 @@
 pub mod tilemap_layer_builder;
 
-use crate::map::chunk::{Chunk, ChunkLayer, ChunkLayerType, Chunks};
-use crate::map::{MapData, MapLayer, Tilemap};
+use crate::map::chunk::{
+    Chunk, ChunkLayer, ChunkLayerStorage, ChunkLayerType, ChunkPos, Chunks, ConversionPolicy,
+    TileDataEq,
+};
+use crate::map::{LayerMask, MapData, MapLayer, MapLayerDefault, TileEntity, Tilemap};
 use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
-use bevy::prelude::{BuildChildren, Commands, Entity, UVec2};
-use bevy::utils::HashMap;
-use std::hash::Hash;
+use crate::tilemap_manager::{ChunkRebuilders, TilemapManagerError};
+use bevy::prelude::{BuildChildren, Commands, Entity, UVec2, World};
+use bevy::utils::{HashMap, HashSet};
+use lettuces::cell::Cell;
+use std::any::Any;
 use std::marker::PhantomData;
 
+/// Spawns a layer with a different `TileData` than the main layer onto the chunk entities of the tilemap, registers
+/// its chunk type in the [`ChunkRebuilders`] and returns the tile entities of the layer
+type TypedLayerSpawner<TileData, MapLayers, MapChunk, MapType> = Box<
+    dyn FnOnce(
+            &TilemapBuilder<TileData, MapLayers, MapChunk, MapType>,
+            &[Vec<Entity>],
+            &mut ChunkRebuilders,
+            &mut Commands,
+        ) -> Vec<(usize, Cell, Entity)>
+        + Send
+        + Sync,
+>;
+
 /// Helper struct used to construct a new tilemap.
 pub struct TilemapBuilder<TileData, MapLayers, Chunk, MapType>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+    TileData: Clone + Sized + Send + Sync + 'static,
     MapLayers: MapLayer + Clone + Copy + Send + Sync + 'static,
     Chunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
     MapType: MapData + Default,
 {
     main_layer: Option<TilemapLayer<TileData>>,
-    layer_info: HashMap<u32, TilemapLayer<TileData>>,
+    /// Whether the tilemap is spawned with every chunk unloaded, see [`TilemapBuilder::new_streamed`]
+    streamed: bool,
+    layer_info: HashMap<usize, TilemapLayer<TileData>>,
+    typed_layers: Vec<(
+        usize,
+        TypedLayerSpawner<TileData, MapLayers, Chunk, MapType>,
+    )>,
+    /// The conversion policy of each layer along with the [`TileDataEq`] of the layers tile data, stored as `Any`
+    /// as typed layers use a different tile data
+    conversion_policies: HashMap<usize, (ConversionPolicy, Box<dyn Any + Send + Sync>)>,
+    /// The default tile data of each layer, stored as `Any` as typed layers use a different tile data
+    default_tile_data: HashMap<usize, Box<dyn Any + Send + Sync>>,
+    /// The layers that track their dirty cells
+    dirty_tracked_layers: HashSet<usize>,
     map_size: UVec2,
+    origin: ChunkPos,
     map_type: MapType,
     chunk_settings: Chunk::ChunkSettings,
     // All phantom data below
@@ -30,7 +62,7 @@ where
 impl<TileData, MapLayers, MapChunk, MapType> Default
     for TilemapBuilder<TileData, MapLayers, MapChunk, MapType>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+    TileData: Clone + Sized + Send + Sync + 'static,
     MapLayers: MapLayer + Clone + Copy + Send + Sync + 'static,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
     MapType: MapData + Default,
@@ -38,30 +70,42 @@ where
     fn default() -> Self {
         Self {
             main_layer: None,
+            streamed: false,
             layer_info: Default::default(),
+            typed_layers: Default::default(),
+            conversion_policies: Default::default(),
+            default_tile_data: Default::default(),
+            dirty_tracked_layers: Default::default(),
             map_size: Default::default(),
+            origin: Default::default(),
             map_type: Default::default(),
             chunk_settings: MapChunk::ChunkSettings::default(),
-            td_phantom: PhantomData::default(),
-            ml_phantom: PhantomData::default(),
-            ct_phantom: PhantomData::default(),
+            td_phantom: PhantomData,
+            ml_phantom: PhantomData,
+            ct_phantom: PhantomData,
         }
     }
 }
 
 impl<TileData, MapLayers, MapChunk, MapType> TilemapBuilder<TileData, MapLayers, MapChunk, MapType>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
-    MapLayers: MapLayer + Clone + Copy + Send + Sync + 'static,
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + MapLayerDefault<TileData> + Clone + Copy + Send + Sync + 'static,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
     MapType: MapData + Default + Send + Sync + 'static,
 {
     /// Converts all the data from the tilemap builder and spawns the tilemap returning the Tilemaps [`Entity`]
+    ///
+    /// Every layer with a storage declared on its [`MapLayer`] that was not added to the builder is created empty,
+    /// or filled with its [`MapLayerDefault`]. Streamed tilemaps are spawned without any chunks, see
+    /// [`new_streamed`](TilemapBuilder::new_streamed).
     #[must_use]
     pub fn spawn_tilemap(mut self, commands: &mut Commands) -> Option<Entity> {
-        let Some(layer) = self.main_layer.take() else {
-            return None;
-        };
+        if self.streamed {
+            return Some(self.spawn_streamed_tilemap(commands));
+        }
+        let layer = self.main_layer.take()?;
+        self.add_declared_layers();
 
         let mut chunks = self.create_new_chunks_from_layer(
             &layer,
@@ -69,20 +113,40 @@ where
             self.map_type.max_chunk_size(),
         );
 
-        let layers: Vec<(u32, TilemapLayer<TileData>)> = self.layer_info.drain().collect();
+        let layers: Vec<(usize, TilemapLayer<TileData>)> = self.layer_info.drain().collect();
+
+        let mut tile_entities: Vec<(usize, Cell, Entity)> = layer
+            .entities()
+            .iter()
+            .map(|(cell, entity)| (MapLayers::default().to_index(), *cell, *entity))
+            .collect();
+
+        let default_layer = MapLayers::default().to_index();
+        self.apply_layer_settings(
+            default_layer,
+            &mut chunks,
+            self.layer_default_tile_data(default_layer),
+        );
 
         for (id, layer) in layers {
-            self.add_layer_to_chunks(id, &mut chunks, &layer, self.map_type.max_chunk_size())
+            tile_entities.extend(
+                layer
+                    .entities()
+                    .iter()
+                    .map(|(cell, entity)| (id, *cell, *entity)),
+            );
+            self.add_layer_to_chunks(id, &mut chunks, &layer, self.map_type.max_chunk_size());
+            self.apply_layer_settings(id, &mut chunks, self.layer_default_tile_data(id));
         }
 
         let mut chunk_entities: Vec<Vec<Entity>> = vec![];
 
         let map_x = chunks[0].len();
 
-        for y in 0..chunks.len() {
+        for chunk_row in chunks.iter_mut() {
             let mut vec: Vec<Entity> = vec![];
             for _ in 0..map_x {
-                let entity = commands.spawn(chunks[y].remove(0)).id();
+                let entity = commands.spawn(chunk_row.remove(0)).id();
                 vec.push(entity);
             }
             chunk_entities.push(vec);
@@ -94,45 +158,407 @@ where
             flattened_chunk_entities.extend(chunk_entity.iter().cloned())
         }
 
-        let chunks = Chunks::new(
+        let mut rebuilders = ChunkRebuilders::default();
+        rebuilders.register::<TileData, MapLayers, MapChunk, MapType>();
+        let mut typed_layers = LayerMask::NONE;
+        for (map_layer, spawn_typed_layer) in std::mem::take(&mut self.typed_layers) {
+            typed_layers = typed_layers | LayerMask::from_index(map_layer);
+            tile_entities.extend(spawn_typed_layer(
+                &self,
+                &chunk_entities,
+                &mut rebuilders,
+                commands,
+            ));
+        }
+
+        let chunks = Chunks::new_with_origin(
             Chunks::new_chunk_entity_grid(chunk_entities),
             self.map_type.max_chunk_size(),
+            self.origin,
         );
 
+        let mut tilemap = Tilemap::new(chunks);
+        tilemap.set_typed_layers(typed_layers);
         let tilemap_entity = commands
-            .spawn((Tilemap::new(chunks), self.map_type))
+            .spawn((tilemap, self.map_type, rebuilders))
             .push_children(flattened_chunk_entities.as_slice())
             .id();
+
+        for (layer, cell, entity) in tile_entities {
+            commands.entity(entity).insert(TileEntity {
+                tilemap: tilemap_entity,
+                cell,
+                layer,
+            });
+        }
         Some(tilemap_entity)
     }
 
     /// Makes a new [`TilemapBuilder`] with the given [`TilemapLayer`] as the main layer.
+    ///
+    /// # Errors
+    /// - [`TilemapManagerError::LayerStorageMismatch`] if the layer does not use the storage declared on the default
+    ///   [`MapLayer`]
+    /// - [`TilemapManagerError::MapShapeMismatch`] if the layer is not the size the shape of the map type needs, see
+    ///   [`MapData::check_layout`]
     pub fn new(
         layer_data: TilemapLayer<TileData>,
         map_type: MapType,
         chunk_settings: MapChunk::ChunkSettings,
-    ) -> Self {
+    ) -> Result<Self, TilemapManagerError> {
+        check_declared_storage(MapLayers::default(), &layer_data)?;
         let dimensions = layer_data.dimensions();
-        TilemapBuilder::<TileData, MapLayers, MapChunk, MapType> {
+        map_type.check_layout(dimensions, ChunkPos::default())?;
+        Ok(TilemapBuilder::<TileData, MapLayers, MapChunk, MapType> {
             main_layer: Some(layer_data),
+            streamed: false,
             layer_info: Default::default(),
+            typed_layers: Default::default(),
+            conversion_policies: Default::default(),
+            default_tile_data: Default::default(),
+            dirty_tracked_layers: Default::default(),
             map_size: dimensions,
+            origin: ChunkPos::default(),
             map_type,
             chunk_settings,
             td_phantom: Default::default(),
             ml_phantom: Default::default(),
-            ct_phantom: PhantomData::default(),
+            ct_phantom: PhantomData,
+        })
+    }
+
+    /// Makes a new [`TilemapBuilder`] for a streamed tilemap of the given size in cells. Every chunk of the tilemap
+    /// starts out unloaded and is loaded from the [`ChunkSource`](crate::streaming::ChunkSource) of the tilemap by
+    /// [`stream_chunks`](crate::streaming::stream_chunks).
+    ///
+    /// Insert the [`ChunkSource`](crate::streaming::ChunkSource) on the entity returned by
+    /// [`spawn_tilemap`](TilemapBuilder::spawn_tilemap).
+    ///
+    /// # Note
+    /// - The chunks come with their own layers, so layers can't be added to the builder and the layer settings of the
+    ///   builder, such as [`set_default_tile_data`](TilemapBuilder::set_default_tile_data), are not used
+    ///
+    /// # Errors
+    /// - [`TilemapManagerError::InvalidMapSize`] if the map size has a zero dimension
+    /// - [`TilemapManagerError::MapShapeMismatch`] if the map size is not the size the shape of the map type needs,
+    ///   see [`MapData::check_layout`]
+    pub fn new_streamed(
+        map_size: UVec2,
+        map_type: MapType,
+        chunk_settings: MapChunk::ChunkSettings,
+    ) -> Result<Self, TilemapManagerError> {
+        if map_size.x == 0 || map_size.y == 0 {
+            return Err(TilemapManagerError::InvalidMapSize);
         }
+        map_type.check_layout(map_size, ChunkPos::default())?;
+        Ok(TilemapBuilder::<TileData, MapLayers, MapChunk, MapType> {
+            streamed: true,
+            map_size,
+            map_type,
+            chunk_settings,
+            ..Default::default()
+        })
+    }
+
+    /// Spawns a streamed tilemap with every chunk unloaded, see [`new_streamed`](TilemapBuilder::new_streamed)
+    fn spawn_streamed_tilemap(self, commands: &mut Commands) -> Entity {
+        let max_chunk_size = self.map_type.max_chunk_size();
+        let chunk_counts = (self.map_size + max_chunk_size - UVec2::ONE) / max_chunk_size;
+        let mut rebuilders = ChunkRebuilders::default();
+        rebuilders.register::<TileData, MapLayers, MapChunk, MapType>();
+        let tilemap = Tilemap::new(Chunks::new_unloaded(
+            chunk_counts,
+            max_chunk_size,
+            self.origin,
+        ));
+        commands.spawn((tilemap, self.map_type, rebuilders)).id()
+    }
+
+    /// Sets the [`ChunkPos`] that the first chunk of the tilemap is placed at. Defaults to (0, 0).
+    ///
+    /// Dense layer data starts at the first cell of this chunk and sparse layer data is keyed by
+    /// [`Cell`](lettuces::cell::Cell)s inside the resulting area. Use a negative origin to build
+    /// maps that cover negative cells, eg a map centered on (0, 0).
+    ///
+    /// # Errors
+    /// - [`TilemapManagerError::MapShapeMismatch`] if the shape of the map type must start at another chunk, see
+    ///   [`MapData::check_layout`]
+    pub fn set_origin(&mut self, origin: ChunkPos) -> Result<(), TilemapManagerError> {
+        self.map_type.check_layout(self.map_size, origin)?;
+        self.origin = origin;
+        Ok(())
     }
 
     /// Adds the given [`TilemapLayer`] to the tilemap keyed to the given [`MapLayer`]
-    pub fn add_layer(&mut self, layer_data: TilemapLayer<TileData>, map_layer: MapLayers) {
-        assert_eq!(
-            self.map_size,
-            layer_data.dimensions(),
-            "New layers must be the same size as the map dimensions"
+    ///
+    /// # Errors
+    /// - [`TilemapManagerError::LayerSizeMismatch`] if the layer is not the same size as the map
+    /// - [`TilemapManagerError::LayerStorageMismatch`] if the layer does not use the storage declared on the
+    ///   [`MapLayer`]
+    /// - [`TilemapManagerError::StreamedTilemapLayer`] if the builder was made with
+    ///   [`new_streamed`](TilemapBuilder::new_streamed)
+    pub fn add_layer(
+        &mut self,
+        layer_data: TilemapLayer<TileData>,
+        map_layer: MapLayers,
+    ) -> Result<(), TilemapManagerError> {
+        self.check_layer_size(&layer_data)?;
+        check_declared_storage(map_layer, &layer_data)?;
+        self.layer_info.insert(map_layer.to_index(), layer_data);
+        Ok(())
+    }
+
+    /// Returns an error if the given layer is not the same size as the map or the map is streamed
+    fn check_layer_size<T>(&self, layer_data: &TilemapLayer<T>) -> Result<(), TilemapManagerError>
+    where
+        T: Clone + Sized + Send + Sync,
+    {
+        if self.streamed {
+            return Err(TilemapManagerError::StreamedTilemapLayer);
+        }
+        if self.map_size != layer_data.dimensions() {
+            return Err(TilemapManagerError::LayerSizeMismatch(
+                self.map_size,
+                layer_data.dimensions(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Adds the given [`TilemapLayer`] keyed to the given [`MapLayer`], using a different `TileData` type than the
+    /// rest of the tilemap.
+    ///
+    /// The layer is stored in its own [`Chunk<TypedChunk, T>`](Chunk) component on the same chunk entities as the
+    /// main layer. Access it with a [`TilemapManager`](crate::tilemap_manager::TilemapManager) for the same `T`
+    /// and `TypedChunk`, eg `SquareTilemapManager<T, MapLayers>`, set to the given [`MapLayer`].
+    ///
+    /// # Note
+    /// - Typed layers with the same `T` and `TypedChunk` share one chunk component
+    /// - Typed layers can also be added and removed on a spawned tilemap with
+    ///   [`TilemapManager::add_layer`](crate::tilemap_manager::TilemapManager::add_layer) and
+    ///   [`TilemapManager::remove_layer`](crate::tilemap_manager::TilemapManager::remove_layer)
+    ///
+    /// # Errors
+    /// - [`TilemapManagerError::LayerSizeMismatch`] if the layer is not the same size as the map
+    /// - [`TilemapManagerError::LayerStorageMismatch`] if the layer does not use the storage declared on the
+    ///   [`MapLayer`]
+    /// - [`TilemapManagerError::StreamedTilemapLayer`] if the builder was made with
+    ///   [`new_streamed`](TilemapBuilder::new_streamed)
+    pub fn add_typed_layer<T, TypedChunk>(
+        &mut self,
+        layer_data: TilemapLayer<T>,
+        map_layer: MapLayers,
+    ) -> Result<(), TilemapManagerError>
+    where
+        T: Clone + Sized + Send + Sync + 'static,
+        TypedChunk: ChunkLayer<T, ChunkSettings = MapChunk::ChunkSettings>
+            + Send
+            + Sync
+            + 'static
+            + Default,
+    {
+        self.check_layer_size(&layer_data)?;
+        check_declared_storage(map_layer, &layer_data)?;
+        let map_layer = map_layer.to_index();
+        self.typed_layers.push((
+            map_layer,
+            Box::new(
+                move |builder: &Self,
+                      chunk_entities: &[Vec<Entity>],
+                      rebuilders: &mut ChunkRebuilders,
+                      commands: &mut Commands| {
+                    rebuilders.register::<T, MapLayers, TypedChunk, MapType>();
+                    builder.spawn_typed_layer::<T, TypedChunk>(
+                        map_layer,
+                        &layer_data,
+                        chunk_entities,
+                        commands,
+                    )
+                },
+            ),
+        ));
+        Ok(())
+    }
+
+    /// Creates an empty layer for every [`MapLayer`] that declares a storage but was not added to the builder
+    fn add_declared_layers(&mut self) {
+        let default_layer = MapLayers::default().to_index();
+        for index in 0..MapLayers::layer_count() {
+            let Some(map_layer) = MapLayers::from_index(index) else {
+                continue;
+            };
+            let Some(storage) = map_layer.storage() else {
+                continue;
+            };
+            if index == default_layer
+                || self.layer_info.contains_key(&index)
+                || self
+                    .typed_layers
+                    .iter()
+                    .any(|(typed_index, _)| *typed_index == index)
+            {
+                continue;
+            }
+            let tile_data = self.layer_default_tile_data(index);
+            let (x, y) = (self.map_size.x as usize, self.map_size.y as usize);
+            // Dense and palette layers can only be filled when the layer has default tile data
+            let layer_data = match (storage, tile_data, map_layer.tile_data_eq()) {
+                (ChunkLayerStorage::Sparse, ..) => TilemapLayer::new_sparse_empty(x, y),
+                (ChunkLayerStorage::Dense, Some(tile_data), _) => {
+                    TilemapLayer::new_dense_uniform(x, y, tile_data)
+                }
+                (ChunkLayerStorage::Palette, Some(tile_data), Some(tile_data_eq)) => {
+                    TilemapLayer::new_palette_from_vecs_with(
+                        vec![vec![tile_data; x]; y],
+                        tile_data_eq,
+                    )
+                }
+                _ => continue,
+            };
+            self.layer_info.insert(index, layer_data);
+        }
+    }
+
+    /// Sets the [`ConversionPolicy`] used by every chunk of the given [`MapLayer`] to automatically convert
+    /// between sparse and dense storage
+    ///
+    /// # Note
+    /// - The layer must be the main layer or added with [`add_layer`](TilemapBuilder::add_layer), use
+    ///   [`set_typed_conversion_policy`](TilemapBuilder::set_typed_conversion_policy) for typed layers
+    /// - The layer only converts if it has default tile data
+    pub fn set_conversion_policy(
+        &mut self,
+        map_layer: MapLayers,
+        conversion_policy: ConversionPolicy,
+    ) where
+        TileData: PartialEq,
+    {
+        self.set_typed_conversion_policy::<TileData>(map_layer, conversion_policy);
+    }
+
+    /// Sets the [`ConversionPolicy`] used by every chunk of the given typed [`MapLayer`] to automatically convert
+    /// between sparse and dense storage
+    ///
+    /// # Note
+    /// - `T` must be the tile data the layer was added with in [`add_typed_layer`](TilemapBuilder::add_typed_layer),
+    ///   the policy is ignored otherwise
+    /// - The layer only converts if it has default tile data
+    pub fn set_typed_conversion_policy<T>(
+        &mut self,
+        map_layer: MapLayers,
+        conversion_policy: ConversionPolicy,
+    ) where
+        T: PartialEq + 'static,
+    {
+        let tile_data_eq: TileDataEq<T> = T::eq;
+        self.conversion_policies.insert(
+            map_layer.to_index(),
+            (conversion_policy, Box::new(tile_data_eq)),
+        );
+    }
+
+    /// Sets the default tile data of the given [`MapLayer`]. Overrides the [`MapLayerDefault`] of the layer.
+    ///
+    /// Dense and palette layers reset removed tiles to their default tile data and can only be converted by a
+    /// [`ConversionPolicy`] if they have one.
+    ///
+    /// # Note
+    /// - The layer must be the main layer or added with [`add_layer`](TilemapBuilder::add_layer), use
+    ///   [`set_typed_default_tile_data`](TilemapBuilder::set_typed_default_tile_data) for typed layers
+    pub fn set_default_tile_data(&mut self, map_layer: MapLayers, tile_data: TileData) {
+        self.set_typed_default_tile_data(map_layer, tile_data);
+    }
+
+    /// Sets the default tile data of the given typed [`MapLayer`]
+    ///
+    /// # Note
+    /// - `T` must be the tile data the layer was added with in [`add_typed_layer`](TilemapBuilder::add_typed_layer),
+    ///   the default is ignored otherwise
+    pub fn set_typed_default_tile_data<T>(&mut self, map_layer: MapLayers, tile_data: T)
+    where
+        T: Clone + Sized + Send + Sync + 'static,
+    {
+        self.default_tile_data
+            .insert(map_layer.to_index(), Box::new(tile_data));
+    }
+
+    /// Sets whether every chunk of the given [`MapLayer`] records its dirty cells, which is needed for the layer to
+    /// send [`TileChanged`](crate::map::TileChanged) events. See [`ChunkLayer::set_track_dirty_cells`]
+    ///
+    /// # Note
+    /// - Works for typed layers as well
+    pub fn set_track_dirty_cells(&mut self, map_layer: MapLayers, track_dirty_cells: bool) {
+        if track_dirty_cells {
+            self.dirty_tracked_layers.insert(map_layer.to_index());
+        } else {
+            self.dirty_tracked_layers.remove(&map_layer.to_index());
+        }
+    }
+
+    /// Returns the default tile data of the given main tile data layer, set on the builder or declared on the
+    /// [`MapLayer`]
+    fn layer_default_tile_data(&self, map_layer: usize) -> Option<TileData> {
+        self.typed_default_tile_data(map_layer).or_else(|| {
+            MapLayers::from_index(map_layer).and_then(|map_layer| map_layer.default_tile_data())
+        })
+    }
+
+    /// Returns the default tile data set on the builder for the given layer if it uses `T`
+    fn typed_default_tile_data<T>(&self, map_layer: usize) -> Option<T>
+    where
+        T: Clone + 'static,
+    {
+        self.default_tile_data
+            .get(&map_layer)?
+            .downcast_ref::<T>()
+            .cloned()
+    }
+
+    /// Sets the given default tile data along with the conversion policy and dirty cell tracking set on the builder
+    /// on the given layer of every chunk that has it. The layer is emptied and made sparse in chunks outside of the
+    /// shape of the map, see [`MapData::chunk_in_shape`]
+    fn apply_layer_settings<T, TypedChunk>(
+        &self,
+        map_layer: usize,
+        chunks: &mut [Vec<Chunk<TypedChunk, T>>],
+        default_tile_data: Option<T>,
+    ) where
+        T: Clone + Sized + Send + Sync + 'static,
+        TypedChunk: ChunkLayer<T> + Send + Sync + 'static + Default,
+    {
+        let conversion_policy = self.conversion_policies.get(&map_layer).and_then(
+            |(conversion_policy, tile_data_eq)| {
+                let tile_data_eq = tile_data_eq.downcast_ref::<TileDataEq<T>>()?;
+                Some((*conversion_policy, *tile_data_eq))
+            },
         );
-        self.layer_info.insert(map_layer.to_bits(), layer_data);
+        let track_dirty_cells = self.dirty_tracked_layers.contains(&map_layer);
+        for chunk in chunks
+            .iter_mut()
+            .flatten()
+            .filter(|chunk| !self.map_type.chunk_in_shape(chunk.chunk_pos))
+        {
+            let Some(layer) = chunk.layer(map_layer) else {
+                continue;
+            };
+            let layer = TypedChunk::new(
+                ChunkLayerType::Sparse(HashMap::new()),
+                layer.get_chunk_dimensions(),
+                &chunk.chunk_settings,
+            );
+            chunk.insert_layer(map_layer, layer);
+        }
+        for layer in chunks
+            .iter_mut()
+            .flatten()
+            .filter_map(|chunk| chunk.layer_mut(map_layer))
+        {
+            layer.set_default_tile_data(default_tile_data.clone());
+            layer.set_track_dirty_cells(track_dirty_cells);
+            if let Some((conversion_policy, tile_data_eq)) = conversion_policy {
+                layer.set_conversion_policy_with(Some(conversion_policy), tile_data_eq);
+            }
+        }
     }
 
     /// Function which creates new chunks and inserts the given tilemap layer into those chunks
@@ -143,46 +569,135 @@ where
         max_chunk_size: UVec2,
     ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
     where
-        TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+        TileData: Clone + Sized + Send + Sync + 'static,
     {
-        return match tilemap_layer {
+        match tilemap_layer {
             TilemapLayer::Sparse(data, map_size, entities) => {
                 let mut chunks = self.map_type.break_hashmap_into_chunks(
                     MapLayers::default(),
                     data,
-                    map_size.clone(),
+                    self.origin,
+                    *map_size,
                     max_chunk_size,
                     chunk_settings,
                 );
                 self.map_type.add_entities_to_layer(
-                    MapLayers::default().to_bits(),
+                    MapLayers::default().to_index(),
                     &mut chunks,
                     entities,
                 );
                 chunks
             }
             TilemapLayer::Dense(data, entities) => {
-                let mut chunks =
-                    self.map_type
-                        .break_data_vecs_into_chunks(data, max_chunk_size, chunk_settings);
+                let mut chunks = self.map_type.break_data_vecs_into_chunks(
+                    data,
+                    self.origin,
+                    max_chunk_size,
+                    chunk_settings,
+                );
                 self.map_type.add_entities_to_layer(
-                    MapLayers::default().to_bits(),
+                    MapLayers::default().to_index(),
                     &mut chunks,
                     entities,
                 );
                 chunks
             }
-        };
+            TilemapLayer::Palette(..) => {
+                let mut chunks = self.map_type.break_hashmap_into_chunks(
+                    MapLayers::default(),
+                    &HashMap::new(),
+                    self.origin,
+                    tilemap_layer.dimensions(),
+                    max_chunk_size,
+                    chunk_settings,
+                );
+                self.add_layer_to_chunks(
+                    MapLayers::default().to_index(),
+                    &mut chunks,
+                    tilemap_layer,
+                    max_chunk_size,
+                );
+                chunks
+            }
+        }
+    }
+
+    /// Splits the given typed layer into chunks and inserts them onto the given chunk entities. Returns the
+    /// tile entities of the layer
+    fn spawn_typed_layer<T, TypedChunk>(
+        &self,
+        map_layer: usize,
+        tilemap_layer: &TilemapLayer<T>,
+        chunk_entities: &[Vec<Entity>],
+        commands: &mut Commands,
+    ) -> Vec<(usize, Cell, Entity)>
+    where
+        T: Clone + Sized + Send + Sync + 'static,
+        TypedChunk: ChunkLayer<T, ChunkSettings = MapChunk::ChunkSettings>
+            + Send
+            + Sync
+            + 'static
+            + Default,
+    {
+        let max_chunk_size = self.map_type.max_chunk_size();
+        let mut chunks = self.map_type.break_hashmap_into_chunks::<T, TypedChunk>(
+            MapLayers::default(),
+            &HashMap::new(),
+            self.origin,
+            self.map_size,
+            max_chunk_size,
+            self.chunk_settings,
+        );
+        self.add_layer_to_chunks(map_layer, &mut chunks, tilemap_layer, max_chunk_size);
+        self.apply_layer_settings(
+            map_layer,
+            &mut chunks,
+            self.typed_default_tile_data(map_layer),
+        );
+
+        let default_layer = MapLayers::default().to_index();
+        for (chunk_row, entity_row) in chunks.into_iter().zip(chunk_entities) {
+            for (mut chunk, chunk_entity) in chunk_row.into_iter().zip(entity_row) {
+                // Drop the empty layer the chunks were created with
+                if map_layer != default_layer {
+                    chunk.remove_layer(default_layer);
+                }
+                let chunk_entity = *chunk_entity;
+                // Merge into the chunk component of an earlier layer with the same type if there is one
+                commands.add(move |world: &mut World| {
+                    if let Some(mut existing_chunk) =
+                        world.get_mut::<Chunk<TypedChunk, T>>(chunk_entity)
+                    {
+                        for (map_layer, layer) in chunk.data.into_iter().enumerate() {
+                            if let Some(layer) = layer {
+                                existing_chunk.insert_layer(map_layer, layer);
+                            }
+                        }
+                    } else {
+                        world.entity_mut(chunk_entity).insert(chunk);
+                    }
+                });
+            }
+        }
+
+        tilemap_layer
+            .entities()
+            .iter()
+            .map(|(cell, entity)| (map_layer, *cell, *entity))
+            .collect()
     }
 
     /// Adds the given layer to the tilemap
-    pub fn add_layer_to_chunks(
-        &mut self,
-        map_layer: u32,
-        chunks: &mut Vec<Vec<Chunk<MapChunk, TileData>>>,
-        tilemap_layer: &TilemapLayer<TileData>,
+    pub fn add_layer_to_chunks<T, TypedChunk>(
+        &self,
+        map_layer: usize,
+        chunks: &mut Vec<Vec<Chunk<TypedChunk, T>>>,
+        tilemap_layer: &TilemapLayer<T>,
         max_chunk_size: UVec2,
-    ) {
+    ) where
+        T: Clone + Sized + Send + Sync + 'static,
+        TypedChunk: ChunkLayer<T> + Send + Sync + 'static + Default,
+    {
         match tilemap_layer {
             TilemapLayer::Sparse(data, .., entities) => {
                 for y in chunks.iter_mut() {
@@ -190,27 +705,40 @@ where
                         chunk.add_layer(map_layer, ChunkLayerType::Sparse(HashMap::new()));
                     }
                 }
-                for (cell, tile_data) in data.iter() {
+                for (cell, tile_data) in data
+                    .iter()
+                    .filter(|(cell, _)| self.map_type.contains_cell(**cell))
+                {
                     let chunk_pos = self.map_type.into_chunk_pos(*cell);
-                    let chunk = &mut chunks[chunk_pos.y() as usize][chunk_pos.x() as usize];
+                    let chunk = &mut chunks[(chunk_pos.y() - self.origin.y()) as usize]
+                        [(chunk_pos.x() - self.origin.x()) as usize];
                     chunk.set_tile_data(
                         map_layer,
-                        MapChunk::into_chunk_cell(*cell, &chunk.chunk_settings),
+                        TypedChunk::into_chunk_cell(*cell, &chunk.chunk_settings),
                         tile_data.clone(),
                     );
                 }
                 self.map_type
                     .add_entities_to_layer(map_layer, chunks, entities);
             }
-            TilemapLayer::Dense(data, entities) => {
+            TilemapLayer::Dense(data, entities) | TilemapLayer::Palette(data, _, entities) => {
                 for y in chunks.iter_mut() {
                     for chunk in y.iter_mut() {
                         let vec = self.map_type.break_data_vecs_down_into_chunk_data(
-                            &data,
-                            chunk.chunk_pos,
+                            data,
+                            ChunkPos::new(
+                                chunk.chunk_pos.x() - self.origin.x(),
+                                chunk.chunk_pos.y() - self.origin.y(),
+                            ),
                             max_chunk_size,
                         );
-                        chunk.add_layer(map_layer, ChunkLayerType::Dense(vec));
+                        let layer_type = match tilemap_layer {
+                            TilemapLayer::Palette(_, tile_data_eq, _) => {
+                                ChunkLayerType::Palette(vec, *tile_data_eq)
+                            }
+                            _ => ChunkLayerType::Dense(vec),
+                        };
+                        chunk.add_layer(map_layer, layer_type);
                     }
                 }
                 self.map_type
@@ -220,20 +748,43 @@ where
     }
 }
 
+/// Returns an error if the given layer does not use the storage declared on its [`MapLayer`]
+fn check_declared_storage<T>(
+    map_layer: impl MapLayer,
+    layer_data: &TilemapLayer<T>,
+) -> Result<(), TilemapManagerError>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    match map_layer.storage() {
+        Some(storage) if storage != layer_data.storage() => Err(
+            TilemapManagerError::LayerStorageMismatch(storage, layer_data.storage()),
+        ),
+        _ => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate as bevy_sparse_tilemap;
 
+    use crate::map::chunk::{ChunkLayer, ChunkLayerStorage, ChunkPos, ConversionPolicy};
+    use crate::map::TileEntity;
+    use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
     use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
     use crate::tilemap_builder::TilemapBuilder;
-    use crate::tilemap_manager::TilemapManager;
-    use bevy::ecs::system::SystemState;
+    use crate::tilemap_manager::{TilemapManager, TilemapManagerError};
+    use bevy::ecs::system::{Commands, SystemState};
     use bevy::math::UVec2;
     use bevy::prelude::World;
     use bevy::utils::hashbrown::HashMap;
     use bst_map_layer_derive::MapLayer;
+    use declared::DeclaredLayers;
+    use lettuces::cell::Cell;
 
-    #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Hash)]
     struct TileData(u8);
 
     #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
@@ -243,5 +794,394 @@ mod tests {
         Secondary,
     }
 
-    pub struct MainMap;
+    #[test]
+    fn builder_origin_layers() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(
+            Commands,
+            TilemapManager<TileData, MapLayers, SquareChunkLayer<TileData>, SquareMapData>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let mut tilemap_builder =
+            TilemapBuilder::<TileData, MapLayers, SquareChunkLayer<TileData>, SquareMapData>::new(
+                TilemapLayer::new_dense_from_vecs(vec![vec![TileData(1); 6]; 6]),
+                SquareMapData {
+                    max_chunk_size: UVec2::new(3, 3),
+                },
+                SquareChunkSettings {
+                    max_chunk_size: UVec2::new(3, 3),
+                },
+            )
+            .unwrap();
+        tilemap_builder.set_origin(ChunkPos::new(-1, -1)).unwrap();
+
+        let mut hashmap: HashMap<Cell, TileData> = HashMap::new();
+        hashmap.insert(Cell::new(-3, -3), TileData(2));
+        hashmap.insert(Cell::new(2, 2), TileData(3));
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(6, 6, hashmap),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+        tilemap_builder.set_conversion_policy(MapLayers::Secondary, ConversionPolicy::default());
+
+        let map_entity = tilemap_builder
+            .spawn_tilemap(&mut commands)
+            .expect("Tilemap should spawn");
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(6, 6));
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(-3, -3)).unwrap(),
+            &TileData(1)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(2, 2)).unwrap(),
+            &TileData(1)
+        );
+        assert!(tilemap_manager.get_tile_data(Cell::new(3, 0)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(0, -4)).is_err());
+
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(-3, -3)).unwrap(),
+            &TileData(2)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(2, 2)).unwrap(),
+            &TileData(3)
+        );
+        assert!(tilemap_manager.get_tile_data(Cell::new(0, 0)).is_err());
+
+        let chunk = tilemap_manager.get_chunk(ChunkPos::new(0, 0)).unwrap();
+        assert_eq!(
+            chunk
+                .layer(MapLayers::Main.to_index())
+                .unwrap()
+                .conversion_policy(),
+            None
+        );
+        assert_eq!(
+            chunk
+                .layer(MapLayers::Secondary.to_index())
+                .unwrap()
+                .conversion_policy(),
+            Some(ConversionPolicy::default())
+        );
+    }
+
+    mod declared {
+        use super::{bevy_sparse_tilemap, TileData};
+        use bst_map_layer_derive::MapLayer;
+
+        #[derive(MapLayer, Default, Debug, Clone, Copy)]
+        #[layer(tile_data = TileData)]
+        pub enum DeclaredLayers {
+            #[default]
+            #[layer(dense)]
+            Main,
+            #[layer(default = TileData(4))]
+            Height,
+            #[layer(sparse)]
+            Items,
+            #[layer(palette)]
+            Biome,
+            Undeclared,
+        }
+    }
+
+    #[test]
+    fn builder_creates_declared_layers() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(
+            Commands,
+            TilemapManager<TileData, DeclaredLayers, SquareChunkLayer<TileData>, SquareMapData>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let tilemap_builder = TilemapBuilder::<
+            TileData,
+            DeclaredLayers,
+            SquareChunkLayer<TileData>,
+            SquareMapData,
+        >::new(
+            TilemapLayer::new_dense_default(4, 4),
+            SquareMapData {
+                max_chunk_size: UVec2::new(2, 2),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(2, 2),
+            },
+        )
+        .unwrap();
+        let map_entity = tilemap_builder
+            .spawn_tilemap(&mut commands)
+            .expect("Tilemap should spawn");
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        tilemap_manager.set_layer(DeclaredLayers::Height);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(3, 3)).unwrap(),
+            &TileData(4)
+        );
+        tilemap_manager.set_layer(DeclaredLayers::Items);
+        assert!(tilemap_manager.get_tile_data(Cell::new(3, 3)).is_err());
+
+        let chunk = tilemap_manager.get_chunk(ChunkPos::new(1, 1)).unwrap();
+        assert_eq!(
+            chunk
+                .layer(DeclaredLayers::Biome.to_index())
+                .unwrap()
+                .storage(),
+            ChunkLayerStorage::Palette
+        );
+        assert_eq!(
+            chunk
+                .layer(DeclaredLayers::Items.to_index())
+                .unwrap()
+                .storage(),
+            ChunkLayerStorage::Sparse
+        );
+        assert!(!chunk.has_layer(DeclaredLayers::Undeclared.to_index()));
+    }
+
+    #[test]
+    fn builder_rejects_mismatched_storage() {
+        let new_builder = |layer_data| {
+            TilemapBuilder::<TileData, DeclaredLayers, SquareChunkLayer<TileData>, SquareMapData>::new(
+                layer_data,
+                SquareMapData {
+                    max_chunk_size: UVec2::new(2, 2),
+                },
+                SquareChunkSettings {
+                    max_chunk_size: UVec2::new(2, 2),
+                },
+            )
+        };
+        assert!(matches!(
+            new_builder(TilemapLayer::new_sparse_empty(4, 4)),
+            Err(TilemapManagerError::LayerStorageMismatch(
+                ChunkLayerStorage::Dense,
+                ChunkLayerStorage::Sparse
+            ))
+        ));
+
+        let mut tilemap_builder = new_builder(TilemapLayer::new_dense_default(4, 4)).unwrap();
+        assert!(matches!(
+            tilemap_builder.add_layer(TilemapLayer::new_sparse_empty(4, 4), DeclaredLayers::Height),
+            Err(TilemapManagerError::LayerStorageMismatch(
+                ChunkLayerStorage::Dense,
+                ChunkLayerStorage::Sparse
+            ))
+        ));
+        assert!(matches!(
+            tilemap_builder.add_typed_layer::<u32, SquareChunkLayer<u32>>(
+                TilemapLayer::new_dense_default(4, 4),
+                DeclaredLayers::Items
+            ),
+            Err(TilemapManagerError::LayerStorageMismatch(
+                ChunkLayerStorage::Sparse,
+                ChunkLayerStorage::Dense
+            ))
+        ));
+        assert!(matches!(
+            tilemap_builder.add_layer(TilemapLayer::new_sparse_empty(3, 4), DeclaredLayers::Items),
+            Err(TilemapManagerError::LayerSizeMismatch(..))
+        ));
+        assert!(tilemap_builder
+            .add_layer(TilemapLayer::new_sparse_empty(4, 4), DeclaredLayers::Items)
+            .is_ok());
+    }
+
+    #[test]
+    fn builder_palette_main_layer() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(
+            Commands,
+            TilemapManager<TileData, MapLayers, SquareChunkLayer<TileData>, SquareMapData>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let data: Vec<Vec<TileData>> = (0..6u8)
+            .map(|y| (0..6u8).map(|x| TileData((x + y) % 3)).collect())
+            .collect();
+        let mut tilemap_builder =
+            TilemapBuilder::<TileData, MapLayers, SquareChunkLayer<TileData>, SquareMapData>::new(
+                TilemapLayer::new_palette_from_vecs(data),
+                SquareMapData {
+                    max_chunk_size: UVec2::new(4, 4),
+                },
+                SquareChunkSettings {
+                    max_chunk_size: UVec2::new(4, 4),
+                },
+            )
+            .unwrap();
+        tilemap_builder.set_origin(ChunkPos::new(-1, -1)).unwrap();
+
+        let map_entity = tilemap_builder
+            .spawn_tilemap(&mut commands)
+            .expect("Tilemap should spawn");
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(6, 6));
+        for y in 0..6 {
+            for x in 0..6 {
+                assert_eq!(
+                    tilemap_manager
+                        .get_tile_data(Cell::new(x - 4, y - 4))
+                        .unwrap(),
+                    &TileData(((x + y) % 3) as u8)
+                );
+            }
+        }
+
+        tilemap_manager
+            .sets_tile_data(TileData(9), Cell::new(1, 1))
+            .unwrap();
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(1, 1)).unwrap(),
+            &TileData(9)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(0, 1)).unwrap(),
+            &TileData(0)
+        );
+    }
+
+    #[test]
+    fn builder_typed_layers() {
+        #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+        enum TypedLayers {
+            #[default]
+            Terrain,
+            Height,
+            Ownership,
+            Claims,
+        }
+
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Hash)]
+        struct Height(u16);
+
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(
+            Commands,
+            SquareTilemapManager<TileData, TypedLayers>,
+            SquareTilemapManager<Height, TypedLayers>,
+            SquareTilemapManager<u32, TypedLayers>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, ..) = system_state.get_mut(&mut world);
+
+        let mut tilemap_builder = SquareTilemapBuilder::<TileData, TypedLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vec![vec![TileData(1); 6]; 6]),
+            SquareMapData {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+        )
+        .unwrap();
+        tilemap_builder.set_origin(ChunkPos::new(-1, -1)).unwrap();
+        tilemap_builder
+            .add_typed_layer::<Height, SquareChunkLayer<Height>>(
+                TilemapLayer::new_dense_from_vecs(vec![vec![Height(300); 6]; 6]),
+                TypedLayers::Height,
+            )
+            .unwrap();
+        let mut ownership: HashMap<Cell, u32> = HashMap::new();
+        ownership.insert(Cell::new(-4, -4), 7);
+        let mut ownership_layer = TilemapLayer::new_sparse_from_hashmap(6, 6, ownership);
+        let owner_entity = commands.spawn_empty().id();
+        if let TilemapLayer::Sparse(.., entities) = &mut ownership_layer {
+            entities.insert(Cell::new(-4, -4), owner_entity);
+        }
+        tilemap_builder
+            .add_typed_layer::<u32, SquareChunkLayer<u32>>(ownership_layer, TypedLayers::Ownership)
+            .unwrap();
+        tilemap_builder
+            .add_typed_layer::<u32, SquareChunkLayer<u32>>(
+                TilemapLayer::new_dense_from_vecs(vec![vec![2; 6]; 6]),
+                TypedLayers::Claims,
+            )
+            .unwrap();
+        tilemap_builder.set_typed_default_tile_data(TypedLayers::Ownership, 0u32);
+        tilemap_builder.set_typed_conversion_policy::<u32>(
+            TypedLayers::Ownership,
+            ConversionPolicy::default(),
+        );
+
+        let map_entity = tilemap_builder
+            .spawn_tilemap(&mut commands)
+            .expect("Tilemap should spawn");
+        system_state.apply(&mut world);
+
+        let (_, mut terrain, mut heights, mut owners) = system_state.get_mut(&mut world);
+        terrain.set_tilemap_entity(map_entity);
+        heights.set_tilemap_entity(map_entity);
+        owners.set_tilemap_entity(map_entity);
+
+        assert_eq!(
+            terrain.get_tile_data(Cell::new(-4, -4)).unwrap(),
+            &TileData(1)
+        );
+
+        heights.set_layer(TypedLayers::Height);
+        assert_eq!(heights.dimensions().unwrap(), UVec2::new(6, 6));
+        assert_eq!(
+            heights.get_tile_data(Cell::new(1, 1)).unwrap(),
+            &Height(300)
+        );
+        heights.sets_tile_data(Height(12), Cell::new(1, 1)).unwrap();
+        assert_eq!(heights.get_tile_data(Cell::new(1, 1)).unwrap(), &Height(12));
+
+        owners.set_layer(TypedLayers::Ownership);
+        assert_eq!(owners.get_tile_data(Cell::new(-4, -4)).unwrap(), &7);
+        assert!(owners.get_tile_data(Cell::new(-3, -4)).is_err());
+        assert_eq!(
+            owners.get_tile_entity(Cell::new(-4, -4)).unwrap(),
+            owner_entity
+        );
+        let chunk = owners.get_chunk(ChunkPos::new(-1, -1)).unwrap();
+        assert_eq!(chunk.layer_count(), 2);
+        assert_eq!(
+            chunk
+                .layer(TypedLayers::Ownership.to_index())
+                .unwrap()
+                .conversion_policy(),
+            Some(ConversionPolicy::default())
+        );
+        owners.set_layer(TypedLayers::Claims);
+        assert_eq!(owners.get_tile_data(Cell::new(-3, -4)).unwrap(), &2);
+
+        // Pointing a manager at a layer of another TileData is an error rather than a panic
+        owners.set_layer(TypedLayers::Height);
+        assert!(matches!(
+            owners.get_tile_data(Cell::new(1, 1)),
+            Err(TilemapManagerError::LayerTileDataMismatch)
+        ));
+        heights.set_layer(TypedLayers::Claims);
+        assert!(matches!(
+            heights.sets_tile_data(Height(1), Cell::new(1, 1)),
+            Err(TilemapManagerError::LayerTileDataMismatch)
+        ));
+
+        let chunk = terrain.get_chunk(ChunkPos::new(0, 0)).unwrap();
+        assert_eq!(chunk.layer_count(), 1);
+        assert_eq!(
+            world.get::<TileEntity>(owner_entity).unwrap().layer,
+            TypedLayers::Ownership.to_index()
+        );
+    }
 }
@@ -1,6 +1,7 @@
 ﻿//! This module is specifically for making Tilemaps and helps to give ways to make Tilemap global layers
 //! and then convert those into chunks
 
+use crate::map::chunk::{ChunkLayerStorage, TileDataEq};
 use bevy::math::UVec2;
 use bevy::prelude::{Bundle, Commands, Entity};
 use bevy::utils::hashbrown::HashMap;
@@ -12,7 +13,7 @@ use lettuces::cell::Cell;
 #[derive(Clone, Debug)]
 pub enum TilemapLayer<T>
 where
-    T: Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// A layer where ***NOT*** every position on the chunk has data
     ///
@@ -25,11 +26,16 @@ where
     Sparse(HashMap<Cell, T>, UVec2, HashMap<Cell, Entity>),
     /// A layer where ***EVERY***  position on the chunk must have data
     Dense(Vec<Vec<T>>, HashMap<Cell, Entity>),
+    /// A layer where ***EVERY***  position on the chunk must have data, stored in each chunk as a
+    /// [`PaletteStorage`](crate::map::chunk::PaletteStorage) that compares tiles with the given [`TileDataEq`]
+    ///
+    /// Use for dense layers that only contain a handful of distinct values
+    Palette(Vec<Vec<T>>, TileDataEq<T>, HashMap<Cell, Entity>),
 }
 
 impl<T> Default for TilemapLayer<T>
 where
-    T: Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     fn default() -> Self {
         Self::Dense(vec![vec![]], HashMap::default())
@@ -38,13 +44,33 @@ where
 
 impl<T> TilemapLayer<T>
 where
-    T: Clone + Copy + Sized + Default + Send + Sync,
+    T: Clone + Sized + Send + Sync,
 {
     /// Returns the dimensions of the layer
     pub fn dimensions(&self) -> UVec2 {
         match self {
             TilemapLayer::Sparse(_, dimensions, ..) => *dimensions,
-            TilemapLayer::Dense(data, ..) => UVec2::new(data[0].len() as u32, data.len() as u32),
+            TilemapLayer::Dense(data, ..) | TilemapLayer::Palette(data, ..) => {
+                UVec2::new(data[0].len() as u32, data.len() as u32)
+            }
+        }
+    }
+
+    /// Returns the [`ChunkLayerStorage`] that the chunks of the layer are created with
+    pub fn storage(&self) -> ChunkLayerStorage {
+        match self {
+            TilemapLayer::Sparse(..) => ChunkLayerStorage::Sparse,
+            TilemapLayer::Dense(..) => ChunkLayerStorage::Dense,
+            TilemapLayer::Palette(..) => ChunkLayerStorage::Palette,
+        }
+    }
+
+    /// Returns the entities that were spawned for tiles in the layer
+    pub fn entities(&self) -> &HashMap<Cell, Entity> {
+        match self {
+            TilemapLayer::Sparse(.., entities)
+            | TilemapLayer::Dense(_, entities)
+            | TilemapLayer::Palette(.., entities) => entities,
         }
     }
 
@@ -72,26 +98,20 @@ where
 
     /// Creates a new [`TilemapLayer::Dense`] with all the tiles having the same data as the default
     /// for T
-    pub fn new_dense_default(tile_map_size_x: usize, tile_map_size_y: usize) -> Self {
-        let mut y_vec: Vec<Vec<T>> = Vec::with_capacity(tile_map_size_y);
-        for _ in 0..tile_map_size_y {
-            let mut x_vec = Vec::with_capacity(tile_map_size_x);
-            x_vec.fill(T::default());
-            y_vec.push(x_vec);
-        }
-        Self::Dense(y_vec, HashMap::default())
+    pub fn new_dense_default(tile_map_size_x: usize, tile_map_size_y: usize) -> Self
+    where
+        T: Default,
+    {
+        Self::new_dense_uniform(tile_map_size_x, tile_map_size_y, T::default())
     }
 
     /// Creates a new [`TilemapLayer::Dense`] with all the tiles having the same data as the given
     /// tile_data
     pub fn new_dense_uniform(tile_map_size_x: usize, tile_map_size_y: usize, tile_data: T) -> Self {
-        let mut y_vec: Vec<Vec<T>> = Vec::with_capacity(tile_map_size_y);
-        for _ in 0..tile_map_size_y {
-            let mut x_vec = Vec::with_capacity(tile_map_size_x);
-            x_vec.fill(tile_data);
-            y_vec.push(x_vec);
-        }
-        Self::Dense(y_vec, HashMap::default())
+        Self::Dense(
+            vec![vec![tile_data; tile_map_size_x]; tile_map_size_y],
+            HashMap::default(),
+        )
     }
 
     /// Creates a new [`TilemapLayer::Dense`] from the given vectors of vectors of T
@@ -108,16 +128,34 @@ where
         );
 
         let mut y_vec: Vec<Vec<T>> = Vec::with_capacity(tile_data.len());
-        for y in 0..tile_data.len() {
-            let mut x_vec = Vec::with_capacity(tile_data[0].len());
-            for x in 0..tile_data[0].len() {
-                x_vec.push(tile_data[y][x]);
+        for row in tile_data.iter() {
+            let mut x_vec = Vec::with_capacity(row.len());
+            for tile in row.iter() {
+                x_vec.push(tile.clone());
             }
             y_vec.push(x_vec);
         }
         Self::Dense(y_vec, HashMap::default())
     }
 
+    /// Creates a new [`TilemapLayer::Palette`] from the given vectors of vectors of T
+    pub fn new_palette_from_vecs(tile_data: Vec<Vec<T>>) -> Self
+    where
+        T: PartialEq,
+    {
+        Self::new_palette_from_vecs_with(tile_data, T::eq)
+    }
+
+    /// Creates a new [`TilemapLayer::Palette`] from the given vectors of vectors of T, that compares tiles with the
+    /// given [`TileDataEq`]
+    pub fn new_palette_from_vecs_with(tile_data: Vec<Vec<T>>, tile_data_eq: TileDataEq<T>) -> Self {
+        let given_tile_count: usize = tile_data.iter().map(|row| row.len()).sum();
+
+        assert_eq!(tile_data[0].len() * tile_data.len(), given_tile_count);
+
+        Self::Palette(tile_data, tile_data_eq, HashMap::default())
+    }
+
     /// Spawns an entity at the given [`Cell`] with the given [`Bundle`]
     pub fn spawn_entity_at_tile_pos<B: Bundle>(
         &mut self,
@@ -131,7 +169,7 @@ where
             TilemapLayer::Sparse(_, _, entities) => {
                 entities.insert(cell, entity);
             }
-            TilemapLayer::Dense(_, entities) => {
+            TilemapLayer::Dense(_, entities) | TilemapLayer::Palette(.., entities) => {
                 entities.insert(cell, entity);
             }
         }
@@ -1,4 +1,8 @@
 ﻿use bevy::ecs::query::QueryEntityError;
+use bevy::math::UVec2;
+use lettuces::cell::Cell;
+
+use crate::map::chunk::{ChunkLayerStorage, ChunkPos};
 
 /// Errors returned by a [`super::TilemapManager`]
 #[derive(thiserror::Error, Debug)]
@@ -7,10 +11,22 @@ pub enum TilemapManagerError {
     #[error("A Chunk does not exist for the given ChunkPos")]
     InvalidChunkPos,
 
+    /// The chunk at the given [`ChunkPos`] is inside the map but is not currently loaded
+    #[error("The Chunk at ChunkPos {0} is not loaded")]
+    ChunkNotLoaded(ChunkPos),
+
     /// A chunk does not exist for the given [`ChunkPos`](crate::map::chunk::ChunkPos)
     #[error("A Chunk entity does not exist for the given ChunkPos")]
     ChunkEntityDoesNotExist(#[from] QueryEntityError),
 
+    /// The [`Cell`] is not part of the shape of the map, see [`MapData::contains_cell`](crate::map::MapData::contains_cell)
+    #[error("The Cell {0:?} is outside of the shape of the map")]
+    CellOutsideMapShape(Cell),
+
+    /// The [`Cell`] is past the edge of the map, in the part of an edge chunk that is smaller than the max chunk size
+    #[error("The Cell {0:?} is past the edge of the map")]
+    CellOutsideMap(Cell),
+
     /// A tile entity does not exist for the given [`ChunkCell`](crate::map::chunk::ChunkCell)
     #[error("An Entity does not exist for the given ChunkCell")]
     TileEntityDoesNotExist,
@@ -18,4 +34,54 @@ pub enum TilemapManagerError {
     /// `TileData` does not exist for the given [`ChunkCell`](crate::map::chunk::ChunkCell)
     #[error("TileData does not exist for the given ChunkCell")]
     TileDataDoesNotExist,
+
+    /// The given map size has a zero dimension
+    #[error("The map size must be larger than zero")]
+    InvalidMapSize,
+
+    /// The given maximum chunk size has a zero dimension or does not line up with the first cell of the map
+    #[error("The chunk size must be larger than zero and line up with the first cell of the map")]
+    InvalidChunkSize,
+
+    /// The layer being added already exists in the tilemap
+    #[error("The layer already exists in the tilemap")]
+    LayerAlreadyExists,
+
+    /// The layer does not exist in any loaded chunk of the tilemap
+    #[error("The layer does not exist in the tilemap")]
+    LayerDoesNotExist,
+
+    /// The layer is a typed layer that stores a different `TileData` than the
+    /// [`TilemapManager`](super::TilemapManager), see
+    /// [`TilemapBuilder::add_typed_layer`](crate::tilemap_builder::TilemapBuilder::add_typed_layer)
+    #[error("The layer stores a different TileData than the TilemapManager")]
+    LayerTileDataMismatch,
+
+    /// The layer being added is not the same size as the tilemap
+    #[error("The layer is {1} cells but the tilemap is {0} cells")]
+    LayerSizeMismatch(UVec2, UVec2),
+
+    /// The size or first chunk of the tilemap does not fit the shape of its [`MapData`](crate::map::MapData), see
+    /// [`MapData::check_layout`](crate::map::MapData::check_layout)
+    #[error("A map of {0} cells starting at ChunkPos {1} does not fit the shape of the map")]
+    MapShapeMismatch(UVec2, ChunkPos),
+
+    /// Layers can't be added to a streamed tilemap, its chunks get their layers from its
+    /// [`ChunkSource`](crate::streaming::ChunkSource)
+    #[error("Layers can't be added to a streamed tilemap")]
+    StreamedTilemapLayer,
+
+    /// The layer being added does not use the storage declared on its [`MapLayer`](crate::map::MapLayer)
+    #[error("The layer was declared with {0:?} storage but was supplied with {1:?} storage")]
+    LayerStorageMismatch(ChunkLayerStorage, ChunkLayerStorage),
+
+    /// The tilemap has typed layers but does not know their `TileData` types to rebuild them together with the other
+    /// layers, eg because it was deserialized instead of spawned by the
+    /// [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder)
+    #[error("The tilemap has typed layers of unknown types and can not be resized or rechunked")]
+    TypedLayersExist,
+
+    /// Removing the layer would leave a chunk without any layers
+    #[error("A Chunk must keep at least one layer")]
+    LastLayer,
 }
@@ -1,9 +1,15 @@
-﻿use bevy::prelude::{Entity, Resource};
+use bevy::prelude::{Entity, Resource};
 
 mod errors;
+mod resize;
+mod tile_reader;
+#[allow(clippy::module_inception)]
 mod tilemap_manager;
 
 pub use errors::TilemapManagerError;
+pub(crate) use resize::ChunkRebuilders;
+pub use resize::MapAnchor;
+pub(crate) use tile_reader::{chunk_contains_cell, TileReader};
 pub use tilemap_manager::TilemapManager;
 
 /// A local resource for the tilemap manager that holds the currently selected map layer
@@ -11,11 +17,5 @@ pub use tilemap_manager::TilemapManager;
 pub(crate) struct LayerIndex<MapLayer>(pub(crate) MapLayer);
 
 /// A local resource for the tilemap that holds the map entity that the tilemap manager is working with
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub(crate) struct MapEntity(pub(crate) Option<Entity>);
-
-impl Default for MapEntity {
-    fn default() -> Self {
-        Self(None)
-    }
-}
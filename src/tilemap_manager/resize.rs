@@ -0,0 +1,841 @@
+use crate::map::chunk::{
+    Chunk, ChunkLayer, ChunkLayerStorage, ChunkLayerType, ChunkPos, Chunks, ConversionPolicy,
+    TileDataEq,
+};
+use crate::map::{MapData, MapLayer, TileEntity, Tilemap};
+use bevy::hierarchy::{BuildWorldChildren, DespawnRecursiveExt};
+use bevy::math::UVec2;
+use bevy::prelude::{Component, Entity, World};
+use bevy::utils::HashMap;
+use std::any::TypeId;
+
+/// The point of a map that stays in place when the map is resized with
+/// [`TilemapManager::resize`](crate::tilemap_manager::TilemapManager::resize).
+///
+/// The bottom of the map is the row with the lowest y and the left of the map is the column with the lowest x
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum MapAnchor {
+    /// Rows and columns are added or removed at the top and right of the map
+    #[default]
+    BottomLeft,
+    /// Rows are added or removed at the top of the map, columns on both sides
+    Bottom,
+    /// Rows are added or removed at the top of the map, columns on the left
+    BottomRight,
+    /// Rows are added or removed on both sides of the map, columns on the right
+    Left,
+    /// Rows and columns are added or removed on both sides of the map
+    Center,
+    /// Rows are added or removed on both sides of the map, columns on the left
+    Right,
+    /// Rows are added or removed at the bottom of the map, columns on the right
+    TopLeft,
+    /// Rows are added or removed at the bottom of the map, columns on both sides
+    Top,
+    /// Rows and columns are added or removed at the bottom and left of the map
+    TopRight,
+}
+
+impl MapAnchor {
+    /// Returns the `[column, row]` offset that existing tiles move by when a map of the given size is resized to the
+    /// new size
+    pub fn offset(&self, map_size: UVec2, new_map_size: UVec2) -> [i32; 2] {
+        let difference = [
+            new_map_size.x as i32 - map_size.x as i32,
+            new_map_size.y as i32 - map_size.y as i32,
+        ];
+        let column = match self {
+            MapAnchor::BottomLeft | MapAnchor::Left | MapAnchor::TopLeft => 0,
+            MapAnchor::Bottom | MapAnchor::Center | MapAnchor::Top => difference[0] / 2,
+            MapAnchor::BottomRight | MapAnchor::Right | MapAnchor::TopRight => difference[0],
+        };
+        let row = match self {
+            MapAnchor::BottomLeft | MapAnchor::Bottom | MapAnchor::BottomRight => 0,
+            MapAnchor::Left | MapAnchor::Center | MapAnchor::Right => difference[1] / 2,
+            MapAnchor::TopLeft | MapAnchor::Top | MapAnchor::TopRight => difference[1],
+        };
+        [column, row]
+    }
+}
+
+/// The contents of one layer of every chunk of a tilemap, keyed by offset coordinates
+struct LayerContents<TileData> {
+    /// How many chunks use each storage, in the order the storages were first seen
+    storage_counts: Vec<(ChunkLayerStorage, usize)>,
+    conversion_policy: Option<ConversionPolicy>,
+    track_dirty_cells: bool,
+    default_tile_data: Option<TileData>,
+    tile_data_eq: Option<TileDataEq<TileData>>,
+    tiles: Vec<([i32; 2], TileData)>,
+    tile_entities: Vec<([i32; 2], Entity)>,
+}
+
+/// Takes the chunks of one `TileData` type out of a tilemap and returns the function that puts them back into the
+/// new layout of the tilemap
+type ChunkRebuilder = fn(&mut World, &ChunkLayout) -> PlaceChunks;
+
+/// Puts the chunks taken out by a [`ChunkRebuilder`] back into the new layout of the tilemap
+type PlaceChunks = Box<dyn FnOnce(&mut World, &ChunkLayout) + Send>;
+
+/// The chunk components of every `TileData` type of a tilemap, so that all of them are rebuilt when the tilemap is
+/// resized or rechunked.
+///
+/// Added to the tilemap by the [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder) and
+/// [`TilemapManager::add_layer`](crate::tilemap_manager::TilemapManager::add_layer)
+#[derive(Component, Clone, Default)]
+pub(crate) struct ChunkRebuilders(Vec<(TypeId, ChunkRebuilder)>);
+
+impl ChunkRebuilders {
+    /// Adds the `Chunk<MapChunk, TileData>` components to the chunks that are rebuilt
+    pub(crate) fn register<TileData, MapLayers, MapChunk, Map>(&mut self)
+    where
+        TileData: Clone + Sized + Send + Sync + 'static,
+        MapLayers: MapLayer + 'static,
+        MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+        Map: MapData,
+    {
+        let type_id = TypeId::of::<Chunk<MapChunk, TileData>>();
+        if self.0.iter().all(|(registered, _)| *registered != type_id) {
+            self.0.push((
+                type_id,
+                |world: &mut World, layout: &ChunkLayout| -> PlaceChunks {
+                    Box::new(take_chunks::<TileData, MapLayers, MapChunk, Map>(
+                        world, layout, None,
+                    ))
+                },
+            ));
+        }
+    }
+}
+
+/// Adds the `Chunk<MapChunk, TileData>` components to the chunks that are rebuilt along with the given tilemap
+pub(crate) fn register_chunk_rebuilder<TileData, MapLayers, MapChunk, Map>(
+    world: &mut World,
+    tilemap_entity: Entity,
+) where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    let Some(mut tilemap_entity) = world.get_entity_mut(tilemap_entity) else {
+        return;
+    };
+    let mut rebuilders = tilemap_entity.take::<ChunkRebuilders>().unwrap_or_default();
+    rebuilders.register::<TileData, MapLayers, MapChunk, Map>();
+    tilemap_entity.insert(rebuilders);
+}
+
+/// The old and new layout of a tilemap that is being rebuilt
+pub(crate) struct ChunkLayout {
+    tilemap_entity: Entity,
+    old_map_size: UVec2,
+    map_size: UVec2,
+    max_chunk_size: UVec2,
+    offset: [i32; 2],
+    /// The offset coordinates of the first cell of the map
+    first_cell: [i32; 2],
+    origin: ChunkPos,
+    chunk_counts: UVec2,
+    /// The chunk entity of every new chunk, row by row
+    chunk_entities: Vec<Entity>,
+}
+
+impl ChunkLayout {
+    fn chunk_total(&self) -> usize {
+        (self.chunk_counts.x * self.chunk_counts.y) as usize
+    }
+
+    fn chunk_pos(&self, index: usize) -> ChunkPos {
+        ChunkPos::new(
+            self.origin.x() + (index as u32 % self.chunk_counts.x) as i32,
+            self.origin.y() + (index as u32 / self.chunk_counts.x) as i32,
+        )
+    }
+
+    fn chunk_dimensions(&self, index: usize) -> UVec2 {
+        let x = index as u32 % self.chunk_counts.x;
+        let y = index as u32 / self.chunk_counts.x;
+        UVec2::new(
+            self.max_chunk_size
+                .x
+                .min(self.map_size.x - x * self.max_chunk_size.x),
+            self.max_chunk_size
+                .y
+                .min(self.map_size.y - y * self.max_chunk_size.y),
+        )
+    }
+
+    /// Returns the index of the chunk and the `[column, row]` inside of it for the given offset coordinates
+    fn locate(&self, [column, row]: [i32; 2]) -> Option<(usize, [usize; 2])> {
+        let column = column - self.first_cell[0];
+        let row = row - self.first_cell[1];
+        if column < 0
+            || row < 0
+            || column as u32 >= self.map_size.x
+            || row as u32 >= self.map_size.y
+        {
+            return None;
+        }
+        let index = (row as u32 / self.max_chunk_size.y) * self.chunk_counts.x
+            + column as u32 / self.max_chunk_size.x;
+        Some((
+            index as usize,
+            [
+                (column as u32 % self.max_chunk_size.x) as usize,
+                (row as u32 % self.max_chunk_size.y) as usize,
+            ],
+        ))
+    }
+
+    /// Returns true if every cell of the new map held a tile of the old map
+    fn covered_by_old_map(&self) -> bool {
+        self.offset[0] <= 0
+            && self.offset[1] <= 0
+            && self.old_map_size.x as i32 + self.offset[0] >= self.map_size.x as i32
+            && self.old_map_size.y as i32 + self.offset[1] >= self.map_size.y as i32
+    }
+}
+
+/// Redistributes every layer of the tilemap, which covers `old_map_size` cells, into new chunks covering `map_size`
+/// cells using the given maximum chunk size.
+///
+/// Tiles are moved by `offset` in offset coordinates. Tiles and tile entities that end up outside of the map are
+/// dropped and despawned, new tiles in dense and palette layers are filled with `fill`, or the default tile data of
+/// the layer if `fill` is `None`. Chunk entities are reused for every [`ChunkPos`] that is still inside of the map.
+///
+/// The chunks of every other `TileData` type in the [`ChunkRebuilders`] of the tilemap are rebuilt the same way,
+/// filling new tiles with the default tile data of their layers.
+///
+/// Each layer is rebuilt with the storage declared on its [`MapLayer`], or the storage used by most of its chunks if
+/// none is declared. Layers that have no tile data to fill new dense or palette tiles with are rebuilt as sparse
+/// layers.
+pub(crate) fn rebuild_tilemap<TileData, MapLayers, MapChunk, Map>(
+    world: &mut World,
+    tilemap_entity: Entity,
+    old_map_size: UVec2,
+    map_size: UVec2,
+    max_chunk_size: UVec2,
+    offset: [i32; 2],
+    fill: Option<TileData>,
+) where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    let Some(tilemap) = world.get::<Tilemap>(tilemap_entity).cloned() else {
+        return;
+    };
+    let old_max_chunk_size = tilemap.get_chunks_max_size();
+    let first_cell = [
+        tilemap.chunks().origin().x() * old_max_chunk_size.x as i32,
+        tilemap.chunks().origin().y() * old_max_chunk_size.y as i32,
+    ];
+    let chunk_counts = UVec2::new(
+        map_size.x.div_ceil(max_chunk_size.x),
+        map_size.y.div_ceil(max_chunk_size.y),
+    );
+    let mut layout = ChunkLayout {
+        tilemap_entity,
+        old_map_size,
+        map_size,
+        max_chunk_size,
+        offset,
+        first_cell,
+        origin: ChunkPos::new(
+            first_cell[0].div_euclid(max_chunk_size.x as i32),
+            first_cell[1].div_euclid(max_chunk_size.y as i32),
+        ),
+        chunk_counts,
+        chunk_entities: vec![],
+    };
+
+    // Take the data out of every chunk before the chunks are rebuilt, while the map still has the old chunk size
+    let rebuilders = world
+        .get::<ChunkRebuilders>(tilemap_entity)
+        .cloned()
+        .unwrap_or_default();
+    let type_id = TypeId::of::<Chunk<MapChunk, TileData>>();
+    let mut place_chunks: Vec<PlaceChunks> =
+        vec![Box::new(take_chunks::<TileData, MapLayers, MapChunk, Map>(
+            world, &layout, fill,
+        ))];
+    for (_, rebuilder) in rebuilders
+        .0
+        .iter()
+        .filter(|(registered, _)| *registered != type_id)
+    {
+        place_chunks.push(rebuilder(world, &layout));
+    }
+
+    let Some(mut map) = world.get_mut::<Map>(tilemap_entity) else {
+        return;
+    };
+    map.set_max_chunk_size(max_chunk_size);
+    let mut old_chunk_entities: HashMap<ChunkPos, Entity> =
+        tilemap.chunks().loaded_chunks().collect();
+    let mut new_chunks = Chunks::new_unloaded(chunk_counts, max_chunk_size, layout.origin);
+    for index in 0..layout.chunk_total() {
+        let chunk_pos = layout.chunk_pos(index);
+        let chunk_entity = match old_chunk_entities.remove(&chunk_pos) {
+            Some(chunk_entity) if world.get_entity(chunk_entity).is_some() => chunk_entity,
+            _ => {
+                let chunk_entity = world.spawn_empty().id();
+                world.entity_mut(tilemap_entity).add_child(chunk_entity);
+                chunk_entity
+            }
+        };
+        new_chunks.set_chunk(chunk_pos, Some(chunk_entity));
+        layout.chunk_entities.push(chunk_entity);
+    }
+    for place_chunks in place_chunks {
+        place_chunks(world, &layout);
+    }
+    for chunk_entity in old_chunk_entities.into_values() {
+        if let Some(entity) = world.get_entity_mut(chunk_entity) {
+            entity.despawn_recursive();
+        }
+    }
+    if let Some(mut tilemap) = world.get_mut::<Tilemap>(tilemap_entity) {
+        *tilemap.chunks_mut() = new_chunks;
+    }
+}
+
+/// Takes every `Chunk<MapChunk, TileData>` out of the old chunks of the tilemap and returns the function that
+/// rebuilds them in the new layout, see [`rebuild_tilemap`]
+fn take_chunks<TileData, MapLayers, MapChunk, Map>(
+    world: &mut World,
+    layout: &ChunkLayout,
+    fill: Option<TileData>,
+) -> impl FnOnce(&mut World, &ChunkLayout) + Send
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    let offset = layout.offset;
+    let mut layers: HashMap<usize, LayerContents<TileData>> = HashMap::new();
+    let mut chunk_settings = None;
+    let chunk_entities: Vec<(ChunkPos, Entity)> = world
+        .get::<Tilemap>(layout.tilemap_entity)
+        .map(|tilemap| tilemap.chunks().loaded_chunks().collect())
+        .unwrap_or_default();
+    for (chunk_pos, chunk_entity) in chunk_entities {
+        let Some(chunk) = world
+            .get_entity_mut(chunk_entity)
+            .and_then(|mut entity| entity.take::<Chunk<MapChunk, TileData>>())
+        else {
+            continue;
+        };
+        let Some(map) = world.get::<Map>(layout.tilemap_entity) else {
+            break;
+        };
+        chunk_settings = Some(chunk.chunk_settings);
+        for (map_layer, layer) in chunk.data.into_iter().enumerate() {
+            let Some(mut layer) = layer else {
+                continue;
+            };
+            let contents = layers.entry(map_layer).or_insert_with(|| LayerContents {
+                storage_counts: vec![],
+                conversion_policy: layer.conversion_policy(),
+                track_dirty_cells: layer.tracks_dirty_cells(),
+                default_tile_data: layer.default_tile_data().cloned(),
+                tile_data_eq: layer.tile_data_eq(),
+                tiles: vec![],
+                tile_entities: vec![],
+            });
+            match contents
+                .storage_counts
+                .iter_mut()
+                .find(|(storage, _)| *storage == layer.storage())
+            {
+                Some((_, count)) => *count += 1,
+                None => contents.storage_counts.push((layer.storage(), 1)),
+            }
+            let moved_offset_coordinates = |chunk_cell| {
+                let [column, row] =
+                    map.cell_to_offset_coordinates(map.into_cell(chunk_pos, chunk_cell));
+                [column + offset[0], row + offset[1]]
+            };
+            contents.tile_entities.extend(
+                layer
+                    .iter_tile_entities()
+                    .map(|(chunk_cell, entity)| (moved_offset_coordinates(chunk_cell), entity)),
+            );
+            let tiles = &mut contents.tiles;
+            layer.for_each_mut(&mut |chunk_cell, tile_data| {
+                tiles.push((moved_offset_coordinates(chunk_cell), tile_data.clone()));
+            });
+        }
+    }
+
+    move |world: &mut World, layout: &ChunkLayout| {
+        let Some(chunk_settings) = chunk_settings else {
+            return;
+        };
+        place_chunks::<TileData, MapLayers, MapChunk, Map>(
+            world,
+            layout,
+            layers,
+            MapChunk::with_max_chunk_size(chunk_settings, layout.max_chunk_size),
+            fill,
+        );
+    }
+}
+
+/// Rebuilds the given layers into chunks in the new layout of the tilemap and inserts them onto the new chunk
+/// entities
+fn place_chunks<TileData, MapLayers, MapChunk, Map>(
+    world: &mut World,
+    layout: &ChunkLayout,
+    layers: HashMap<usize, LayerContents<TileData>>,
+    chunk_settings: MapChunk::ChunkSettings,
+    fill: Option<TileData>,
+) where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapLayers: MapLayer + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    let Some(map) = world.get::<Map>(layout.tilemap_entity) else {
+        return;
+    };
+    let mut chunks: Vec<Chunk<MapChunk, TileData>> = (0..layout.chunk_total())
+        .map(|index| {
+            let mut chunk = Chunk::default();
+            chunk.chunk_pos = layout.chunk_pos(index);
+            chunk.chunk_settings = chunk_settings;
+            chunk
+        })
+        .collect();
+    let mut moved_tile_entities = vec![];
+    let mut dropped_tile_entities = vec![];
+    for (map_layer, contents) in layers {
+        // Chunks only differ in storage after being converted by their conversion policy, which will convert the
+        // rebuilt chunks again as they are modified
+        let storage = MapLayers::from_index(map_layer)
+            .and_then(|layer| layer.storage())
+            .or_else(|| {
+                contents
+                    .storage_counts
+                    .iter()
+                    .rev()
+                    .max_by_key(|(_, count)| *count)
+                    .map(|(storage, _)| *storage)
+            })
+            .unwrap_or(ChunkLayerStorage::Sparse);
+        // Without a fill or default tile data, the tiles of the old map can only stand in when they cover the new map
+        let fill = fill
+            .clone()
+            .or_else(|| contents.default_tile_data.clone())
+            .or_else(|| {
+                contents
+                    .tiles
+                    .first()
+                    .filter(|_| layout.covered_by_old_map())
+                    .map(|(_, tile_data)| tile_data.clone())
+            });
+        let storage = match (storage, &fill, contents.tile_data_eq) {
+            (ChunkLayerStorage::Dense, Some(_), _)
+            | (ChunkLayerStorage::Palette, Some(_), Some(_)) => storage,
+            _ => ChunkLayerStorage::Sparse,
+        };
+        let mut layer_types: Vec<ChunkLayerType<TileData>> = (0..layout.chunk_total())
+            .map(|index| {
+                let dimensions = layout.chunk_dimensions(index);
+                let filled_tiles = || match &fill {
+                    Some(fill) => {
+                        vec![vec![fill.clone(); dimensions.x as usize]; dimensions.y as usize]
+                    }
+                    None => vec![],
+                };
+                match (storage, contents.tile_data_eq) {
+                    (ChunkLayerStorage::Dense, _) => ChunkLayerType::Dense(filled_tiles()),
+                    (ChunkLayerStorage::Palette, Some(tile_data_eq)) => {
+                        ChunkLayerType::Palette(filled_tiles(), tile_data_eq)
+                    }
+                    _ => ChunkLayerType::Sparse(HashMap::new()),
+                }
+            })
+            .collect();
+        // Tiles missing from sparse chunks were empty rather than new so they get the default tile data instead of
+        // the fill
+        let had_sparse_chunks = contents
+            .storage_counts
+            .iter()
+            .any(|(storage, _)| *storage == ChunkLayerStorage::Sparse);
+        if let (true, Some(default_tile_data)) = (
+            storage != ChunkLayerStorage::Sparse && had_sparse_chunks,
+            &contents.default_tile_data,
+        ) {
+            for row in 0..layout.old_map_size.y as i32 {
+                for column in 0..layout.old_map_size.x as i32 {
+                    let Some((index, [column, row])) = layout.locate([
+                        layout.first_cell[0] + column + layout.offset[0],
+                        layout.first_cell[1] + row + layout.offset[1],
+                    ]) else {
+                        continue;
+                    };
+                    if let ChunkLayerType::Dense(tiles) | ChunkLayerType::Palette(tiles, _) =
+                        &mut layer_types[index]
+                    {
+                        tiles[row][column] = default_tile_data.clone();
+                    }
+                }
+            }
+        }
+        for (offset_coordinates, tile_data) in contents.tiles {
+            let Some((index, [column, row])) = layout.locate(offset_coordinates) else {
+                continue;
+            };
+            match &mut layer_types[index] {
+                ChunkLayerType::Dense(tiles) | ChunkLayerType::Palette(tiles, _) => {
+                    tiles[row][column] = tile_data;
+                }
+                ChunkLayerType::Sparse(tiles) => {
+                    let cell = map.offset_coordinates_to_cell(offset_coordinates);
+                    tiles.insert(MapChunk::into_chunk_cell(cell, &chunk_settings), tile_data);
+                }
+            }
+        }
+        for (index, layer_type) in layer_types.into_iter().enumerate() {
+            let mut layer =
+                MapChunk::new(layer_type, layout.chunk_dimensions(index), &chunk_settings);
+            layer.set_default_tile_data(contents.default_tile_data.clone());
+            layer.set_track_dirty_cells(contents.track_dirty_cells);
+            if let Some(tile_data_eq) = contents.tile_data_eq {
+                layer.set_conversion_policy_with(contents.conversion_policy, tile_data_eq);
+            }
+            chunks[index].insert_layer(map_layer, layer);
+        }
+        for (offset_coordinates, entity) in contents.tile_entities {
+            let Some((index, _)) = layout.locate(offset_coordinates) else {
+                dropped_tile_entities.push(entity);
+                continue;
+            };
+            let cell = map.offset_coordinates_to_cell(offset_coordinates);
+            chunks[index].set_tile_entity_from_cell(map_layer, cell, entity);
+            moved_tile_entities.push((entity, cell));
+        }
+    }
+
+    for (chunk, chunk_entity) in chunks.into_iter().zip(&layout.chunk_entities) {
+        if chunk.layer_count() > 0 {
+            world.entity_mut(*chunk_entity).insert(chunk);
+        }
+    }
+    for (entity, cell) in moved_tile_entities {
+        if let Some(mut tile_entity) = world.get_mut::<TileEntity>(entity) {
+            tile_entity.cell = cell;
+        }
+    }
+    for entity in dropped_tile_entities {
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::map::chunk::{ChunkLayer, ChunkLayerStorage, ChunkPos, ConversionPolicy};
+    use crate::map::TileEntity;
+    use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
+    use crate::square::map_data::SquareMapData;
+    use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
+    use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+    use crate::tilemap_manager::{ChunkRebuilders, MapAnchor, TilemapManagerError};
+    use bevy::ecs::system::{Commands, SystemState};
+    use bevy::math::UVec2;
+    use bevy::prelude::World;
+    use bevy::utils::HashMap;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
+    enum MapLayers {
+        #[default]
+        Main,
+        Secondary,
+        Height,
+    }
+
+    fn builder(
+        map_size: UVec2,
+        max_chunk_size: UVec2,
+    ) -> SquareTilemapBuilder<(i32, i32), MapLayers> {
+        let mut hashmap: HashMap<Cell, (i32, i32)> = HashMap::new();
+        hashmap.insert(Cell::new(0, 0), (1, 1));
+        hashmap.insert(Cell::new(3, 3), (9, 9));
+        let mut tilemap_builder = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(
+                (0..map_size.y as i32)
+                    .map(|y| (0..map_size.x as i32).map(|x| (x, y)).collect())
+                    .collect(),
+            ),
+            SquareMapData { max_chunk_size },
+            SquareChunkSettings { max_chunk_size },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(
+                    map_size.x as usize,
+                    map_size.y as usize,
+                    hashmap,
+                ),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+        tilemap_builder
+    }
+
+    #[test]
+    fn resize_moves_tiles_with_anchor() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let map_entity = builder(UVec2::new(4, 4), UVec2::new(3, 3))
+            .spawn_tilemap(&mut commands)
+            .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let cropped_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(0, 0))
+            .unwrap();
+        let moved_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(3, 3))
+            .unwrap();
+        assert!(matches!(
+            tilemap_manager.resize(UVec2::new(6, 0), MapAnchor::TopRight, (-1, -1)),
+            Err(TilemapManagerError::InvalidMapSize)
+        ));
+        // Grows two columns on the left and crops the bottom row
+        tilemap_manager
+            .resize(UVec2::new(6, 3), MapAnchor::TopRight, (-1, -1))
+            .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(6, 3));
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(2, 0)).unwrap(),
+            &(0, 1)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(5, 2)).unwrap(),
+            &(3, 3)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(1, 2)).unwrap(),
+            &(-1, -1)
+        );
+        assert!(tilemap_manager.get_tile_data(Cell::new(2, 3)).is_err());
+        assert_eq!(
+            tilemap_manager.get_tile_entity(Cell::new(5, 2)).unwrap(),
+            moved_entity
+        );
+
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(5, 2)).unwrap(),
+            &(9, 9)
+        );
+        assert!(tilemap_manager.get_tile_data(Cell::new(0, 0)).is_err());
+        assert_eq!(tilemap_manager.iter_layer().unwrap().count(), 1);
+
+        assert!(world.get_entity(cropped_entity).is_none());
+        assert_eq!(
+            world.get::<TileEntity>(moved_entity).unwrap().cell,
+            Cell::new(5, 2)
+        );
+    }
+
+    #[test]
+    fn resize_keeps_storage_of_mixed_layers() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let mut tilemap_builder = builder(UVec2::new(4, 4), UVec2::new(2, 2));
+        tilemap_builder.set_default_tile_data(MapLayers::Main, (0, 0));
+        tilemap_builder.set_conversion_policy(MapLayers::Main, ConversionPolicy::new(1.0, 0.5));
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        // Empties most of the top right chunk so it converts to sparse
+        for cell in [Cell::new(2, 2), Cell::new(3, 2), Cell::new(2, 3)] {
+            tilemap_manager.sets_tile_data((0, 0), cell).unwrap();
+        }
+        let storage = |tilemap_manager: &SquareTilemapManager<(i32, i32), MapLayers>, chunk_pos| {
+            tilemap_manager
+                .get_chunk(chunk_pos)
+                .unwrap()
+                .layer(0)
+                .unwrap()
+                .storage()
+        };
+        assert_eq!(
+            storage(&tilemap_manager, ChunkPos::new(1, 1)),
+            ChunkLayerStorage::Sparse
+        );
+        tilemap_manager
+            .resize(UVec2::new(5, 5), MapAnchor::BottomLeft, (-1, -1))
+            .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, tilemap_manager) = system_state.get_mut(&mut world);
+        for chunk_pos in [ChunkPos::new(1, 1), ChunkPos::new(2, 2)] {
+            assert_eq!(
+                storage(&tilemap_manager, chunk_pos),
+                ChunkLayerStorage::Dense
+            );
+        }
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(3, 3)).unwrap(),
+            &(3, 3)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(2, 2)).unwrap(),
+            &(0, 0)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(4, 4)).unwrap(),
+            &(-1, -1)
+        );
+    }
+
+    #[test]
+    fn resize_and_rechunk_typed_layers() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(
+            Commands,
+            SquareTilemapManager<(i32, i32), MapLayers>,
+            SquareTilemapManager<u32, MapLayers>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, ..) = system_state.get_mut(&mut world);
+        let mut tilemap_builder = builder(UVec2::new(4, 4), UVec2::new(2, 2));
+        tilemap_builder
+            .add_typed_layer::<u32, SquareChunkLayer<u32>>(
+                TilemapLayer::new_dense_from_vecs(vec![vec![5; 4]; 4]),
+                MapLayers::Height,
+            )
+            .unwrap();
+        tilemap_builder.set_typed_default_tile_data(MapLayers::Height, 0u32);
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager, mut heights) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        heights.set_tilemap_entity(map_entity);
+        heights.set_layer(MapLayers::Height);
+        tilemap_manager
+            .resize(UVec2::new(6, 5), MapAnchor::BottomLeft, (-1, -1))
+            .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, tilemap_manager, mut heights) = system_state.get_mut(&mut world);
+        assert_eq!(heights.dimensions().unwrap(), UVec2::new(6, 5));
+        assert_eq!(heights.get_tile_data(Cell::new(3, 3)).unwrap(), &5);
+        assert_eq!(heights.get_tile_data(Cell::new(5, 4)).unwrap(), &0);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(5, 4)).unwrap(),
+            &(-1, -1)
+        );
+        // Typed managers rebuild the chunks of the main tile data as well
+        heights.rechunk(UVec2::new(3, 3)).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, tilemap_manager, heights) = system_state.get_mut(&mut world);
+        assert!(heights.get_chunk(ChunkPos::new(1, 1)).is_ok());
+        assert!(heights.get_chunk(ChunkPos::new(2, 0)).is_err());
+        assert_eq!(heights.get_tile_data(Cell::new(3, 3)).unwrap(), &5);
+        assert_eq!(heights.iter_layer().unwrap().count(), 30);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(3, 3)).unwrap(),
+            &(3, 3)
+        );
+        assert_eq!(
+            tilemap_manager
+                .get_chunk(ChunkPos::new(1, 1))
+                .unwrap()
+                .get_chunk_dimensions(),
+            UVec2::new(3, 2)
+        );
+
+        // Tilemaps that don't know the types of their typed layers can't rebuild them
+        world.entity_mut(map_entity).remove::<ChunkRebuilders>();
+        let (_, mut tilemap_manager, mut heights) = system_state.get_mut(&mut world);
+        assert!(matches!(
+            tilemap_manager.resize(UVec2::new(6, 6), MapAnchor::BottomLeft, (-1, -1)),
+            Err(TilemapManagerError::TypedLayersExist)
+        ));
+        assert!(matches!(
+            heights.rechunk(UVec2::new(2, 2)),
+            Err(TilemapManagerError::TypedLayersExist)
+        ));
+    }
+
+    #[test]
+    fn rechunk_keeps_every_tile() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let map_entity = builder(UVec2::new(5, 4), UVec2::new(2, 2))
+            .spawn_tilemap(&mut commands)
+            .unwrap();
+        let mut offset_builder = builder(UVec2::new(6, 6), UVec2::new(2, 2));
+        offset_builder.set_origin(ChunkPos::new(-1, -1)).unwrap();
+        let offset_map_entity = offset_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        let tile_entity = tilemap_manager
+            .get_or_spawn_tile_entity(Cell::new(4, 3))
+            .unwrap();
+        assert!(matches!(
+            tilemap_manager.rechunk(UVec2::new(0, 3)),
+            Err(TilemapManagerError::InvalidChunkSize)
+        ));
+        tilemap_manager.rechunk(UVec2::new(3, 3)).unwrap();
+        tilemap_manager.set_tilemap_entity(offset_map_entity);
+        assert!(matches!(
+            tilemap_manager.rechunk(UVec2::new(3, 3)),
+            Err(TilemapManagerError::InvalidChunkSize)
+        ));
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(5, 4));
+        assert!(tilemap_manager.get_chunk(ChunkPos::new(1, 1)).is_ok());
+        assert!(tilemap_manager.get_chunk(ChunkPos::new(2, 0)).is_err());
+        let mut tile_count = 0;
+        for (cell, tile_data) in tilemap_manager.iter_layer().unwrap() {
+            assert_eq!(tile_data, &(cell.x, cell.y));
+            tile_count += 1;
+        }
+        assert_eq!(tile_count, 20);
+        assert_eq!(
+            tilemap_manager.get_tile_entity(Cell::new(4, 3)).unwrap(),
+            tile_entity
+        );
+
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(3, 3)).unwrap(),
+            &(9, 9)
+        );
+        assert_eq!(tilemap_manager.iter_layer().unwrap().count(), 2);
+    }
+}
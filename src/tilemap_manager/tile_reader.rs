@@ -0,0 +1,111 @@
+use crate::map::chunk::{Chunk, ChunkLayer, ChunkPos};
+use crate::map::{MapData, Tilemap};
+use bevy::math::UVec2;
+use bevy::prelude::Entity;
+use bevy::utils::HashMap;
+use lettuces::cell::Cell;
+
+/// Reads tiles straight out of the chunks of a [`Tilemap`] for algorithms that visit a lot of cells, looking up
+/// each chunk only once instead of querying the tilemap and chunk for every cell.
+pub(crate) struct TileReader<'a, TileData, MapChunk, Map>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    pub(crate) map: &'a Map,
+    tilemap: &'a Tilemap,
+    chunk_lookup: Box<dyn Fn(Entity) -> Option<&'a Chunk<MapChunk, TileData>> + 'a>,
+    chunks: HashMap<ChunkPos, Option<&'a Chunk<MapChunk, TileData>>>,
+}
+
+impl<'a, TileData, MapChunk, Map> TileReader<'a, TileData, MapChunk, Map>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    Map: MapData,
+{
+    pub(crate) fn new(
+        tilemap: &'a Tilemap,
+        map: &'a Map,
+        chunk_lookup: impl Fn(Entity) -> Option<&'a Chunk<MapChunk, TileData>> + 'a,
+    ) -> Self {
+        Self {
+            map,
+            tilemap,
+            chunk_lookup: Box::new(chunk_lookup),
+            chunks: HashMap::default(),
+        }
+    }
+
+    /// Returns the chunk at the given [`ChunkPos`] if it exists and is loaded
+    pub(crate) fn chunk(&mut self, chunk_pos: ChunkPos) -> Option<&'a Chunk<MapChunk, TileData>> {
+        if let Some(chunk) = self.chunks.get(&chunk_pos) {
+            return *chunk;
+        }
+        let chunk = self
+            .tilemap
+            .get_chunk(chunk_pos)
+            .and_then(|chunk_entity| (self.chunk_lookup)(chunk_entity));
+        self.chunks.insert(chunk_pos, chunk);
+        chunk
+    }
+
+    /// Returns whether the [`Cell`] is part of the map and in a loaded chunk
+    pub(crate) fn contains(&mut self, cell: Cell) -> bool {
+        if !self.map.contains_cell(cell) {
+            return false;
+        }
+        let chunk_pos = self.map.into_chunk_pos(cell);
+        let Some(chunk) = self.chunk(chunk_pos) else {
+            return false;
+        };
+        chunk_contains_cell(self.map, chunk_pos, chunk.get_chunk_dimensions(), cell)
+    }
+
+    /// Returns every [`Cell`] of the map in the loaded chunk at the given [`ChunkPos`]
+    pub(crate) fn chunk_cells(&mut self, chunk_pos: ChunkPos) -> Vec<Cell> {
+        let Some(chunk) = self.chunk(chunk_pos) else {
+            return vec![];
+        };
+        let dimensions = chunk.get_chunk_dimensions().as_ivec2();
+        let max_chunk_size = self.map.max_chunk_size().as_ivec2();
+        let (first_column, first_row) = (
+            chunk_pos.x() * max_chunk_size.x,
+            chunk_pos.y() * max_chunk_size.y,
+        );
+        (0..dimensions.y)
+            .flat_map(|row| {
+                (0..dimensions.x).map(move |column| [first_column + column, first_row + row])
+            })
+            .map(|offset_coordinates| self.map.offset_coordinates_to_cell(offset_coordinates))
+            .filter(|cell| self.map.contains_cell(*cell))
+            .collect()
+    }
+
+    /// Returns the tile data of the [`Cell`] in the given layer if the cell is in a loaded chunk that has the layer
+    pub(crate) fn tile_data(&mut self, map_layer: usize, cell: Cell) -> Option<&'a TileData> {
+        if !self.map.contains_cell(cell) {
+            return None;
+        }
+        let chunk = self.chunk(self.map.into_chunk_pos(cell))?;
+        chunk
+            .layer(map_layer)?
+            .get_tile_data(MapChunk::into_chunk_cell(cell, &chunk.chunk_settings))
+    }
+}
+
+/// Returns whether the [`Cell`] is within the dimensions of the chunk at the given [`ChunkPos`]. Chunks on the far
+/// edges of the map can be smaller than the max chunk size, so cells past the edge of the map still map to them
+pub(crate) fn chunk_contains_cell(
+    map: &impl MapData,
+    chunk_pos: ChunkPos,
+    dimensions: UVec2,
+    cell: Cell,
+) -> bool {
+    let [column, row] = map.cell_to_offset_coordinates(cell);
+    let max_chunk_size = map.max_chunk_size().as_ivec2();
+    let dimensions = dimensions.as_ivec2();
+    column - chunk_pos.x() * max_chunk_size.x < dimensions.x
+        && row - chunk_pos.y() * max_chunk_size.y < dimensions.y
+}
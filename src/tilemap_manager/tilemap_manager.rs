@@ -1,12 +1,15 @@
-use crate::map::chunk::{Chunk, ChunkLayer, ChunkPos};
-use crate::map::{MapData, MapLayer, Tilemap};
-use crate::tilemap_manager::TilemapManagerError;
-use crate::tilemap_manager::{LayerIndex, MapEntity};
+use crate::map::chunk::{Chunk, ChunkLayer, ChunkLayerType, ChunkPos};
+use crate::map::{Connectivity, MapData, MapLayer, TileEntity, Tilemap};
+use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
+use crate::tilemap_manager::resize::{rebuild_tilemap, register_chunk_rebuilder, ChunkRebuilders};
+use crate::tilemap_manager::{chunk_contains_cell, TileReader, TilemapManagerError};
+use crate::tilemap_manager::{LayerIndex, MapAnchor, MapEntity};
+use bevy::ecs::entity::Entities;
 use bevy::ecs::system::SystemParam;
 use bevy::math::UVec2;
-use bevy::prelude::{Children, Commands, DespawnRecursiveExt, Entity, Local, Query};
+use bevy::prelude::{Children, Commands, DespawnRecursiveExt, Entity, Local, Query, World};
+use bevy::utils::HashMap;
 use lettuces::cell::Cell;
-use std::hash::Hash;
 use std::ops::Deref;
 
 /// A [`SystemParam`] used to access and interact with a [`Tilemap`]
@@ -16,13 +19,19 @@ use std::ops::Deref;
 /// You **MUST** set the [TilemapManager] to a specific tilemap using [`set_tilemap_entity()`](TilemapManager::set_tilemap_entity) before you use the Tilemap Manager.
 /// If you don't the functions on this manager will panic.
 ///
+/// Managers for different `TileData` types can be used in the same system to access the typed layers added with
+/// [`TilemapBuilder::add_typed_layer`](crate::tilemap_builder::TilemapBuilder::add_typed_layer). Accessing a typed
+/// layer with a manager for a different `TileData` returns [`TilemapManagerError::LayerTileDataMismatch`].
+///
 /// # Internal [`SystemParam`]s
-/// - `Query<(Entity, &mut Tilemap, Option<&'static Children>)>`
-/// - `Query<(Entity, &mut Chunk<TileData>, Option<&'static Children>)>`
+/// - `Query<(Entity, &Tilemap, &Map, Option<&'static Children>)>`
+/// - `Query<(Entity, &mut Chunk<MapChunk, TileData>, Option<&'static Children>)>`
+/// - `Query<&ChunkRebuilders>`
+/// - `&Entities`
 #[derive(SystemParam)]
 pub struct TilemapManager<'w, 's, TileData, MapLayers, MapChunk, Map>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+    TileData: Clone + Sized + Send + Sync + 'static,
     MapLayers: MapLayer + Default + Clone + Copy + Send + Sync + 'static,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
     Map: MapData,
@@ -32,7 +41,7 @@ where
         's,
         (
             Entity,
-            &'static mut Tilemap,
+            &'static Tilemap,
             &'static Map,
             Option<&'static Children>,
         ),
@@ -46,7 +55,9 @@ where
             Option<&'static Children>,
         ),
     >,
+    rebuilders_query: Query<'w, 's, &'static ChunkRebuilders>,
     commands: Commands<'w, 's>,
+    entities: &'w Entities,
     layer_index: Local<'s, LayerIndex<MapLayers>>,
     map_entity: Local<'s, MapEntity>,
 }
@@ -54,7 +65,7 @@ where
 impl<'w, 's, TileData, MapLayers, MapChunk, Map>
     TilemapManager<'w, 's, TileData, MapLayers, MapChunk, Map>
 where
-    TileData: Hash + Clone + Copy + Sized + Default + Send + Sync + 'static,
+    TileData: Clone + Sized + Send + Sync + 'static,
     MapLayers: MapLayer + Default + Clone + Copy + Send + Sync + 'static,
     MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
     Map: MapData,
@@ -84,7 +95,8 @@ where
         *self.layer_index = LayerIndex(map_layer)
     }
 
-    /// Returns the [`Tilemap`]s dimensions.
+    /// Returns the [`Tilemap`]s dimensions. For maps that aren't rectangles this is the size of the rectangle around
+    /// the map, see [`cell_count`](TilemapManager::cell_count) for the amount of cells in the map.
     pub fn dimensions(&self) -> Result<UVec2, TilemapManagerError> {
         let (_, tilemap, _map, _) = self.tilemap_query.get(
             self.map_entity
@@ -94,33 +106,28 @@ where
         )?;
 
         let chunks = tilemap.chunks().chunk_counts();
+        let origin = tilemap.chunks().origin();
         let average_chunk_size = self
             .chunk_query
-            .get(
-                tilemap
-                    .get_chunk(ChunkPos::new(0, 0))
-                    .ok_or(TilemapManagerError::InvalidChunkPos)?,
-            )?
+            .get(chunk_entity(tilemap, origin)?)?
             .1
             .get_chunk_dimensions();
 
         let max_x_chunk = self
             .chunk_query
-            .get(
-                tilemap
-                    .get_chunk(ChunkPos::new((chunks.x - 1) as i32, 0))
-                    .ok_or(TilemapManagerError::InvalidChunkPos)?,
-            )?
+            .get(chunk_entity(
+                tilemap,
+                ChunkPos::new(origin.x() + (chunks.x - 1) as i32, origin.y()),
+            )?)?
             .1
             .get_chunk_dimensions();
 
         let max_y_chunk = self
             .chunk_query
-            .get(
-                tilemap
-                    .get_chunk(ChunkPos::new(0, (chunks.y - 1) as i32))
-                    .ok_or(TilemapManagerError::InvalidChunkPos)?,
-            )?
+            .get(chunk_entity(
+                tilemap,
+                ChunkPos::new(origin.x(), origin.y() + (chunks.y - 1) as i32),
+            )?)?
             .1
             .get_chunk_dimensions();
 
@@ -130,19 +137,31 @@ where
         ))
     }
 
-    /// Gets the tile data for the given [`Cell`] if it exists.
-    pub fn get_tile_data(&self, cell: Cell) -> Result<TileData, TilemapManagerError> {
-        let (_, tilemap, map, _) = self.tilemap_query.get(
+    /// Returns the amount of cells that are part of the [`Tilemap`]
+    pub fn cell_count(&self) -> Result<usize, TilemapManagerError> {
+        let dimensions = self.dimensions()?;
+        let (_, _, map, _) = self.tilemap_query.get(
             self.map_entity
                 .deref()
                 .0
                 .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        let (_, chunk, _) = self.chunk_query.get(
-            tilemap
-                .get_chunk_for_cell(cell, map)
-                .ok_or(TilemapManagerError::InvalidChunkPos)?,
+        Ok(map.cell_count(dimensions))
+    }
+
+    /// Gets a reference to the tile data for the given [`Cell`] if it exists.
+    pub fn get_tile_data(&self, cell: Cell) -> Result<&TileData, TilemapManagerError> {
+        let (_, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
         )?;
+        let (_, chunk, _) = self
+            .chunk_query
+            .get(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, chunk)?;
+        ensure_chunk_has_layer(tilemap, chunk, self.layer_index.0)?;
         chunk
             .get_tile_data(
                 self.layer_index.0,
@@ -151,6 +170,25 @@ where
             .ok_or(TilemapManagerError::TileDataDoesNotExist)
     }
 
+    /// Gets a mutable reference to the tile data for the given [`Cell`] if it exists.
+    pub fn get_tile_data_mut(&mut self, cell: Cell) -> Result<&mut TileData, TilemapManagerError> {
+        let (_, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        let (_, chunk, _) = self
+            .chunk_query
+            .get_mut(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, &chunk)?;
+        ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
+        chunk
+            .into_inner()
+            .get_tile_data_mut_from_cell(self.layer_index.0, cell)
+            .ok_or(TilemapManagerError::TileDataDoesNotExist)
+    }
+
     /// Sets the tile data for the given [`Cell`] if it exists.
     pub fn sets_tile_data(
         &mut self,
@@ -163,15 +201,251 @@ where
                 .0
                 .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        let (_, mut chunk, _) = self.chunk_query.get_mut(
-            tilemap
-                .get_chunk_for_cell(cell, map)
-                .ok_or(TilemapManagerError::InvalidChunkPos)?,
+        let (_, mut chunk, _) = self
+            .chunk_query
+            .get_mut(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, &chunk)?;
+        ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
+        chunk.set_tile_data_from_cell(self.layer_index.0.to_index(), cell, tile_data);
+        Ok(())
+    }
+
+    /// Removes the tile data for the given [`Cell`] and returns it.
+    ///
+    /// # Note
+    /// - Tiles in dense and palette layers are reset to the default tile data of the layer instead, layers without
+    ///   default tile data return [`TilemapManagerError::TileDataDoesNotExist`] and keep the tile
+    pub fn take_tile_data(&mut self, cell: Cell) -> Result<TileData, TilemapManagerError> {
+        let (_, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        let (_, mut chunk, _) = self
+            .chunk_query
+            .get_mut(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, &chunk)?;
+        ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
+        chunk
+            .take_tile_data_from_cell(self.layer_index.0.to_index(), cell)
+            .ok_or(TilemapManagerError::TileDataDoesNotExist)
+    }
+
+    /// Removes the tile data for the given [`Cell`] if it exists.
+    ///
+    /// # Note
+    /// - Tiles in dense and palette layers are reset to the default tile data of the layer instead, or kept if the
+    ///   layer has no default tile data
+    pub fn remove_tile_data(&mut self, cell: Cell) -> Result<(), TilemapManagerError> {
+        let (_, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        let (_, mut chunk, _) = self
+            .chunk_query
+            .get_mut(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, &chunk)?;
+        ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
+        chunk.remove_tile_data_from_cell(self.layer_index.0.to_index(), cell);
+        Ok(())
+    }
+
+    /// Returns the neighbours of the given [`Cell`] with the given [`Connectivity`] that have tile data in the current
+    /// layer along with their tile data. The neighbours are found with [`MapData::neighbours`], so they match the
+    /// shape of the map type.
+    ///
+    /// Each chunk the neighbours are in is only looked up once, including chunks across a chunk boundary.
+    ///
+    /// # Note
+    /// - Neighbours outside of the map or in chunks that are not loaded are skipped
+    pub fn neighbours(
+        &self,
+        cell: Cell,
+        connectivity: Connectivity,
+    ) -> Result<Vec<(Cell, &TileData)>, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer_index.0.to_index();
+        Ok(tile_reader
+            .map
+            .neighbours(cell, connectivity)
+            .into_iter()
+            .filter_map(|neighbour| {
+                tile_reader
+                    .tile_data(map_layer, neighbour)
+                    .map(|tile_data| (neighbour, tile_data))
+            })
+            .collect())
+    }
+
+    /// Returns a [`TileReader`] over the tilemap that reads tiles straight from the loaded chunks
+    pub(crate) fn tile_reader(
+        &self,
+    ) -> Result<TileReader<'_, TileData, MapChunk, Map>, TilemapManagerError> {
+        let (_, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        Ok(TileReader::new(tilemap, map, |chunk_entity| {
+            self.chunk_query
+                .get(chunk_entity)
+                .ok()
+                .map(|(_, chunk, _)| chunk)
+        }))
+    }
+
+    /// Returns the given [`Cell`]s that are part of the map and in a loaded chunk, in the same order. Use it to clip
+    /// the lines and shapes of [`geometry`](crate::geometry) to the tilemap
+    pub fn clip_cells(
+        &self,
+        cells: impl IntoIterator<Item = Cell>,
+    ) -> Result<Vec<Cell>, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        Ok(cells
+            .into_iter()
+            .filter(|cell| tile_reader.contains(*cell))
+            .collect())
+    }
+
+    /// Returns the tile data in the current layer of every given [`Cell`] that has tile data, in the same order.
+    ///
+    /// Each chunk is only looked up once, so it's much faster than calling
+    /// [`get_tile_data`](Self::get_tile_data) for every cell of a line or shape.
+    ///
+    /// # Note
+    /// - Cells outside of the map or in chunks that are not loaded are skipped
+    pub fn get_tiles(
+        &self,
+        cells: impl IntoIterator<Item = Cell>,
+    ) -> Result<Vec<(Cell, &TileData)>, TilemapManagerError> {
+        let mut tile_reader = self.tile_reader()?;
+        let map_layer = self.layer_index.0.to_index();
+        Ok(cells
+            .into_iter()
+            .filter_map(|cell| {
+                tile_reader
+                    .tile_data(map_layer, cell)
+                    .map(|tile_data| (cell, tile_data))
+            })
+            .collect())
+    }
+
+    /// Sets the tile data in the current layer of every given [`Cell`] to a clone of the given tile data.
+    ///
+    /// See [`set_tiles_with`](Self::set_tiles_with)
+    pub fn set_tiles(
+        &mut self,
+        cells: impl IntoIterator<Item = Cell>,
+        tile_data: TileData,
+    ) -> Result<(), TilemapManagerError> {
+        self.set_tiles_with(cells, |_| tile_data.clone())
+    }
+
+    /// Sets the tile data in the current layer of every given [`Cell`] to the tile data returned for it.
+    ///
+    /// The cells are grouped by chunk first, so each chunk is only looked up and marked as changed once.
+    ///
+    /// # Note
+    /// - Cells outside of the map or in chunks that are not loaded are skipped
+    pub fn set_tiles_with(
+        &mut self,
+        cells: impl IntoIterator<Item = Cell>,
+        mut tile_data: impl FnMut(Cell) -> TileData,
+    ) -> Result<(), TilemapManagerError> {
+        let chunk_cells = {
+            let mut tile_reader = self.tile_reader()?;
+            let mut chunk_cells: HashMap<ChunkPos, Vec<Cell>> = HashMap::default();
+            for cell in cells {
+                if tile_reader.contains(cell) {
+                    chunk_cells
+                        .entry(tile_reader.map.into_chunk_pos(cell))
+                        .or_default()
+                        .push(cell);
+                }
+            }
+            chunk_cells
+        };
+        let (_, tilemap, _, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        let map_layer = self.layer_index.0.to_index();
+        for (chunk_pos, cells) in chunk_cells {
+            let (_, mut chunk, _) = self
+                .chunk_query
+                .get_mut(chunk_entity(tilemap, chunk_pos)?)?;
+            ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
+            for cell in cells {
+                chunk.set_tile_data_from_cell(map_layer, cell, tile_data(cell));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes all the tile data in the given [`MapLayer`] across every loaded chunk of the tilemap.
+    ///
+    /// # Note
+    /// - Tiles in dense and palette layers are reset to the default tile data of the layer instead, or kept if the
+    ///   layer has no default tile data
+    /// - Chunks that don't have the layer are skipped
+    pub fn clear_layer(&mut self, map_layer: MapLayers) -> Result<(), TilemapManagerError> {
+        let (_, tilemap, _map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        for (_, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let (_, mut chunk, _) = self.chunk_query.get_mut(chunk_entity)?;
+            if let Some(layer) = chunk.layer_mut(map_layer.to_index()) {
+                layer.clear_tile_data();
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the tile data of every tile in the current layer of the loaded chunks along
+    /// with its [`Cell`]. Sparse layers only visit occupied tiles
+    ///
+    /// # Note
+    /// - Chunks that do not have the current layer are skipped
+    pub fn iter_layer(
+        &self,
+    ) -> Result<impl Iterator<Item = (Cell, &TileData)> + '_, TilemapManagerError> {
+        let (_, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        Ok(chunk.set_tile_data_from_cell(self.layer_index.0.to_bits(), cell, tile_data))
+        let map_layer = self.layer_index.0.to_index();
+        Ok(tilemap
+            .chunks()
+            .loaded_chunks()
+            .filter_map(move |(chunk_pos, chunk_entity)| {
+                let (_, chunk, _) = self.chunk_query.get(chunk_entity).ok()?;
+                Some((chunk_pos, chunk.layer(map_layer)?))
+            })
+            .flat_map(move |(chunk_pos, layer)| {
+                layer
+                    .iter()
+                    .map(move |(chunk_cell, tile_data)| {
+                        (map.into_cell(chunk_pos, chunk_cell), tile_data)
+                    })
+                    .filter(|(cell, _)| map.contains_cell(*cell))
+            }))
     }
 
     /// Gets the [`Entity`] for the given [`Cell`] if it exists.
+    ///
+    /// # Note
+    /// - Entities that were despawned but are still mapped in the chunk are treated as not existing
     pub fn get_tile_entity(&self, cell: Cell) -> Result<Entity, TilemapManagerError> {
         let (_, tilemap, map, _) = self.tilemap_query.get(
             self.map_entity
@@ -179,42 +453,51 @@ where
                 .0
                 .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        let (_, chunk, _) = self.chunk_query.get(
-            tilemap
-                .get_chunk_for_cell(cell, map)
-                .ok_or(TilemapManagerError::InvalidChunkPos)?,
-        )?;
+        let (_, chunk, _) = self
+            .chunk_query
+            .get(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, chunk)?;
+        ensure_chunk_has_layer(tilemap, chunk, self.layer_index.0)?;
         chunk
             .get_tile_entity(
                 self.layer_index.0,
                 MapChunk::into_chunk_cell(cell, &chunk.chunk_settings),
             )
+            .filter(|entity| self.entities.contains(*entity))
             .ok_or(TilemapManagerError::TileEntityDoesNotExist)
     }
 
     /// Sets the [`Entity`] for the given [`Cell`]. Prefer to use [`get_or_spawn_tile_entity`](TilemapManager::get_or_spawn_tile_entity).
+    ///
+    /// # Note
+    /// - Inserts a [`TileEntity`] component on the entity so that it is unmapped when despawned
     pub fn set_tile_entity(
         &mut self,
         cell: Cell,
         entity: Entity,
     ) -> Result<(), TilemapManagerError> {
-        let (_, tilemap, map, _) = self.tilemap_query.get(
+        let (tilemap_entity, tilemap, map, _) = self.tilemap_query.get(
             self.map_entity
                 .deref()
                 .0
                 .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        let (_, mut chunk, _) = self.chunk_query.get_mut(
-            tilemap
-                .get_chunk_for_cell(cell, map)
-                .ok_or(TilemapManagerError::InvalidChunkPos)?,
-        )?;
+        let (_, mut chunk, _) = self
+            .chunk_query
+            .get_mut(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, &chunk)?;
+        ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
         let chunk_conversion_settings = chunk.chunk_settings;
         chunk.set_tile_entity(
-            self.layer_index.0.to_bits(),
+            self.layer_index.0.to_index(),
             MapChunk::into_chunk_cell(cell, &chunk_conversion_settings),
             entity,
         );
+        self.commands.entity(entity).insert(TileEntity {
+            tilemap: tilemap_entity,
+            cell,
+            layer: self.layer_index.0.to_index(),
+        });
 
         Ok(())
     }
@@ -222,34 +505,42 @@ where
     /// Gets the [`Entity`] for the given [`Cell`] if it exists or spawns one and returns that if it
     /// doesn't.
     pub fn get_or_spawn_tile_entity(&mut self, cell: Cell) -> Result<Entity, TilemapManagerError> {
-        let (_, tilemap, map, _) = self.tilemap_query.get(
+        let (tilemap_entity, tilemap, map, _) = self.tilemap_query.get(
             self.map_entity
                 .deref()
                 .0
                 .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        let (_, mut chunk, _) = self.chunk_query.get_mut(
-            tilemap
-                .get_chunk_for_cell(cell, map)
-                .ok_or(TilemapManagerError::InvalidChunkPos)?,
-        )?;
+        let (_, mut chunk, _) = self
+            .chunk_query
+            .get_mut(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, &chunk)?;
+        ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
 
         let entity = chunk
             .get_tile_entity(
                 self.layer_index.0,
                 MapChunk::into_chunk_cell(cell, &chunk.chunk_settings),
             )
+            .filter(|entity| self.entities.contains(*entity))
             .unwrap_or_else(|| {
-                let entity = self.commands.spawn_empty().id();
-                chunk.set_tile_entity_from_cell(self.layer_index.0.to_bits(), cell, entity);
+                let layer = self.layer_index.0.to_index();
+                let entity = self
+                    .commands
+                    .spawn(TileEntity {
+                        tilemap: tilemap_entity,
+                        cell,
+                        layer,
+                    })
+                    .id();
+                chunk.set_tile_entity_from_cell(layer, cell, entity);
                 entity
             });
 
         Ok(entity)
     }
 
-    /// Gets the [`Entity`] for the given [`Cell`] if it exists or spawns one and returns that if it
-    /// doesn't.
+    /// Despawns the [`Entity`] for the given [`Cell`] if it exists and removes it from the chunk.
     pub fn despawn_tile_entity(&mut self, cell: Cell) -> Result<(), TilemapManagerError> {
         let (_, tilemap, map, _) = self.tilemap_query.get(
             self.map_entity
@@ -257,19 +548,412 @@ where
                 .0
                 .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        let (_, chunk, _) = self.chunk_query.get(
-            tilemap
-                .get_chunk_for_cell(cell, map)
-                .ok_or(TilemapManagerError::InvalidChunkPos)?,
+        let (_, mut chunk, _) = self
+            .chunk_query
+            .get_mut(cell_chunk_entity(tilemap, map, cell)?)?;
+        ensure_chunk_contains_cell(map, cell, &chunk)?;
+        ensure_chunk_has_layer(tilemap, &chunk, self.layer_index.0)?;
+
+        if let Some(entity) =
+            chunk.remove_tile_entity_from_cell(self.layer_index.0.to_index(), cell)
+        {
+            if self.entities.contains(entity) {
+                self.commands.entity(entity).despawn_recursive();
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Adds the given layer to every chunk of the [`Tilemap`], splitting its data across the chunks the same way the
+    /// [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder) does. The layer must be the same size as the tilemap.
+    ///
+    /// Chunks without a [`Chunk`] component for this managers `TileData` get a new one, which makes the layer a typed
+    /// layer like the ones added with
+    /// [`TilemapBuilder::add_typed_layer`](crate::tilemap_builder::TilemapBuilder::add_typed_layer). The new
+    /// components use the chunk settings created from the tilemaps [`MapData`].
+    ///
+    /// # Note
+    /// - Every chunk of the tilemap must be loaded
+    /// - Tiles of sparse layers outside of the tilemap are ignored
+    /// - The layer must use the storage declared on its [`MapLayer`], if any
+    /// - New chunk components are inserted when the commands of the system are applied
+    pub fn add_layer(
+        &mut self,
+        map_layer: MapLayers,
+        tilemap_layer: TilemapLayer<TileData>,
+    ) -> Result<(), TilemapManagerError>
+    where
+        MapChunk::ChunkSettings: for<'a> From<&'a Map>,
+    {
+        if let Some(storage) = map_layer.storage() {
+            if storage != tilemap_layer.storage() {
+                return Err(TilemapManagerError::LayerStorageMismatch(
+                    storage,
+                    tilemap_layer.storage(),
+                ));
+            }
+        }
+        let (tilemap_entity, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
         )?;
+        if let Some(chunk_pos) = tilemap.chunks().unloaded_chunks().next() {
+            return Err(TilemapManagerError::ChunkNotLoaded(chunk_pos));
+        }
+        let max_chunk_size = tilemap.get_chunks_max_size();
+        // Without chunk components for this `TileData` the layer can only be checked against the chunk counts
+        let map_size = self
+            .dimensions()
+            .unwrap_or_else(|_| tilemap_layer.dimensions());
+        if tilemap_layer.dimensions() != map_size
+            || UVec2::new(
+                map_size.x.div_ceil(max_chunk_size.x),
+                map_size.y.div_ceil(max_chunk_size.y),
+            ) != tilemap.chunks().chunk_counts()
+        {
+            return Err(TilemapManagerError::LayerSizeMismatch(
+                map_size,
+                tilemap_layer.dimensions(),
+            ));
+        }
+        let layer = map_layer.to_index();
+        let mut typed = true;
+        for (_, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let Ok((_, chunk, _)) = self.chunk_query.get(chunk_entity) else {
+                continue;
+            };
+            if chunk.has_layer(layer) {
+                return Err(TilemapManagerError::LayerAlreadyExists);
+            }
+            typed &= chunk
+                .layers()
+                .all(|(map_layer, _)| tilemap.typed_layers().contains_index(map_layer));
+        }
+
+        // The layer is built in its own chunks first so chunks without a component can be given one
+        let origin = tilemap.chunks().origin();
+        let mut new_chunks: HashMap<Entity, Chunk<MapChunk, TileData>> = HashMap::new();
+        for (chunk_pos, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let chunk_index = UVec2::new(
+                (chunk_pos.x() - origin.x()) as u32,
+                (chunk_pos.y() - origin.y()) as u32,
+            );
+            let (chunk_dimensions, chunk_settings) = match self.chunk_query.get(chunk_entity) {
+                Ok((_, chunk, _)) => (chunk.get_chunk_dimensions(), chunk.chunk_settings),
+                Err(_) => (
+                    max_chunk_size.min(map_size - chunk_index * max_chunk_size),
+                    MapChunk::with_max_chunk_size(
+                        MapChunk::ChunkSettings::from(map),
+                        max_chunk_size,
+                    ),
+                ),
+            };
+            let layer_type = match &tilemap_layer {
+                TilemapLayer::Sparse(..) => ChunkLayerType::Sparse(HashMap::new()),
+                TilemapLayer::Dense(data, _) | TilemapLayer::Palette(data, ..) => {
+                    let data = map.break_data_vecs_down_into_chunk_data(
+                        data,
+                        ChunkPos::new(chunk_index.x as i32, chunk_index.y as i32),
+                        max_chunk_size,
+                    );
+                    match &tilemap_layer {
+                        TilemapLayer::Palette(_, tile_data_eq, _) => {
+                            ChunkLayerType::Palette(data, *tile_data_eq)
+                        }
+                        _ => ChunkLayerType::Dense(data),
+                    }
+                }
+            };
+            let mut chunk = Chunk::default();
+            chunk.chunk_pos = chunk_pos;
+            chunk.chunk_settings = chunk_settings;
+            chunk.insert_layer(
+                layer,
+                MapChunk::new(layer_type, chunk_dimensions, &chunk_settings),
+            );
+            new_chunks.insert(chunk_entity, chunk);
+        }
 
-        if let Some(entity) = chunk.get_tile_entity(
-            self.layer_index.0,
-            MapChunk::into_chunk_cell(cell, &chunk.chunk_settings),
-        ) {
-            self.commands.entity(entity).despawn_recursive();
+        let cell_chunk = |cell: Cell| {
+            map.contains_cell(cell)
+                .then(|| tilemap.get_chunk_for_cell(cell, map))
+                .flatten()
         };
+        if let TilemapLayer::Sparse(data, ..) = &tilemap_layer {
+            for (cell, tile_data) in data.iter() {
+                let Some(chunk) = cell_chunk(*cell).and_then(|entity| new_chunks.get_mut(&entity))
+                else {
+                    continue;
+                };
+                chunk.set_tile_data_from_cell(layer, *cell, tile_data.clone());
+            }
+        }
+        for (cell, entity) in tilemap_layer.entities() {
+            let Some(chunk) = cell_chunk(*cell).and_then(|entity| new_chunks.get_mut(&entity))
+            else {
+                continue;
+            };
+            chunk.set_tile_entity_from_cell(layer, *cell, *entity);
+            self.commands.entity(*entity).insert(TileEntity {
+                tilemap: tilemap_entity,
+                cell: *cell,
+                layer,
+            });
+        }
+
+        for (chunk_entity, mut new_chunk) in new_chunks {
+            match self.chunk_query.get_mut(chunk_entity) {
+                Ok((_, mut chunk, _)) => {
+                    if let Some(new_layer) = new_chunk.remove_layer(layer) {
+                        chunk.insert_layer(layer, new_layer);
+                    }
+                }
+                Err(_) => {
+                    self.commands.entity(chunk_entity).insert(new_chunk);
+                }
+            }
+        }
+        if typed {
+            self.commands.add(move |world: &mut World| {
+                register_chunk_rebuilder::<TileData, MapLayers, MapChunk, Map>(
+                    world,
+                    tilemap_entity,
+                );
+                if let Some(mut tilemap) = world.get_mut::<Tilemap>(tilemap_entity) {
+                    let mut typed_layers = tilemap.typed_layers();
+                    typed_layers.insert(map_layer);
+                    tilemap.set_typed_layers(typed_layers);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Sets the default tile data of the given [`MapLayer`] in every loaded chunk of the [`Tilemap`].
+    ///
+    /// Dense and palette layers reset removed tiles to their default tile data and can only be converted by a
+    /// [`ConversionPolicy`](crate::map::chunk::ConversionPolicy) if they have one.
+    ///
+    /// # Note
+    /// - Chunks that don't have the layer are skipped
+    pub fn set_default_tile_data(
+        &mut self,
+        map_layer: MapLayers,
+        tile_data: Option<TileData>,
+    ) -> Result<(), TilemapManagerError> {
+        let (_, tilemap, _map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        for (_, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let (_, mut chunk, _) = self.chunk_query.get_mut(chunk_entity)?;
+            if let Some(layer) = chunk.layer_mut(map_layer.to_index()) {
+                layer.set_default_tile_data(tile_data.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets whether the given layer of every loaded chunk of the [`Tilemap`] records its dirty cells, which is
+    /// needed for the layer to send [`TileChanged`](crate::map::TileChanged) events. See
+    /// [`ChunkLayer::set_track_dirty_cells`]
+    ///
+    /// # Note
+    /// - Chunks that are loaded later keep the setting they were saved with
+    pub fn set_track_dirty_cells(
+        &mut self,
+        map_layer: MapLayers,
+        track_dirty_cells: bool,
+    ) -> Result<(), TilemapManagerError> {
+        let (_, tilemap, _map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        for (_, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let (_, mut chunk, _) = self.chunk_query.get_mut(chunk_entity)?;
+            if let Some(layer) = chunk.layer_mut(map_layer.to_index()) {
+                layer.set_track_dirty_cells(track_dirty_cells);
+            }
+        }
+        Ok(())
+    }
 
+    /// Removes the given layer from every loaded chunk of the [`Tilemap`] and despawns its tile entities.
+    ///
+    /// Removing the last layer of a typed layers [`Chunk`] component removes the whole component, see
+    /// [`TilemapBuilder::add_typed_layer`](crate::tilemap_builder::TilemapBuilder::add_typed_layer).
+    ///
+    /// # Errors
+    /// - [`TilemapManagerError::LastLayer`] if the layer is the last layer of the main `TileData` of the tilemap
+    /// - [`TilemapManagerError::LayerTileDataMismatch`] if the layer is a typed layer with a different `TileData`
+    /// - [`TilemapManagerError::LayerDoesNotExist`] if no loaded chunk has the layer
+    pub fn remove_layer(&mut self, map_layer: MapLayers) -> Result<(), TilemapManagerError> {
+        let (tilemap_entity, tilemap, _map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        let layer = map_layer.to_index();
+        let typed = tilemap.typed_layers().contains_index(layer);
+        let mut layer_exists = false;
+        for (_, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let Ok((_, chunk, _)) = self.chunk_query.get(chunk_entity) else {
+                continue;
+            };
+            if chunk.has_layer(layer) {
+                if chunk.layer_count() == 1 && !typed {
+                    return Err(TilemapManagerError::LastLayer);
+                }
+                layer_exists = true;
+            }
+        }
+        if !layer_exists {
+            return Err(match typed {
+                true => TilemapManagerError::LayerTileDataMismatch,
+                false => TilemapManagerError::LayerDoesNotExist,
+            });
+        }
+
+        for (_, chunk_entity) in tilemap.chunks().loaded_chunks() {
+            let Ok((_, mut chunk, _)) = self.chunk_query.get_mut(chunk_entity) else {
+                continue;
+            };
+            let Some(chunk_layer) = chunk.remove_layer(layer) else {
+                continue;
+            };
+            if chunk.layer_count() == 0 {
+                self.commands
+                    .entity(chunk_entity)
+                    .remove::<Chunk<MapChunk, TileData>>();
+            }
+            for (_, entity) in chunk_layer.iter_tile_entities() {
+                if self.entities.contains(entity) {
+                    self.commands.entity(entity).despawn_recursive();
+                }
+            }
+        }
+        if typed {
+            self.commands.add(move |world: &mut World| {
+                if let Some(mut tilemap) = world.get_mut::<Tilemap>(tilemap_entity) {
+                    let mut typed_layers = tilemap.typed_layers();
+                    typed_layers.remove(map_layer);
+                    tilemap.set_typed_layers(typed_layers);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Resizes the [`Tilemap`] to the given size in cells, keeping the given [`MapAnchor`] in place.
+    ///
+    /// Chunks are added or removed as needed and existing tiles are moved along with the anchor. New tiles in
+    /// dense and palette layers are set to `fill` while new tiles in sparse layers are left empty. Tiles and tile
+    /// entities that end up outside of the map are dropped and despawned.
+    ///
+    /// Typed layers with a different `TileData`, see
+    /// [`TilemapBuilder::add_typed_layer`](crate::tilemap_builder::TilemapBuilder::add_typed_layer), are resized
+    /// along with the rest of the tilemap. Their new tiles are set to the default tile data of the layer, dense and
+    /// palette typed layers without one are rebuilt as sparse layers when the map grows.
+    ///
+    /// # Note
+    /// - The tilemap is resized when the commands of the system are applied
+    /// - Every chunk of the tilemap must be loaded
+    /// - Tilemaps with typed layers that were not spawned by the
+    ///   [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder), eg deserialized tilemaps, return
+    ///   [`TilemapManagerError::TypedLayersExist`]
+    /// - Maps whose shape only fits one size, eg hexagon shaped hex maps, return
+    ///   [`TilemapManagerError::MapShapeMismatch`] for any other size
+    pub fn resize(
+        &mut self,
+        new_size: UVec2,
+        anchor: MapAnchor,
+        fill: TileData,
+    ) -> Result<(), TilemapManagerError> {
+        if new_size.x == 0 || new_size.y == 0 {
+            return Err(TilemapManagerError::InvalidMapSize);
+        }
+        let (tilemap_entity, tilemap, map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        if !tilemap.typed_layers().is_empty() && !self.rebuilders_query.contains(tilemap_entity) {
+            return Err(TilemapManagerError::TypedLayersExist);
+        }
+        if let Some(chunk_pos) = tilemap.chunks().unloaded_chunks().next() {
+            return Err(TilemapManagerError::ChunkNotLoaded(chunk_pos));
+        }
+        map.check_layout(new_size, tilemap.chunks().origin())?;
+        let max_chunk_size = tilemap.get_chunks_max_size();
+        let map_size = self.dimensions()?;
+        let offset = anchor.offset(map_size, new_size);
+        self.commands.add(move |world: &mut World| {
+            rebuild_tilemap::<TileData, MapLayers, MapChunk, Map>(
+                world,
+                tilemap_entity,
+                map_size,
+                new_size,
+                max_chunk_size,
+                offset,
+                Some(fill),
+            );
+        });
+        Ok(())
+    }
+
+    /// Redistributes every layer and tile entity of the [`Tilemap`] into chunks of the given maximum size.
+    ///
+    /// # Note
+    /// - The tilemap is rechunked when the commands of the system are applied
+    /// - Every chunk of the tilemap must be loaded
+    /// - The first cell of the map must be a multiple of the new maximum chunk size, which is always the case
+    ///   for maps whose first chunk is at [`ChunkPos`] (0, 0)
+    /// - Typed layers with a different `TileData` are rechunked along with the rest of the tilemap. Tilemaps with
+    ///   typed layers that were not spawned by the [`TilemapBuilder`](crate::tilemap_builder::TilemapBuilder), eg
+    ///   deserialized tilemaps, return [`TilemapManagerError::TypedLayersExist`]
+    pub fn rechunk(&mut self, new_max_chunk_size: UVec2) -> Result<(), TilemapManagerError> {
+        let (tilemap_entity, tilemap, _map, _) = self.tilemap_query.get(
+            self.map_entity
+                .deref()
+                .0
+                .expect("TilemapManager must have a tilemap entity set"),
+        )?;
+        if !tilemap.typed_layers().is_empty() && !self.rebuilders_query.contains(tilemap_entity) {
+            return Err(TilemapManagerError::TypedLayersExist);
+        }
+        let max_chunk_size = tilemap.get_chunks_max_size();
+        let origin = tilemap.chunks().origin();
+        if new_max_chunk_size.x == 0
+            || new_max_chunk_size.y == 0
+            || (origin.x() * max_chunk_size.x as i32).rem_euclid(new_max_chunk_size.x as i32) != 0
+            || (origin.y() * max_chunk_size.y as i32).rem_euclid(new_max_chunk_size.y as i32) != 0
+        {
+            return Err(TilemapManagerError::InvalidChunkSize);
+        }
+        if let Some(chunk_pos) = tilemap.chunks().unloaded_chunks().next() {
+            return Err(TilemapManagerError::ChunkNotLoaded(chunk_pos));
+        }
+        let map_size = self.dimensions()?;
+        self.commands.add(move |world: &mut World| {
+            rebuild_tilemap::<TileData, MapLayers, MapChunk, Map>(
+                world,
+                tilemap_entity,
+                map_size,
+                map_size,
+                new_max_chunk_size,
+                [0, 0],
+                None,
+            );
+        });
         Ok(())
     }
 
@@ -284,25 +968,91 @@ where
                 .0
                 .expect("TilemapManager must have a tilemap entity set"),
         )?;
-        let (_, chunk, _) = self.chunk_query.get(
-            tilemap
-                .get_chunk(chunk_pos)
-                .ok_or(TilemapManagerError::InvalidChunkPos)?,
-        )?;
+        let (_, chunk, _) = self.chunk_query.get(chunk_entity(tilemap, chunk_pos)?)?;
         Ok(chunk)
     }
 }
 
+/// Gets the entity of the chunk at the given [`ChunkPos`], separating chunks outside of the map from
+/// chunks that are not loaded
+fn chunk_entity(tilemap: &Tilemap, chunk_pos: ChunkPos) -> Result<Entity, TilemapManagerError> {
+    match tilemap.get_chunk(chunk_pos) {
+        Some(entity) => Ok(entity),
+        None if tilemap.chunks().contains(chunk_pos) => {
+            Err(TilemapManagerError::ChunkNotLoaded(chunk_pos))
+        }
+        None => Err(TilemapManagerError::InvalidChunkPos),
+    }
+}
+
+/// Gets the entity of the chunk the [`Cell`] is in, rejecting cells that are outside of the shape of the map
+fn cell_chunk_entity(
+    tilemap: &Tilemap,
+    map: &impl MapData,
+    cell: Cell,
+) -> Result<Entity, TilemapManagerError> {
+    if !map.contains_cell(cell) {
+        return Err(TilemapManagerError::CellOutsideMapShape(cell));
+    }
+    chunk_entity(tilemap, map.into_chunk_pos(cell))
+}
+
+/// Rejects cells past the edge of the map that still map to the given chunk because it's on the edge of the map
+fn ensure_chunk_contains_cell<TileData, MapChunk>(
+    map: &impl MapData,
+    cell: Cell,
+    chunk: &Chunk<MapChunk, TileData>,
+) -> Result<(), TilemapManagerError>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+{
+    if !chunk_contains_cell(
+        map,
+        map.into_chunk_pos(cell),
+        chunk.get_chunk_dimensions(),
+        cell,
+    ) {
+        return Err(TilemapManagerError::CellOutsideMap(cell));
+    }
+    Ok(())
+}
+
+/// Returns an error if the given chunk does not have the given layer, eg because it was removed with
+/// [`TilemapManager::remove_layer`] or it is a typed layer with a different `TileData` than the chunk
+fn ensure_chunk_has_layer<TileData, MapChunk>(
+    tilemap: &Tilemap,
+    chunk: &Chunk<MapChunk, TileData>,
+    map_layer: impl MapLayer,
+) -> Result<(), TilemapManagerError>
+where
+    TileData: Clone + Sized + Send + Sync + 'static,
+    MapChunk: ChunkLayer<TileData> + Send + Sync + 'static + Default,
+{
+    let layer = map_layer.to_index();
+    if chunk.has_layer(layer) {
+        return Ok(());
+    }
+    if tilemap.typed_layers().contains_index(layer) {
+        return Err(TilemapManagerError::LayerTileDataMismatch);
+    }
+    Err(TilemapManagerError::LayerDoesNotExist)
+}
+
 #[cfg(test)]
 mod tests {
     use crate as bevy_sparse_tilemap;
+    use crate::geometry;
     use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
     use crate::square::map_data::SquareMapData;
     use crate::square::{SquareTilemapBuilder, SquareTilemapManager};
 
+    use crate::map::chunk::ChunkPos;
+    use crate::map::{Connectivity, TileEntity, Tilemap};
     use crate::tilemap_builder::tilemap_layer_builder::TilemapLayer;
     use crate::tilemap_builder::TilemapBuilder;
     use crate::tilemap_manager::tilemap_manager::TilemapManager;
+    use crate::tilemap_manager::TilemapManagerError;
     use bevy::ecs::system::{Commands, SystemState};
     use bevy::math::UVec2;
     use bevy::prelude::World;
@@ -310,14 +1060,12 @@ mod tests {
     use bst_map_layer_derive::MapLayer;
     use lettuces::cell::Cell;
 
-    #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
-    struct TileData(u8);
-
     #[derive(MapLayer, Default, Debug, PartialEq, Eq, Clone, Copy)]
     enum MapLayers {
         #[default]
         Main,
         Secondary,
+        Overlay,
     }
 
     #[test]
@@ -370,7 +1118,8 @@ mod tests {
             SquareChunkSettings {
                 max_chunk_size: UVec2 { x: 5, y: 5 },
             },
-        );
+        )
+        .unwrap();
 
         let Some(map_entity) = tilemap_builder.spawn_tilemap(&mut commands) else {
             return;
@@ -381,48 +1130,165 @@ mod tests {
         tilemap_manager.set_tilemap_entity(map_entity);
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(0, 0)).unwrap(),
-            (0, 0)
+            &(0, 0)
         );
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(7, 8)).unwrap(),
-            (7, 8)
+            &(7, 8)
         );
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(7, 0)).unwrap(),
-            (7, 0)
+            &(7, 0)
         );
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(0, 6)).unwrap(),
-            (0, 6)
+            &(0, 6)
         );
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(2, 1)).unwrap(),
-            (2, 1)
+            &(2, 1)
         );
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(4, 4)).unwrap(),
-            (4, 4)
+            &(4, 4)
         );
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(7, 8)).unwrap(),
-            (7, 8)
+            &(7, 8)
         );
         // Testing bounds
+        assert!(tilemap_manager.get_tile_data(Cell::new(7, 9)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(8, 7)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(0, 9)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(8, 0)).is_err());
+    }
+
+    #[test]
+    fn tilemap_manager_neighbours() {
+        // Each tile holds its own cell
+        let vecs: Vec<Vec<(i32, i32)>> = (0..9).map(|y| (0..8).map(|x| (x, y)).collect()).collect();
+
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let tilemap_builder = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vecs),
+            SquareMapData {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+        )
+        .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        let sorted_neighbours = |cell: Cell, connectivity: Connectivity| {
+            let mut neighbours: Vec<(i32, i32)> = tilemap_manager
+                .neighbours(cell, connectivity)
+                .unwrap()
+                .into_iter()
+                .map(|(neighbour, tile_data)| {
+                    assert_eq!((neighbour.x, neighbour.y), *tile_data);
+                    *tile_data
+                })
+                .collect();
+            neighbours.sort();
+            neighbours
+        };
+
+        // (4, 4) is the last cell of the first chunk so its neighbours are spread over 4 chunks
         assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(7, 9)).is_err(),
-            true
+            sorted_neighbours(Cell::new(4, 4), Connectivity::Edges),
+            vec![(3, 4), (4, 3), (4, 5), (5, 4)]
         );
         assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(8, 7)).is_err(),
-            true
+            sorted_neighbours(Cell::new(4, 4), Connectivity::Vertices),
+            vec![
+                (3, 3),
+                (3, 4),
+                (3, 5),
+                (4, 3),
+                (4, 5),
+                (5, 3),
+                (5, 4),
+                (5, 5)
+            ]
         );
+        // Neighbours outside of the map are skipped, including ones in chunks that exist but are smaller
         assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(0, 9)).is_err(),
-            true
+            sorted_neighbours(Cell::new(0, 0), Connectivity::Vertices),
+            vec![(0, 1), (1, 0), (1, 1)]
         );
         assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(8, 0)).is_err(),
-            true
+            sorted_neighbours(Cell::new(7, 8), Connectivity::Edges),
+            vec![(6, 8), (7, 7)]
+        );
+    }
+
+    #[test]
+    fn tilemap_manager_bulk_access() {
+        let mut world = World::new();
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<u32, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+        let tilemap_builder = SquareTilemapBuilder::<u32, MapLayers>::new(
+            TilemapLayer::new_dense_default(8, 9),
+            SquareMapData {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+        )
+        .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        // The line crosses 3 chunks and leaves the map at (8, 8)
+        let line = || geometry::line(Cell::new(0, 0), Cell::new(8, 8));
+        assert_eq!(tilemap_manager.clip_cells(line()).unwrap().len(), 8);
+        tilemap_manager.set_tiles(line(), 1).unwrap();
+        tilemap_manager
+            .set_tiles_with(
+                geometry::rectangle(Cell::new(6, 0), Cell::new(7, 1)),
+                |cell| (cell.x + cell.y) as u32,
+            )
+            .unwrap();
+        assert_eq!(tilemap_manager.get_tile_data(Cell::new(7, 7)).unwrap(), &1);
+        assert_eq!(tilemap_manager.get_tile_data(Cell::new(7, 1)).unwrap(), &8);
+        assert_eq!(tilemap_manager.get_tile_data(Cell::new(6, 7)).unwrap(), &0);
+
+        let tiles = tilemap_manager
+            .get_tiles(geometry::circle(Cell::new(5, 5), 2))
+            .unwrap();
+        assert_eq!(tiles.len(), 13);
+        assert_eq!(
+            tiles
+                .iter()
+                .filter(|(_, tile_data)| **tile_data == 1)
+                .count(),
+            3
+        );
+        // Cells outside of the map are skipped
+        let tiles = tilemap_manager
+            .get_tiles(geometry::circle(Cell::new(0, 0), 1))
+            .unwrap();
+        assert_eq!(
+            tiles,
+            vec![
+                (Cell::new(0, 0), &1),
+                (Cell::new(1, 0), &0),
+                (Cell::new(0, 1), &0)
+            ]
         );
     }
 
@@ -459,7 +1325,8 @@ mod tests {
                 max_chunk_size: UVec2::new(5, 5),
             },
             chunk_settings,
-        );
+        )
+        .unwrap();
 
         let Some(map_entity) = tilemap_builder.spawn_tilemap(&mut commands) else {
             return;
@@ -471,29 +1338,27 @@ mod tests {
 
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(0, 0)).unwrap(),
-            (0, 0)
+            &(0, 0)
         );
         assert_eq!(
             tilemap_manager.get_tile_data(Cell::new(31, 31)).unwrap(),
-            (31, 31)
+            &(31, 31)
         );
         // Testing bounds
-        assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(7, 9)).is_err(),
-            true
-        );
-        assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(8, 7)).is_err(),
-            true
-        );
-        assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(0, 9)).is_err(),
-            true
-        );
-        assert_eq!(
-            tilemap_manager.get_tile_data(Cell::new(8, 0)).is_err(),
-            true
-        );
+        assert!(tilemap_manager.get_tile_data(Cell::new(7, 9)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(8, 7)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(0, 9)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(8, 0)).is_err());
+        // The last chunk of each row is only 2 cells wide, cells past the edge of the map are rejected
+        assert!(matches!(
+            tilemap_manager.sets_tile_data((33, 0), Cell::new(33, 0)),
+            Err(TilemapManagerError::CellOutsideMap(_))
+        ));
+        assert!(matches!(
+            tilemap_manager.get_tile_data(Cell::new(31, 33)),
+            Err(TilemapManagerError::CellOutsideMap(_))
+        ));
+        assert_eq!(tilemap_manager.iter_layer().unwrap().count(), 2);
     }
 
     #[test]
@@ -527,7 +1392,8 @@ mod tests {
                 max_chunk_size: UVec2::new(5, 5),
             },
             chunk_settings,
-        );
+        )
+        .unwrap();
 
         let Some(map_entity) = tilemap_builder.spawn_tilemap(&mut commands) else {
             return;
@@ -539,4 +1405,557 @@ mod tests {
 
         assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(8, 9));
     }
+
+    #[test]
+    fn tilemap_manager_negative_origin_access() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        // Each tile holds its own cell, the map covers -5:-5 -> 2:3
+        let mut vecs = vec![];
+        for y in -5..4 {
+            let mut row = vec![];
+            for x in -5..3 {
+                row.push((x, y));
+            }
+            vecs.push(row);
+        }
+
+        let mut tilemap_builder = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vecs),
+            SquareMapData {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 5, y: 5 },
+            },
+        )
+        .unwrap();
+        tilemap_builder.set_origin(ChunkPos::new(-1, -1)).unwrap();
+
+        let Some(map_entity) = tilemap_builder.spawn_tilemap(&mut commands) else {
+            return;
+        };
+        system_state.apply(&mut world);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        assert_eq!(tilemap_manager.dimensions().unwrap(), UVec2::new(8, 9));
+        for cell in [
+            Cell::new(-5, -5),
+            Cell::new(-1, -1),
+            Cell::new(0, 0),
+            Cell::new(-3, 2),
+            Cell::new(2, 3),
+        ] {
+            assert_eq!(
+                tilemap_manager.get_tile_data(cell).unwrap(),
+                &(cell.x, cell.y)
+            );
+        }
+        // Testing bounds
+        assert!(tilemap_manager.get_tile_data(Cell::new(-6, 0)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(0, -6)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(3, 0)).is_err());
+        assert!(tilemap_manager.get_tile_data(Cell::new(0, 4)).is_err());
+    }
+
+    #[test]
+    fn tilemap_manager_remove_and_clear() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let mut hashmap: HashMap<Cell, (i32, i32)> = HashMap::new();
+        hashmap.insert(Cell::new(1, 1), (1, 1));
+        hashmap.insert(Cell::new(7, 8), (7, 8));
+
+        let mut tilemap_builder = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vec![vec![(1, 1); 8]; 9]),
+            SquareMapData {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 5, y: 5 },
+            },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(8, 9, hashmap),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+
+        let Some(map_entity) = tilemap_builder.spawn_tilemap(&mut commands) else {
+            return;
+        };
+        system_state.apply(&mut world);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        // Dense tiles are kept until the layer has default tile data
+        assert!(matches!(
+            tilemap_manager.take_tile_data(Cell::new(2, 2)),
+            Err(TilemapManagerError::TileDataDoesNotExist)
+        ));
+        tilemap_manager
+            .set_default_tile_data(MapLayers::Main, Some((0, 0)))
+            .unwrap();
+
+        // Dense tiles are reset to the default
+        assert_eq!(
+            tilemap_manager.take_tile_data(Cell::new(2, 2)).unwrap(),
+            (1, 1)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(2, 2)).unwrap(),
+            &(0, 0)
+        );
+        assert!(tilemap_manager.remove_tile_data(Cell::new(10, 10)).is_err());
+
+        // Sparse tiles are emptied
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert_eq!(
+            tilemap_manager.take_tile_data(Cell::new(1, 1)).unwrap(),
+            (1, 1)
+        );
+        assert!(tilemap_manager.get_tile_data(Cell::new(1, 1)).is_err());
+        assert!(tilemap_manager.take_tile_data(Cell::new(1, 1)).is_err());
+        tilemap_manager.remove_tile_data(Cell::new(1, 1)).unwrap();
+
+        tilemap_manager.clear_layer(MapLayers::Secondary).unwrap();
+        assert!(tilemap_manager.get_tile_data(Cell::new(7, 8)).is_err());
+
+        tilemap_manager.clear_layer(MapLayers::Main).unwrap();
+        tilemap_manager.set_layer(MapLayers::Main);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(7, 8)).unwrap(),
+            &(0, 0)
+        );
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(0, 0)).unwrap(),
+            &(0, 0)
+        );
+    }
+
+    #[test]
+    fn tilemap_manager_non_copy_tile_data() {
+        #[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
+        struct TileData {
+            decorations: Vec<u8>,
+            label: Option<String>,
+        }
+
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<TileData, MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let mut hashmap: HashMap<Cell, TileData> = HashMap::new();
+        hashmap.insert(
+            Cell::new(6, 2),
+            TileData {
+                decorations: vec![],
+                label: Some(String::from("Sign")),
+            },
+        );
+
+        let mut tilemap_builder = SquareTilemapBuilder::<TileData, MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vec![
+                vec![
+                    TileData {
+                        decorations: vec![1, 2],
+                        label: None,
+                    };
+                    8
+                ];
+                8
+            ]),
+            SquareMapData {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 5, y: 5 },
+            },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(8, 8, hashmap),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+
+        let Some(map_entity) = tilemap_builder.spawn_tilemap(&mut commands) else {
+            return;
+        };
+        system_state.apply(&mut world);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        assert_eq!(
+            tilemap_manager
+                .get_tile_data(Cell::new(7, 7))
+                .unwrap()
+                .decorations,
+            vec![1, 2]
+        );
+        tilemap_manager
+            .get_tile_data_mut(Cell::new(7, 7))
+            .unwrap()
+            .decorations
+            .push(3);
+        assert_eq!(
+            tilemap_manager
+                .get_tile_data(Cell::new(7, 7))
+                .unwrap()
+                .decorations,
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            tilemap_manager
+                .get_tile_data(Cell::new(6, 7))
+                .unwrap()
+                .decorations,
+            vec![1, 2]
+        );
+
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert_eq!(
+            tilemap_manager
+                .get_tile_data(Cell::new(6, 2))
+                .unwrap()
+                .label
+                .as_deref(),
+            Some("Sign")
+        );
+        assert!(tilemap_manager.get_tile_data_mut(Cell::new(0, 0)).is_err());
+    }
+
+    #[test]
+    fn tilemap_manager_iter_layer() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let mut hashmap: HashMap<Cell, (i32, i32)> = HashMap::new();
+        hashmap.insert(Cell::new(-4, -3), (-4, -3));
+        hashmap.insert(Cell::new(3, 4), (3, 4));
+
+        let mut tilemap_builder = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(
+                (-5..4).map(|y| (-5..3).map(|x| (x, y)).collect()).collect(),
+            ),
+            SquareMapData {
+                max_chunk_size: UVec2::new(5, 5),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 5, y: 5 },
+            },
+        )
+        .unwrap();
+        tilemap_builder.set_origin(ChunkPos::new(-1, -1)).unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_sparse_from_hashmap(8, 9, hashmap),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+
+        let Some(map_entity) = tilemap_builder.spawn_tilemap(&mut commands) else {
+            return;
+        };
+        system_state.apply(&mut world);
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+
+        // Every dense tile knows its own cell
+        let mut tile_count = 0;
+        for (cell, tile_data) in tilemap_manager.iter_layer().unwrap() {
+            assert_eq!(tile_data, &(cell.x, cell.y));
+            tile_count += 1;
+        }
+        assert_eq!(tile_count, 8 * 9);
+
+        // Sparse layers only yield the occupied tiles
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        let mut tiles: Vec<(Cell, (i32, i32))> = tilemap_manager
+            .iter_layer()
+            .unwrap()
+            .map(|(cell, tile_data)| (cell, *tile_data))
+            .collect();
+        tiles.sort_by_key(|(cell, _)| (cell.x, cell.y));
+        assert_eq!(
+            tiles,
+            vec![(Cell::new(-4, -3), (-4, -3)), (Cell::new(3, 4), (3, 4))]
+        );
+    }
+
+    #[test]
+    fn tilemap_manager_add_and_remove_layer() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let map_entity = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vec![vec![(0, 0); 6]; 5]),
+            SquareMapData {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 4, y: 4 },
+            },
+        )
+        .unwrap()
+        .spawn_tilemap(&mut commands)
+        .unwrap();
+        let mut hashmap: HashMap<Cell, (i32, i32)> = HashMap::new();
+        hashmap.insert(Cell::new(5, 4), (5, 4));
+        let mut overlay = TilemapLayer::new_sparse_from_hashmap(6, 5, hashmap);
+        overlay.spawn_entity_at_tile_pos(Cell::new(5, 4), (), &mut commands);
+        let overlay_entity = overlay.entities()[&Cell::new(5, 4)];
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        tilemap_manager
+            .add_layer(
+                MapLayers::Secondary,
+                TilemapLayer::new_dense_from_vecs(
+                    (0..5).map(|y| (0..6).map(|x| (x, y)).collect()).collect(),
+                ),
+            )
+            .unwrap();
+        assert!(matches!(
+            tilemap_manager.add_layer(
+                MapLayers::Secondary,
+                TilemapLayer::new_dense_from_vecs(vec![vec![(0, 0); 6]; 5])
+            ),
+            Err(TilemapManagerError::LayerAlreadyExists)
+        ));
+        assert!(matches!(
+            tilemap_manager.add_layer(MapLayers::Overlay, TilemapLayer::new_sparse_empty(3, 5)),
+            Err(TilemapManagerError::LayerSizeMismatch(..))
+        ));
+        tilemap_manager
+            .add_layer(MapLayers::Overlay, overlay)
+            .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        for cell in [Cell::new(0, 0), Cell::new(5, 2), Cell::new(3, 4)] {
+            assert_eq!(
+                tilemap_manager.get_tile_data(cell).unwrap(),
+                &(cell.x, cell.y)
+            );
+        }
+        tilemap_manager.set_layer(MapLayers::Overlay);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(5, 4)).unwrap(),
+            &(5, 4)
+        );
+        assert!(tilemap_manager.get_tile_data(Cell::new(4, 4)).is_err());
+        assert_eq!(
+            tilemap_manager.get_tile_entity(Cell::new(5, 4)).unwrap(),
+            overlay_entity
+        );
+        assert_eq!(
+            world.get::<TileEntity>(overlay_entity),
+            Some(&TileEntity {
+                tilemap: map_entity,
+                cell: Cell::new(5, 4),
+                layer: MapLayers::Overlay.to_index(),
+            })
+        );
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.remove_layer(MapLayers::Overlay).unwrap();
+        assert!(matches!(
+            tilemap_manager.remove_layer(MapLayers::Overlay),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(!tilemap_manager
+            .get_chunk(ChunkPos::new(1, 1))
+            .unwrap()
+            .has_layer(MapLayers::Overlay.to_index()));
+        tilemap_manager.remove_layer(MapLayers::Main).unwrap();
+        assert!(matches!(
+            tilemap_manager.remove_layer(MapLayers::Secondary),
+            Err(TilemapManagerError::LastLayer)
+        ));
+        system_state.apply(&mut world);
+        assert!(world.get_entity(overlay_entity).is_none());
+    }
+
+    #[test]
+    fn tilemap_manager_removed_layer_access() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(Commands, SquareTilemapManager<(i32, i32), MapLayers>)> =
+            SystemState::new(&mut world);
+        let (mut commands, _) = system_state.get_mut(&mut world);
+
+        let mut tilemap_builder = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vec![vec![(0, 0); 6]; 5]),
+            SquareMapData {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 4, y: 4 },
+            },
+        )
+        .unwrap();
+        tilemap_builder
+            .add_layer(
+                TilemapLayer::new_dense_from_vecs(vec![vec![(1, 1); 6]; 5]),
+                MapLayers::Secondary,
+            )
+            .unwrap();
+        let map_entity = tilemap_builder.spawn_tilemap(&mut commands).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        tilemap_manager.remove_layer(MapLayers::Secondary).unwrap();
+        tilemap_manager.set_layer(MapLayers::Secondary);
+
+        let cell = Cell::new(5, 4);
+        assert!(matches!(
+            tilemap_manager.get_tile_data(cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.get_tile_data_mut(cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.sets_tile_data((2, 2), cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.take_tile_data(cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.remove_tile_data(cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.set_tiles([cell], (2, 2)),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.get_tile_entity(cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.get_or_spawn_tile_entity(cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        let tile_entity = world.spawn_empty().id();
+        let (_, mut tilemap_manager) = system_state.get_mut(&mut world);
+        assert!(matches!(
+            tilemap_manager.set_tile_entity(cell, tile_entity),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+        assert!(matches!(
+            tilemap_manager.despawn_tile_entity(cell),
+            Err(TilemapManagerError::LayerDoesNotExist)
+        ));
+
+        tilemap_manager.set_layer(MapLayers::Main);
+        assert_eq!(tilemap_manager.get_tile_data(cell).unwrap(), &(0, 0));
+    }
+
+    #[test]
+    fn tilemap_manager_add_and_remove_typed_layer() {
+        let mut world = World::new();
+
+        let mut system_state: SystemState<(
+            Commands,
+            SquareTilemapManager<(i32, i32), MapLayers>,
+            SquareTilemapManager<u8, MapLayers>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, ..) = system_state.get_mut(&mut world);
+
+        let map_entity = SquareTilemapBuilder::<(i32, i32), MapLayers>::new(
+            TilemapLayer::new_dense_from_vecs(vec![vec![(0, 0); 6]; 5]),
+            SquareMapData {
+                max_chunk_size: UVec2::new(4, 4),
+            },
+            SquareChunkSettings {
+                max_chunk_size: UVec2 { x: 4, y: 4 },
+            },
+        )
+        .unwrap()
+        .spawn_tilemap(&mut commands)
+        .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager, mut heights) = system_state.get_mut(&mut world);
+        tilemap_manager.set_tilemap_entity(map_entity);
+        heights.set_tilemap_entity(map_entity);
+        heights
+            .add_layer(
+                MapLayers::Secondary,
+                TilemapLayer::new_dense_from_vecs(vec![vec![3; 6]; 5]),
+            )
+            .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager, mut heights) = system_state.get_mut(&mut world);
+        heights.set_layer(MapLayers::Secondary);
+        assert_eq!(heights.get_tile_data(Cell::new(5, 4)).unwrap(), &3);
+        assert_eq!(
+            heights
+                .get_chunk(ChunkPos::new(1, 1))
+                .unwrap()
+                .get_chunk_dimensions(),
+            UVec2::new(2, 1)
+        );
+        tilemap_manager.set_layer(MapLayers::Secondary);
+        assert!(matches!(
+            tilemap_manager.get_tile_data(Cell::new(5, 4)),
+            Err(TilemapManagerError::LayerTileDataMismatch)
+        ));
+        heights
+            .add_layer(MapLayers::Overlay, TilemapLayer::new_sparse_empty(6, 5))
+            .unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager, mut heights) = system_state.get_mut(&mut world);
+        heights.remove_layer(MapLayers::Secondary).unwrap();
+        assert!(matches!(
+            tilemap_manager.remove_layer(MapLayers::Overlay),
+            Err(TilemapManagerError::LayerTileDataMismatch)
+        ));
+        // Removing the last typed layer removes the chunk components of the type
+        heights.remove_layer(MapLayers::Overlay).unwrap();
+        system_state.apply(&mut world);
+
+        let (_, mut tilemap_manager, heights) = system_state.get_mut(&mut world);
+        assert!(heights.get_chunk(ChunkPos::new(0, 0)).is_err());
+        tilemap_manager.set_layer(MapLayers::Main);
+        assert_eq!(
+            tilemap_manager.get_tile_data(Cell::new(5, 4)).unwrap(),
+            &(0, 0)
+        );
+        assert!(world
+            .get::<Tilemap>(map_entity)
+            .unwrap()
+            .typed_layers()
+            .is_empty());
+    }
 }
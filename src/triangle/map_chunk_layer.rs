@@ -0,0 +1,195 @@
+use crate::map::chunk::{
+    ChunkCell, ChunkLayer, ChunkLayerStorage, ChunkLayerType, ConversionPolicy, TileDataEq,
+};
+use crate::square::map_chunk_layer::{SquareChunkLayer, SquareChunkSettings};
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use bevy::math::UVec2;
+use bevy::prelude::{Component, Entity};
+use lettuces::cell::Cell;
+use std::hash::Hash;
+
+#[cfg(feature = "reflect")]
+use bevy::ecs::reflect::ReflectMapEntities;
+#[cfg(feature = "reflect")]
+use bevy::prelude::{Reflect, ReflectComponent};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Settings needed for a triangle chunk.
+#[derive(Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Hash))]
+pub struct TriangleChunkSettings {
+    /// The maximum size that a chunk in the map can be
+    pub max_chunk_size: UVec2,
+}
+
+impl Default for TriangleChunkSettings {
+    fn default() -> Self {
+        Self {
+            max_chunk_size: UVec2 { x: 10, y: 10 },
+        }
+    }
+}
+
+impl TriangleChunkSettings {
+    /// The settings of the square chunk layer that stores the triangles
+    fn square(&self) -> SquareChunkSettings {
+        SquareChunkSettings {
+            max_chunk_size: self.max_chunk_size,
+        }
+    }
+}
+
+/// A struct that holds the chunk map data for the given layer
+///
+/// Each row of triangles is stored as a row of a grid, so the layer stores its data in a [`SquareChunkLayer`]. Whether
+/// a triangle points up or down is decided by its [`Cell`] on the map, see
+/// [`triangle_points_up`](super::triangle_points_up)
+#[derive(Clone, Component, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+#[cfg_attr(feature = "reflect", reflect(MapEntities, Component))]
+pub struct TriangleChunkLayer<T>(SquareChunkLayer<T>)
+where
+    T: Clone + Sized + Send + Sync;
+
+impl<T> Default for TriangleChunkLayer<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    fn default() -> Self {
+        Self(SquareChunkLayer::default())
+    }
+}
+
+impl<T> MapEntities for TriangleChunkLayer<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0.map_entities(entity_mapper);
+    }
+}
+
+impl<T> ChunkLayer<T> for TriangleChunkLayer<T>
+where
+    T: Clone + Sized + Send + Sync,
+{
+    type ChunkSettings = TriangleChunkSettings;
+
+    fn into_chunk_cell(cell: Cell, chunk_settings: &Self::ChunkSettings) -> ChunkCell {
+        SquareChunkLayer::<T>::into_chunk_cell(cell, &chunk_settings.square())
+    }
+
+    fn with_max_chunk_size(_: Self::ChunkSettings, max_chunk_size: UVec2) -> Self::ChunkSettings {
+        Self::ChunkSettings { max_chunk_size }
+    }
+
+    fn new(
+        layer_type: ChunkLayerType<T>,
+        chunk_dimensions: UVec2,
+        chunk_settings: &Self::ChunkSettings,
+    ) -> Self {
+        Self(SquareChunkLayer::new(
+            layer_type,
+            chunk_dimensions,
+            &chunk_settings.square(),
+        ))
+    }
+
+    fn get_chunk_dimensions(&self) -> UVec2 {
+        self.0.get_chunk_dimensions()
+    }
+
+    fn storage(&self) -> ChunkLayerStorage {
+        self.0.storage()
+    }
+
+    fn get_tile_data_mut(&mut self, chunk_cell: ChunkCell) -> Option<&mut T> {
+        self.0.get_tile_data_mut(chunk_cell)
+    }
+
+    fn get_tile_data(&self, chunk_cell: ChunkCell) -> Option<&T> {
+        self.0.get_tile_data(chunk_cell)
+    }
+
+    fn set_tile_data(&mut self, chunk_cell: ChunkCell, tile_data: T) {
+        self.0.set_tile_data(chunk_cell, tile_data);
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (ChunkCell, &T)> + '_> {
+        self.0.iter()
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(ChunkCell, &mut T)) {
+        self.0.for_each_mut(f)
+    }
+
+    fn take_tile_data(&mut self, chunk_cell: ChunkCell) -> Option<T> {
+        self.0.take_tile_data(chunk_cell)
+    }
+
+    fn clear_tile_data(&mut self) {
+        self.0.clear_tile_data();
+    }
+
+    fn default_tile_data(&self) -> Option<&T> {
+        self.0.default_tile_data()
+    }
+
+    fn set_default_tile_data(&mut self, tile_data: Option<T>) {
+        self.0.set_default_tile_data(tile_data);
+    }
+
+    fn dirty_cells(&self) -> Box<dyn Iterator<Item = ChunkCell> + '_> {
+        self.0.dirty_cells()
+    }
+
+    fn clear_dirty_cells(&mut self) {
+        self.0.clear_dirty_cells();
+    }
+
+    fn set_track_dirty_cells(&mut self, track_dirty_cells: bool) {
+        self.0.set_track_dirty_cells(track_dirty_cells);
+    }
+
+    fn tracks_dirty_cells(&self) -> bool {
+        self.0.tracks_dirty_cells()
+    }
+
+    fn get_tile_entity(&self, chunk_cell: ChunkCell) -> Option<Entity> {
+        self.0.get_tile_entity(chunk_cell)
+    }
+
+    fn set_tile_entity(&mut self, chunk_cell: ChunkCell, entity: Entity) {
+        self.0.set_tile_entity(chunk_cell, entity);
+    }
+
+    fn remove_tile_entity(&mut self, chunk_cell: ChunkCell) -> Option<Entity> {
+        self.0.remove_tile_entity(chunk_cell)
+    }
+
+    fn iter_tile_entities(&self) -> Box<dyn Iterator<Item = (ChunkCell, Entity)> + '_> {
+        self.0.iter_tile_entities()
+    }
+
+    fn set_conversion_policy_with(
+        &mut self,
+        conversion_policy: Option<ConversionPolicy>,
+        tile_data_eq: TileDataEq<T>,
+    ) {
+        self.0
+            .set_conversion_policy_with(conversion_policy, tile_data_eq);
+    }
+
+    fn conversion_policy(&self) -> Option<ConversionPolicy> {
+        self.0.conversion_policy()
+    }
+
+    fn tile_data_eq(&self) -> Option<TileDataEq<T>> {
+        self.0.tile_data_eq()
+    }
+}
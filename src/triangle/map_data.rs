@@ -0,0 +1,385 @@
+use bevy::{
+    math::{UVec2, Vec2},
+    prelude::Component,
+};
+
+#[cfg(feature = "reflect")]
+use bevy::prelude::Reflect;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::map::{
+    chunk::{Chunk, ChunkCell, ChunkPos},
+    Connectivity, MapData, MapLayer,
+};
+use crate::square::map_data::SquareMapData;
+use crate::triangle::map_chunk_layer::TriangleChunkSettings;
+use lettuces::cell::Cell;
+
+use super::{edge_neighbours, triangle_points_up, vertex_neighbours};
+
+/// [`MapData`] implementation for a map of alternating up and down pointing triangles. Each row of triangles is a row of
+/// cells, so the map is split into chunks the same way as a square map.
+///
+/// World positions have y pointing up, with the center of the bounding box of cell (0, 0) at the world origin and the
+/// map extending downwards from there. Neighbouring triangles in a row overlap by half of their width
+#[derive(Hash, Component)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Hash))]
+pub struct TriangleMapData {
+    /// The maximum size that a chunk can be in the map
+    pub max_chunk_size: UVec2,
+    /// The width of the base and the height of a single triangle in world units
+    pub tile_size: UVec2,
+}
+
+impl Default for TriangleMapData {
+    fn default() -> Self {
+        Self {
+            max_chunk_size: UVec2::default(),
+            tile_size: UVec2::new(64, 56),
+        }
+    }
+}
+
+impl TriangleMapData {
+    /// Returns the world position of the center of the bounding box of the given [`Cell`]
+    pub fn cell_to_world(&self, cell: Cell) -> Vec2 {
+        Vec2::new(
+            cell.x as f32 * self.tile_size.x as f32 / 2.0,
+            -cell.y as f32 * self.tile_size.y as f32,
+        )
+    }
+
+    /// Returns the [`Cell`] of the triangle that contains the given world position
+    pub fn world_to_cell(&self, world_position: Vec2) -> Cell {
+        let tile_size = self.tile_size.as_vec2();
+        // Distance down from the top of the first row and right from the left edge of the first triangle
+        let down = -world_position.y / tile_size.y + 0.5;
+        let across = world_position.x / (tile_size.x / 2.0) + 1.0;
+        let row = down.floor() as i32;
+        let down_in_row = down - row as f32;
+        // Every half triangle wide column is split diagonally between the triangle starting in it and the one
+        // ending in it
+        let column = across.floor() as i32;
+        let across_in_column = across - column as f32;
+        let left = Cell::new(column - 1, row);
+        let in_left = if triangle_points_up(left) {
+            across_in_column < down_in_row
+        } else {
+            across_in_column < 1.0 - down_in_row
+        };
+        if in_left {
+            left
+        } else {
+            Cell::new(column, row)
+        }
+    }
+
+    /// The [`SquareMapData`] that the rows of the map are split into chunks with
+    fn grid(&self) -> SquareMapData {
+        SquareMapData {
+            max_chunk_size: self.max_chunk_size,
+        }
+    }
+}
+
+impl From<&TriangleMapData> for TriangleChunkSettings {
+    fn from(map: &TriangleMapData) -> Self {
+        Self {
+            max_chunk_size: map.max_chunk_size,
+        }
+    }
+}
+
+impl MapData for TriangleMapData {
+    fn into_chunk_pos(&self, cell: Cell) -> ChunkPos {
+        self.grid().into_chunk_pos(cell)
+    }
+
+    fn into_cell(&self, chunk_pos: ChunkPos, chunk_cell: ChunkCell) -> Cell {
+        self.grid().into_cell(chunk_pos, chunk_cell)
+    }
+
+    fn cell_to_offset_coordinates(&self, cell: Cell) -> [i32; 2] {
+        [cell.x, cell.y]
+    }
+
+    fn offset_coordinates_to_cell(&self, offset_coordinates: [i32; 2]) -> Cell {
+        Cell::new(offset_coordinates[0], offset_coordinates[1])
+    }
+
+    fn neighbours(&self, cell: Cell, connectivity: Connectivity) -> Vec<Cell> {
+        match connectivity {
+            Connectivity::Edges => edge_neighbours(cell).to_vec(),
+            Connectivity::Vertices => vertex_neighbours(cell).to_vec(),
+        }
+    }
+
+    fn distance(&self, from: Cell, to: Cell, connectivity: Connectivity) -> u32 {
+        // A lower bound, each step moves at most one row and one column or two columns along the row
+        let (x, y) = (from.x.abs_diff(to.x), from.y.abs_diff(to.y));
+        match connectivity {
+            Connectivity::Edges => x + y,
+            Connectivity::Vertices => x.div_ceil(2).max(y),
+        }
+    }
+
+    fn max_chunk_size(&self) -> UVec2 {
+        self.max_chunk_size
+    }
+
+    fn set_max_chunk_size(&mut self, max_chunk_size: UVec2) {
+        self.max_chunk_size = max_chunk_size;
+    }
+
+    fn break_data_vecs_down_into_chunk_data<TileData>(
+        &self,
+        data: &[Vec<TileData>],
+        chunk_pos: ChunkPos,
+        max_chunk_size: UVec2,
+    ) -> Vec<Vec<TileData>>
+    where
+        TileData: Clone + Sized + Send + Sync + 'static,
+    {
+        self.grid()
+            .break_data_vecs_down_into_chunk_data(data, chunk_pos, max_chunk_size)
+    }
+
+    fn break_data_vecs_into_chunks<TileData, MapChunk>(
+        &self,
+        data: &[Vec<TileData>],
+        origin: ChunkPos,
+        max_chunk_size: UVec2,
+        chunk_settings: MapChunk::ChunkSettings,
+    ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
+    where
+        TileData: Clone + Sized + Send + Sync + 'static,
+        MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    {
+        self.grid()
+            .break_data_vecs_into_chunks(data, origin, max_chunk_size, chunk_settings)
+    }
+
+    fn break_hashmap_into_chunks<TileData, MapChunk>(
+        &self,
+        map_layer: impl MapLayer,
+        data: &bevy::utils::HashMap<Cell, TileData>,
+        origin: ChunkPos,
+        map_size: UVec2,
+        max_chunk_size: UVec2,
+        chunk_settings: MapChunk::ChunkSettings,
+    ) -> Vec<Vec<Chunk<MapChunk, TileData>>>
+    where
+        TileData: Clone + Sized + Send + Sync + 'static,
+        MapChunk: crate::map::chunk::ChunkLayer<TileData> + Send + Sync + 'static + Default,
+    {
+        self.grid().break_hashmap_into_chunks(
+            map_layer,
+            data,
+            origin,
+            map_size,
+            max_chunk_size,
+            chunk_settings,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_sparse_tilemap;
+    use crate::map::chunk::{ChunkCell, ChunkPos};
+    use crate::map::MapData;
+    use crate::triangle::map_chunk_layer::{TriangleChunkLayer, TriangleChunkSettings};
+    use crate::triangle::map_data::TriangleMapData;
+    use crate::triangle::{edge_neighbours, triangle_points_up, vertex_neighbours};
+    use bevy::math::{UVec2, Vec2};
+    use bevy::utils::HashMap;
+    use bst_map_layer_derive::MapLayer;
+    use lettuces::cell::Cell;
+
+    #[derive(MapLayer, Default)]
+    enum MapLayers {
+        #[default]
+        Main,
+    }
+
+    fn map_data() -> TriangleMapData {
+        TriangleMapData {
+            max_chunk_size: UVec2::new(5, 5),
+            tile_size: UVec2::new(64, 56),
+        }
+    }
+
+    #[test]
+    fn test_vec_breakdown() {
+        #[rustfmt::skip]
+            let vecs = vec![
+            vec![(0, 0), (1, 0), (2, 0), (3, 0),(4, 0), (5, 0), (6, 0), (7, 0)],
+            vec![(0, 1), (1, 1), (2, 1), (3, 1),(4, 1), (5, 1), (6, 1), (7, 1)],
+            vec![(0, 2), (1, 2), (2, 2), (3, 2),(4, 2), (5, 2), (6, 2), (7, 2)],
+            vec![(0, 3), (1, 3), (2, 3), (3, 3),(4, 3), (5, 3), (6, 3), (7, 3)],
+            vec![(0, 4), (1, 4), (2, 4), (3, 4),(4, 4), (5, 4), (6, 4), (7, 4)],
+            vec![(0, 5), (1, 5), (2, 5), (3, 5),(4, 5), (5, 5), (6, 5), (7, 5)],
+            vec![(0, 6), (1, 6), (2, 6), (3, 6),(4, 6), (5, 6), (6, 6), (7, 6)],
+            vec![(0, 7), (1, 7), (2, 7), (3, 7),(4, 7), (5, 7), (6, 7), (7, 7)],
+            vec![(0, 8), (1, 8), (2, 8), (3, 8),(4, 8), (5, 8), (6, 8), (7, 8)]
+        ];
+
+        let max_chunk_size_x = 5;
+        let max_chunk_size_y = 5;
+
+        let map_data = map_data();
+
+        let zero_zero = map_data.break_data_vecs_down_into_chunk_data(
+            &vecs,
+            ChunkPos::new(0, 0),
+            UVec2::new(max_chunk_size_x, max_chunk_size_y),
+        );
+
+        assert_eq!(zero_zero[0][0], (0, 0));
+        assert_eq!(zero_zero[0][4], (4, 0));
+        assert_eq!(zero_zero[4][0], (0, 4));
+        assert_eq!(zero_zero[4][4], (4, 4));
+
+        let one_zero = map_data.break_data_vecs_down_into_chunk_data(
+            &vecs,
+            ChunkPos::new(1, 0),
+            UVec2::new(max_chunk_size_x, max_chunk_size_y),
+        );
+
+        assert_eq!(one_zero[0][0], (5, 0));
+        assert_eq!(one_zero[0][2], (7, 0));
+        assert_eq!(one_zero[4][0], (5, 4));
+        assert_eq!(one_zero[4][2], (7, 4));
+
+        let zero_one = map_data.break_data_vecs_down_into_chunk_data(
+            &vecs,
+            ChunkPos::new(0, 1),
+            UVec2::new(max_chunk_size_x, max_chunk_size_y),
+        );
+        assert_eq!(zero_one[0][0], (0, 5));
+        assert_eq!(zero_one[0][4], (4, 5));
+        assert_eq!(zero_one[3][0], (0, 8));
+        assert_eq!(zero_one[3][4], (4, 8));
+        let one_one = map_data.break_data_vecs_down_into_chunk_data(
+            &vecs,
+            ChunkPos::new(1, 1),
+            UVec2::new(max_chunk_size_x, max_chunk_size_y),
+        );
+        assert_eq!(one_one[0][0], (5, 5));
+        assert_eq!(one_one[0][2], (7, 5));
+        assert_eq!(one_one[3][0], (5, 8));
+        assert_eq!(one_one[3][2], (7, 8));
+    }
+
+    #[test]
+    fn test_hashmap_breakdown() {
+        let mut hashmap = HashMap::new();
+        hashmap.insert(Cell::new(0, 0), (0, 0));
+        hashmap.insert(Cell::new(6, 2), (6, 2));
+        hashmap.insert(Cell::new(7, 8), (7, 8));
+
+        let chunks = map_data()
+            .break_hashmap_into_chunks::<(i32, i32), TriangleChunkLayer<(i32, i32)>>(
+                MapLayers::Main,
+                &hashmap,
+                ChunkPos::new(0, 0),
+                UVec2::new(8, 9),
+                UVec2::new(5, 5),
+                TriangleChunkSettings {
+                    max_chunk_size: UVec2::new(5, 5),
+                },
+            );
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), 2);
+        assert_eq!(chunks[1][1].get_chunk_dimensions(), UVec2::new(3, 4));
+        assert_eq!(
+            chunks[0][0].get_tile_data(MapLayers::Main, ChunkCell::new(0, 0)),
+            Some(&(0, 0))
+        );
+        assert_eq!(
+            chunks[0][1].get_tile_data(MapLayers::Main, ChunkCell::new(1, 2)),
+            Some(&(6, 2))
+        );
+        assert_eq!(
+            chunks[1][1].get_tile_data(MapLayers::Main, ChunkCell::new(2, 3)),
+            Some(&(7, 8))
+        );
+        assert!(chunks[1][1]
+            .get_tile_data(MapLayers::Main, ChunkCell::new(0, 0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        for cell in [
+            Cell::new(0, 0),
+            Cell::new(3, 0),
+            Cell::new(-3, 5),
+            Cell::new(4, -2),
+        ] {
+            let edges = edge_neighbours(cell);
+            let vertices = vertex_neighbours(cell);
+            for neighbour in edges {
+                // Triangles that share an edge always point the other way
+                assert_ne!(triangle_points_up(neighbour), triangle_points_up(cell));
+                assert!(vertices.contains(&neighbour));
+            }
+            for (index, neighbour) in vertices.iter().enumerate() {
+                assert_ne!(*neighbour, cell);
+                assert!(!vertices[index + 1..].contains(neighbour));
+            }
+            // The triangle below an upwards triangle has it as its neighbour above and the other way around
+            for neighbour in edges {
+                assert!(edge_neighbours(neighbour).contains(&cell));
+            }
+            for neighbour in vertices {
+                assert!(vertex_neighbours(neighbour).contains(&cell));
+            }
+        }
+        assert_eq!(
+            edge_neighbours(Cell::new(0, 0)),
+            [Cell::new(-1, 0), Cell::new(1, 0), Cell::new(0, 1)]
+        );
+        assert_eq!(
+            edge_neighbours(Cell::new(1, 0)),
+            [Cell::new(0, 0), Cell::new(2, 0), Cell::new(1, -1)]
+        );
+    }
+
+    #[test]
+    fn test_world_positions() {
+        let map_data = map_data();
+        assert_eq!(
+            map_data.cell_to_world(Cell::new(1, 0)),
+            Vec2::new(32.0, 0.0)
+        );
+        assert_eq!(
+            map_data.cell_to_world(Cell::new(0, 2)),
+            Vec2::new(0.0, -112.0)
+        );
+        for y in -5..5 {
+            for x in -5..5 {
+                let cell = Cell::new(x, y);
+                let center = map_data.cell_to_world(cell);
+                assert_eq!(map_data.world_to_cell(center), cell);
+                // Points just inside of the triangle near its corners
+                let (point, base) = if triangle_points_up(cell) {
+                    (26.0, -27.0)
+                } else {
+                    (-26.0, 27.0)
+                };
+                for offset in [
+                    Vec2::new(0.0, point),
+                    Vec2::new(-30.0, base),
+                    Vec2::new(30.0, base),
+                ] {
+                    assert_eq!(map_data.world_to_cell(center + offset), cell);
+                }
+            }
+        }
+    }
+}
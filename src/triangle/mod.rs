@@ -0,0 +1,63 @@
+use lettuces::cell::Cell;
+use map_chunk_layer::TriangleChunkLayer;
+use map_data::TriangleMapData;
+
+use crate::{map::chunk::Chunk, tilemap_builder::TilemapBuilder, tilemap_manager::TilemapManager};
+
+/// Implements [`ChunkLayer`](crate::map::chunk::ChunkLayer) for a triangle map
+pub mod map_chunk_layer;
+/// Implements [`MapData`](crate::map::MapData) for a triangle map
+pub mod map_data;
+
+/// Type alias for [`TilemapManager`] for the built in triangle map types.
+pub type TriangleTilemapManager<'w, 's, TileData, MapLayers> =
+    TilemapManager<'w, 's, TileData, MapLayers, TriangleChunkLayer<TileData>, TriangleMapData>;
+
+/// Type alias for [`Chunk`] using the built in [`TriangleChunkLayer`]
+pub type TriangleChunk<TileData> = Chunk<TriangleChunkLayer<TileData>, TileData>;
+
+/// Type alias for [`TilemapBuilder`] for the built in triangle map types
+pub type TriangleTilemapBuilder<TileData, MapLayers> =
+    TilemapBuilder<TileData, MapLayers, TriangleChunkLayer<TileData>, TriangleMapData>;
+
+/// Returns whether the triangle at the given [`Cell`] points up. Triangles alternate along each row and column, with
+/// the triangle at (0, 0) pointing up
+pub fn triangle_points_up(cell: Cell) -> bool {
+    (cell.x + cell.y).rem_euclid(2) == 0
+}
+
+/// Returns the 3 triangles that share an edge with the given triangle. The triangles to the left and right and then
+/// the triangle below an upwards triangle or above a downwards triangle
+pub fn edge_neighbours(cell: Cell) -> [Cell; 3] {
+    let vertical = if triangle_points_up(cell) { 1 } else { -1 };
+    [
+        Cell::new(cell.x - 1, cell.y),
+        Cell::new(cell.x + 1, cell.y),
+        Cell::new(cell.x, cell.y + vertical),
+    ]
+}
+
+/// Returns the 12 triangles that share at least one corner with the given triangle, which includes its
+/// [`edge_neighbours`]
+pub fn vertex_neighbours(cell: Cell) -> [Cell; 12] {
+    // The row on the side of the base touches 5 triangles and the row on the side of the point touches 3
+    let (base, point) = if triangle_points_up(cell) {
+        (1, -1)
+    } else {
+        (-1, 1)
+    };
+    [
+        Cell::new(cell.x - 2, cell.y),
+        Cell::new(cell.x - 1, cell.y),
+        Cell::new(cell.x + 1, cell.y),
+        Cell::new(cell.x + 2, cell.y),
+        Cell::new(cell.x - 2, cell.y + base),
+        Cell::new(cell.x - 1, cell.y + base),
+        Cell::new(cell.x, cell.y + base),
+        Cell::new(cell.x + 1, cell.y + base),
+        Cell::new(cell.x + 2, cell.y + base),
+        Cell::new(cell.x - 1, cell.y + point),
+        Cell::new(cell.x, cell.y + point),
+        Cell::new(cell.x + 1, cell.y + point),
+    ]
+}